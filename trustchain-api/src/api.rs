@@ -11,16 +11,31 @@ use ssi::{
 };
 use std::error::Error;
 use trustchain_core::{
-    chain::DIDChain,
+    attestor::AttestationClaims,
+    chain::{Chain, DIDChain},
     holder::Holder,
     issuer::{Issuer, IssuerError},
+    policy::TrustPolicy,
+    report::ChainVerificationReport,
     resolver::{ResolverResult, TrustchainResolver},
+    utils::current_time,
     vc::CredentialError,
     verifier::{Timestamp, Verifier, VerifierError},
     vp::PresentationError,
 };
 use trustchain_ion::{
-    attest::attest_operation, attestor::IONAttestor, create::create_operation, trustchain_resolver,
+    attest::attest_operation,
+    attestor::IONAttestor,
+    chain_proof::{ChainProof, ChainProofError, ChainProofFormat},
+    create::create_operation,
+    revoke::{revoke_operation, Revocation},
+    trustchain_resolver,
+    verifier::TrustchainVerifier,
+};
+use trustchain_sovrin::{
+    anoncreds::{self, AnonCredsError, Presentation as AnonCredsPresentation, PresentationRequest},
+    network::SovrinNetwork,
+    resolver::SovrinDIDResolver,
 };
 
 /// API for Trustchain CLI DID functionality.
@@ -36,10 +51,24 @@ pub trait TrustchainDIDAPI {
     ) -> Result<String, Box<dyn Error>> {
         create_operation(document_state, verbose)
     }
-    /// An uDID attests to a dDID, writing the associated update operation to file in the operations
-    /// path.
-    async fn attest(did: &str, controlled_did: &str, verbose: bool) -> Result<(), Box<dyn Error>> {
-        attest_operation(did, controlled_did, verbose).await
+    /// An uDID attests to a dDID with the given claims (validity period and delegation
+    /// constraints), writing the associated update operation to file in the operations path.
+    async fn attest(
+        did: &str,
+        controlled_did: &str,
+        claims: &AttestationClaims,
+        verbose: bool,
+    ) -> Result<(), Box<dyn Error>> {
+        attest_operation(did, controlled_did, claims, verbose).await
+    }
+    /// An uDID revokes an attestation, writing the update operation of its signed revocation list
+    /// to file in the operations path. Only ION DIDs can publish a revocation list.
+    async fn revoke(
+        did: &str,
+        revocation: &Revocation,
+        verbose: bool,
+    ) -> Result<(), Box<dyn Error>> {
+        revoke_operation(did, revocation, verbose).await
     }
     /// Resolves a given DID using given endpoint.
     async fn resolve(did: &str, resolver: &dyn TrustchainResolver) -> ResolverResult {
@@ -48,16 +77,105 @@ pub trait TrustchainDIDAPI {
     }
 
     /// Verifies a given DID using a resolver available at given endpoint, returning a result.
+    /// Time-bounded attestations are checked at the given verification time, or the current time
+    /// if `None`.
     async fn verify<T, U>(
         did: &str,
         root_event_time: Timestamp,
+        verification_time: Option<Timestamp>,
         verifier: &U,
     ) -> Result<DIDChain, VerifierError>
     where
         T: DIDResolver + Send,
         U: Verifier<T> + Send + Sync,
     {
-        verifier.verify(did, root_event_time).await
+        verifier
+            .verify_at(
+                did,
+                root_event_time,
+                verification_time.unwrap_or_else(current_time),
+            )
+            .await
+    }
+
+    /// Verifies a given DID attested by at least `threshold` of its controllers, returning the
+    /// chain from each root along valid attestations. The root of each chain must have been
+    /// timestamped at one of the given root event times. Time-bounded attestations are checked
+    /// at the given verification time, or the current time if `None`.
+    async fn verify_multichain<T, U>(
+        did: &str,
+        threshold: usize,
+        root_event_times: &[Timestamp],
+        verification_time: Option<Timestamp>,
+        verifier: &U,
+    ) -> Result<Vec<DIDChain>, VerifierError>
+    where
+        T: DIDResolver + Send,
+        U: Verifier<T> + Send + Sync,
+    {
+        verifier
+            .verify_multichain_at(
+                did,
+                threshold,
+                root_event_times,
+                verification_time.unwrap_or_else(current_time),
+            )
+            .await
+    }
+
+    /// Verifies a given DID using a resolver available at given endpoint, returning a report of
+    /// the verification of every level in its chain. Time-bounded attestations are checked at
+    /// the given verification time, or the current time if `None`.
+    async fn verify_report<T, U>(
+        did: &str,
+        root_event_time: Timestamp,
+        verification_time: Option<Timestamp>,
+        verifier: &U,
+    ) -> ChainVerificationReport
+    where
+        T: DIDResolver + Send,
+        U: Verifier<T> + Send + Sync,
+    {
+        verifier
+            .verify_report_at(
+                did,
+                root_event_time,
+                verification_time.unwrap_or_else(current_time),
+            )
+            .await
+    }
+
+    /// Verifies a given DID and exports its chain, with the verification bundle of its ION root,
+    /// as a portable chain proof in the given format.
+    async fn export_chain_proof<T>(
+        did: &str,
+        root_event_time: Timestamp,
+        verifier: &TrustchainVerifier<T>,
+        format: ChainProofFormat,
+    ) -> Result<Vec<u8>, ChainProofError>
+    where
+        T: DIDResolver + Send + Sync,
+    {
+        let chain = verifier.verify(did, root_event_time).await?;
+        let root_bundle = verifier.verification_bundle(chain.root()).await?;
+        ChainProof::new(&chain, (*root_bundle).clone())?.to_bytes(format)
+    }
+
+    /// Verifies a chain proof in the given format offline against the trust policy, if any,
+    /// returning the verified chain. Time-bounded attestations are checked at the given
+    /// verification time, or the current time if `None`.
+    fn verify_chain_proof(
+        chain_proof: &[u8],
+        format: ChainProofFormat,
+        root_event_time: Timestamp,
+        verification_time: Option<Timestamp>,
+        policy: Option<&TrustPolicy>,
+    ) -> Result<DIDChain, ChainProofError> {
+        ChainProof::from_bytes(chain_proof, format)?.verify_at(
+            root_event_time,
+            policy,
+            verification_time.unwrap_or_else(current_time),
+        )
     }
 
     // // TODO: the below have no CLI implementation currently but are planned
@@ -104,11 +222,13 @@ pub trait TrustchainVCAPI {
             .await
     }
 
-    /// Verifies a credential
+    /// Verifies a credential. Time-bounded attestations in the issuer's chain are checked at the
+    /// given verification time, or the current time if `None`.
     async fn verify_credential<T, U>(
         credential: &Credential,
         linked_data_proof_options: Option<LinkedDataProofOptions>,
         root_event_time: Timestamp,
+        verification_time: Option<Timestamp>,
         verifier: &U,
         context_loader: &mut ContextLoader,
     ) -> Result<DIDChain, CredentialError>
@@ -131,7 +251,19 @@ pub trait TrustchainVCAPI {
         let issuer = credential
             .get_issuer()
             .ok_or(CredentialError::NoIssuerPresent)?;
-        Ok(verifier.verify(issuer, root_event_time).await?)
+        let chain = verifier
+            .verify_at(
+                issuer,
+                root_event_time,
+                verification_time.unwrap_or_else(current_time),
+            )
+            .await?;
+        // Verify credential types are allowed by the delegation constraints
+        let types: Vec<String> = credential.type_.clone().into_iter().collect();
+        chain
+            .verify_credential_types(&types)
+            .map_err(VerifierError::from)?;
+        Ok(chain)
     }
 }
 
@@ -158,11 +290,14 @@ pub trait TrustchainVPAPI {
             )
             .await?)
     }
-    /// Verifies a verifiable presentation.
+    /// Verifies a verifiable presentation. Time-bounded attestations in the chains of the
+    /// credential issuers are checked at the given verification time, or the current time if
+    /// `None`.
     async fn verify_presentation<T, U>(
         presentation: &Presentation,
         ldp_options: Option<LinkedDataProofOptions>,
         root_event_time: Timestamp,
+        verification_time: Option<Timestamp>,
         verifier: &U,
         context_loader: &mut ContextLoader,
     ) -> Result<(), PresentationError>
@@ -194,6 +329,7 @@ pub trait TrustchainVPAPI {
                                 credential,
                                 ldp_opts,
                                 root_event_time,
+                                verification_time,
                                 verifier,
                                 &mut context_loader,
                             )
@@ -216,6 +352,7 @@ pub trait TrustchainVPAPI {
                                     &credential,
                                     ldp_opts,
                                     root_event_time,
+                                    verification_time,
                                     verifier,
                                     &mut context_loader,
                                 )
@@ -244,6 +381,46 @@ pub trait TrustchainVPAPI {
     }
 }
 
+/// API for Trustchain AnonCreds functionality.
+#[async_trait]
+pub trait TrustchainAnonCredsAPI {
+    /// Verifies an AnonCreds presentation against the schemas and credential definitions on the
+    /// ledger of the given Sovrin network, then verifies each credential issuer DID and that the
+    /// types (schema names) of its credentials are allowed by the delegation constraints of its
+    /// chain.
+    async fn verify_anoncreds_presentation<T, U>(
+        presentation: &AnonCredsPresentation,
+        presentation_request: &PresentationRequest,
+        network: SovrinNetwork,
+        ledger_resolver: &SovrinDIDResolver,
+        root_event_time: Timestamp,
+        verifier: &U,
+    ) -> Result<Vec<DIDChain>, AnonCredsError>
+    where
+        T: DIDResolver + Send,
+        U: Verifier<T> + Send + Sync,
+    {
+        // Verify presentation proof against the ledger objects
+        let issuers = anoncreds::verify_presentation(
+            ledger_resolver,
+            network,
+            presentation,
+            presentation_request,
+        )
+        .await?;
+        // Verify issuers and the types of the credentials they issued
+        let mut chains = Vec::new();
+        for (issuer, types) in issuers.iter() {
+            let chain = verifier.verify(issuer, root_event_time).await?;
+            chain
+                .verify_credential_types(types)
+                .map_err(VerifierError::from)?;
+            chains.push(chain);
+        }
+        Ok(chains)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::api::{TrustchainVCAPI, TrustchainVPAPI};
@@ -324,6 +501,7 @@ mod tests {
             &vc_with_proof,
             None,
             ROOT_EVENT_TIME_1,
+            None,
             &TrustchainVerifier::new(resolver),
             &mut context_loader,
         )
@@ -339,6 +517,7 @@ mod tests {
             &vc_with_proof,
             None,
             ROOT_EVENT_TIME_1,
+            None,
             &TrustchainVerifier::new(resolver),
             &mut context_loader,
         )
@@ -378,6 +557,7 @@ mod tests {
             &signed_vc,
             None,
             ROOT_EVENT_TIME_1,
+            None,
             &verifier,
             &mut context_loader,
         )
@@ -446,6 +626,7 @@ mod tests {
             &signed_vc,
             None,
             ROOT_EVENT_TIME_1,
+            None,
             &verifier,
             &mut context_loader,
         )
@@ -522,6 +703,7 @@ mod tests {
             &presentation,
             None,
             ROOT_EVENT_TIME_1,
+            None,
             &TrustchainVerifier::new(resolver),
             &mut context_loader,
         )
@@ -553,6 +735,7 @@ mod tests {
                 &presentation,
                 None,
                 ROOT_EVENT_TIME_1,
+                None,
                 &TrustchainVerifier::new(resolver),
                 &mut ContextLoader::default()
             )
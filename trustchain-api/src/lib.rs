@@ -1,5 +1,5 @@
 pub mod api;
-use crate::api::{TrustchainDIDAPI, TrustchainVCAPI, TrustchainVPAPI};
+use crate::api::{TrustchainAnonCredsAPI, TrustchainDIDAPI, TrustchainVCAPI, TrustchainVPAPI};
 
 /// A type for implementing CLI traits on.
 pub struct TrustchainAPI;
@@ -7,3 +7,4 @@ pub struct TrustchainAPI;
 impl TrustchainDIDAPI for TrustchainAPI {}
 impl TrustchainVCAPI for TrustchainAPI {}
 impl TrustchainVPAPI for TrustchainAPI {}
+impl TrustchainAnonCredsAPI for TrustchainAPI {}
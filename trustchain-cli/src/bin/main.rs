@@ -1,24 +1,39 @@
 //! Trustchain CLI binary
-use clap::{arg, ArgAction, Command};
+use clap::{arg, value_parser, ArgAction, ArgGroup, Command};
 use serde_json::to_string_pretty;
 use ssi::{jsonld::ContextLoader, ldp::LinkedDataDocument, vc::Credential};
 use std::{
     fs::File,
     io::{stdin, BufReader},
+    sync::Arc,
 };
 use trustchain_api::{
     api::{TrustchainDIDAPI, TrustchainVCAPI},
     TrustchainAPI,
 };
 use trustchain_cli::config::cli_config;
-use trustchain_core::{vc::CredentialError, verifier::Verifier};
+use trustchain_core::{
+    attestor::{AttestationClaims, Validity},
+    chain::DEFAULT_MAX_CHAIN_DEPTH,
+    constraints::Constraints,
+    policy::TrustPolicy,
+    resolver::DIDMethodResolver,
+    vc::CredentialError,
+    verifier::{DIDMethodVerifier, Timestamp, Verifier, VerifierResolver},
+};
 use trustchain_ion::{
     attest::attest_operation,
+    chain_proof::ChainProofFormat,
     create::{create_operation, create_operation_mnemonic},
+    revoke::Revocation,
     trustchain_resolver,
     verifier::TrustchainVerifier,
+    ION_METHOD,
+};
+use trustchain_sovrin::{
+    network::{SovrinNetwork, SOVRIN_METHODS},
+    verifier::TrustchainVerifier as SovrinVerifier,
 };
-use trustchain_sovrin;
 
 fn cli() -> Command {
     Command::new("Trustchain CLI")
@@ -29,6 +44,7 @@ fn cli() -> Command {
         .arg_required_else_help(true)
         .allow_external_subcommands(true)
         .arg(arg!(-s --sovrin).action(ArgAction::SetTrue))
+        .arg(arg!(-p --policy <POLICY>).required(false))
         .subcommand(
             Command::new("did")
                 .about("DID functionality: create, attest, resolve.")
@@ -40,7 +56,9 @@ fn cli() -> Command {
                         .about("Creates a new controlled DID from a document state.")
                         .arg(arg!(-v - -verbose).action(ArgAction::SetTrue))
                         .arg(arg!(-m - -mnemonic).action(ArgAction::SetTrue))
-                        .arg(arg!(-f --file_path <FILE_PATH>).required(false)),
+                        .arg(arg!(-f --file_path <FILE_PATH>).required(false))
+                        .arg(arg!(-n --network <NETWORK>).required(false))
+                        .arg(arg!(-e --endorser <ENDORSER_DID>).required(false)),
                 )
                 .subcommand(
                     Command::new("attest")
@@ -48,7 +66,42 @@ fn cli() -> Command {
                         .arg(arg!(-v - -verbose).action(ArgAction::SetTrue))
                         .arg(arg!(-d --did <DID>).required(true))
                         .arg(arg!(-c --controlled_did <CONTROLLED_DID>).required(true))
-                        .arg(arg!(-k --key_id <KEY_ID>).required(false)),
+                        .arg(arg!(-k --key_id <KEY_ID>).required(false))
+                        .arg(
+                            arg!(--not_before <NOT_BEFORE>)
+                                .required(false)
+                                .value_parser(value_parser!(u64)),
+                        )
+                        .arg(
+                            arg!(--not_after <NOT_AFTER>)
+                                .required(false)
+                                .value_parser(value_parser!(u64)),
+                        )
+                        .arg(
+                            arg!(--max_depth <MAX_DEPTH>)
+                                .required(false)
+                                .value_parser(value_parser!(usize)),
+                        )
+                        .arg(
+                            arg!(--credential_types <CREDENTIAL_TYPES>)
+                                .required(false)
+                                .value_delimiter(','),
+                        )
+                        .arg(arg!(--endpoint_domain <ENDPOINT_DOMAIN>).required(false))
+                        .arg(arg!(--submit).action(ArgAction::SetTrue)),
+                )
+                .subcommand(
+                    Command::new("revoke")
+                        .about("Controller revokes its attestation of a DID (ION DIDs only).")
+                        .arg(arg!(-v - -verbose).action(ArgAction::SetTrue))
+                        .arg(arg!(-d --did <DID>).required(true))
+                        .arg(arg!(-c --controlled_did <CONTROLLED_DID>).required(false))
+                        .arg(arg!(--proof_hash <PROOF_HASH>).required(false))
+                        .group(
+                            ArgGroup::new("revocation")
+                                .args(["controlled_did", "proof_hash"])
+                                .required(true),
+                        ),
                 )
                 .subcommand(
                     Command::new("resolve")
@@ -60,7 +113,43 @@ fn cli() -> Command {
                     Command::new("verify")
                         .about("Verifies a DID.")
                         .arg(arg!(-d --did <DID>).required(true))
-                        .arg(arg!(-t --root_event_time <ROOT_EVENT_TIME>).required(false))
+                        .arg(
+                            arg!(-t --root_event_time <ROOT_EVENT_TIME>)
+                                .required(false)
+                                .value_parser(value_parser!(u32))
+                                .action(ArgAction::Append),
+                        )
+                        .arg(
+                            arg!(--time <TIME>)
+                                .required(false)
+                                .value_parser(value_parser!(u64))
+                                .conflicts_with("output"),
+                        )
+                        .arg(
+                            arg!(-k --threshold <THRESHOLD>)
+                                .required(false)
+                                .value_parser(value_parser!(usize))
+                                .conflicts_with_all(["report", "output"]),
+                        )
+                        .arg(arg!(-r --report).action(ArgAction::SetTrue))
+                        .arg(arg!(-o --output <OUTPUT>).required(false))
+                        .arg(arg!(--cbor).action(ArgAction::SetTrue)),
+                )
+                .subcommand(
+                    Command::new("verify_proof")
+                        .about("Verifies a chain proof offline.")
+                        .arg(arg!(-f --file <FILE>).required(true))
+                        .arg(
+                            arg!(-t --root_event_time <ROOT_EVENT_TIME>)
+                                .required(false)
+                                .value_parser(value_parser!(u32)),
+                        )
+                        .arg(
+                            arg!(--time <TIME>)
+                                .required(false)
+                                .value_parser(value_parser!(u64)),
+                        )
+                        .arg(arg!(--cbor).action(ArgAction::SetTrue)),
                 ),
         )
         .subcommand(
@@ -82,20 +171,80 @@ fn cli() -> Command {
                         .about("Verifies a credential.")
                         .arg(arg!(-v - -verbose).action(ArgAction::Count))
                         .arg(arg!(-f --credential_file <CREDENTIAL_FILE>).required(false))
-                        .arg(arg!(-t --root_event_time <ROOT_EVENT_TIME>).required(false)),
+                        .arg(
+                            arg!(-t --root_event_time <ROOT_EVENT_TIME>)
+                                .required(false)
+                                .value_parser(value_parser!(u64)),
+                        )
+                        .arg(
+                            arg!(--time <TIME>)
+                                .required(false)
+                                .value_parser(value_parser!(u64)),
+                        ),
                 ),
         )
 }
 
+/// Constructs the verifier for the resolution and verification subcommands. Resolution and root
+/// verification are dispatched by DID method, so chains may mix ION and Sovrin DIDs.
+///
+/// The Sovrin verifier, which connects to the ledger pools, is constructed only here and is
+/// shared with the resolver.
+fn method_verifier(
+    sovrin: bool,
+    policy: Option<TrustPolicy>,
+) -> Result<DIDMethodVerifier, Box<dyn std::error::Error>> {
+    let ion_endpoint = cli_config().ion_endpoint.to_address();
+    let mut resolver =
+        DIDMethodResolver::new().with_resolver(&[ION_METHOD], trustchain_resolver(&ion_endpoint));
+    let sovrin_verifier = match cli_config().sovrin_resolver() {
+        Ok(sovrin_resolver) => Some(Arc::new(SovrinVerifier::new(sovrin_resolver))),
+        // Sovrin must be configured when requested.
+        Err(err) if sovrin => return Err(err.into()),
+        Err(_) => None,
+    };
+    if let Some(sovrin_verifier) = &sovrin_verifier {
+        resolver = resolver.with_resolver(
+            &SOVRIN_METHODS,
+            VerifierResolver::new(Arc::clone(sovrin_verifier)),
+        );
+    }
+    let mut verifier = DIDMethodVerifier::new(resolver)
+        .with_max_chain_depth(
+            cli_config()
+                .max_chain_depth
+                .unwrap_or(DEFAULT_MAX_CHAIN_DEPTH),
+        )
+        .with_verifier(
+            &[ION_METHOD],
+            TrustchainVerifier::new(trustchain_resolver(&ion_endpoint)),
+        );
+    if let Some(sovrin_verifier) = sovrin_verifier {
+        verifier = verifier.with_shared_verifier(&SOVRIN_METHODS, sovrin_verifier);
+    }
+    if let Some(policy) = policy {
+        verifier = verifier.with_policy(policy);
+    }
+    Ok(verifier)
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let matches = cli().get_matches();
     let sovrin = matches!(matches.get_one::<bool>("sovrin"), Some(true));
 
-    let endpoint = if sovrin { cli_config().sovrin_genesis_transactions.to_string()
-    } else { cli_config().ion_endpoint.to_address()} ;
-    let verifier = TrustchainVerifier::new(trustchain_resolver(&endpoint));
-    let resolver = verifier.resolver();
+    let ion_endpoint = cli_config().ion_endpoint.to_address();
+    // A trust policy, if given, is evaluated against every verified chain.
+    let policy = matches
+        .get_one::<String>("policy")
+        .map(TrustPolicy::from_file)
+        .transpose()?;
+    let default_root_event_time = match sovrin {
+        true => cli_config()
+            .sovrin_root_event_time
+            .unwrap_or(cli_config().root_event_time),
+        false => cli_config().root_event_time,
+    };
     let mut context_loader = ContextLoader::default();
     match matches.subcommand() {
         Some(("did", sub_matches)) => {
@@ -107,7 +256,34 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     if mnemonic && file_path.is_some() {
                         panic!("Please use only one of '--file_path' and '--mnemonic'.")
                     }
-                    if !mnemonic {
+                    if sovrin {
+                        if file_path.is_some() {
+                            panic!("A document state file is not supported with '--sovrin'.")
+                        }
+                        let network = match sub_matches.get_one::<String>("network") {
+                            Some(network) => network.parse()?,
+                            None => SovrinNetwork::MainNet,
+                        };
+                        let endorser = sub_matches
+                            .get_one::<String>("endorser")
+                            .map(|string| string.as_str());
+                        if !mnemonic {
+                            trustchain_sovrin::create::create_operation(
+                                network, endorser, verbose,
+                            )?;
+                        } else {
+                            let mut mnemonic = String::new();
+                            println!("Enter a mnemonic:");
+                            std::io::stdin().read_line(&mut mnemonic).unwrap();
+                            trustchain_sovrin::create::create_operation_mnemonic(
+                                mnemonic.trim(),
+                                None,
+                                network,
+                                endorser,
+                                verbose,
+                            )?;
+                        }
+                    } else if !mnemonic {
                         // Read doc state from file path
                         let doc_state = if let Some(file_path) = file_path {
                             Some(serde_json::from_reader(File::open(file_path)?)?)
@@ -126,18 +302,69 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     let did = sub_matches.get_one::<String>("did").unwrap();
                     let controlled_did = sub_matches.get_one::<String>("controlled_did").unwrap();
                     let verbose = matches!(sub_matches.get_one::<bool>("verbose"), Some(true));
-                    let _key_id = sub_matches
+                    let key_id = sub_matches
                         .get_one::<String>("key_id")
                         .map(|string| string.as_str());
-                    // TODO: pass optional key_id
-                    attest_operation(did, controlled_did, verbose).await?;
+                    // Validity period of the attestation, as Unix times in seconds.
+                    let validity = Validity::new(
+                        sub_matches.get_one::<Timestamp>("not_before").copied(),
+                        sub_matches.get_one::<Timestamp>("not_after").copied(),
+                    );
+                    // Delegation constraints on the controlled DID and the DIDs downstream of it.
+                    let constraints = Constraints {
+                        max_depth: sub_matches.get_one::<usize>("max_depth").copied(),
+                        credential_types: sub_matches
+                            .get_many::<String>("credential_types")
+                            .map(|types| types.cloned().collect()),
+                        service_endpoint_domain: sub_matches
+                            .get_one::<String>("endpoint_domain")
+                            .cloned(),
+                    };
+                    let claims = AttestationClaims::new(validity, constraints);
+                    if sovrin {
+                        let submit = matches!(sub_matches.get_one::<bool>("submit"), Some(true));
+                        let sovrin_resolver = cli_config().sovrin_resolver()?;
+                        trustchain_sovrin::attest::attest_operation(
+                            did,
+                            controlled_did,
+                            key_id,
+                            &claims,
+                            &sovrin_resolver,
+                            submit,
+                            verbose,
+                        )
+                        .await?;
+                    } else {
+                        // TODO: pass optional key_id
+                        attest_operation(did, controlled_did, &claims, verbose).await?;
+                    }
+                }
+                Some(("revoke", sub_matches)) => {
+                    if sovrin {
+                        return Err("Revocation is not supported with '--sovrin'.".into());
+                    }
+                    let did = sub_matches.get_one::<String>("did").unwrap();
+                    let verbose = matches!(sub_matches.get_one::<bool>("verbose"), Some(true));
+                    let revocation = match (
+                        sub_matches.get_one::<String>("controlled_did"),
+                        sub_matches.get_one::<String>("proof_hash"),
+                    ) {
+                        (Some(controlled_did), _) => {
+                            Revocation::ControlledDID(controlled_did.to_owned())
+                        }
+                        (_, Some(proof_hash)) => Revocation::ProofHash(proof_hash.to_owned()),
+                        _ => unreachable!(),
+                    };
+                    TrustchainAPI::revoke(did, &revocation, verbose).await?;
                 }
                 // TODO: add a flag for update operation with a mnemonic to add a
                 // key generated on mobile to the DID.
                 Some(("resolve", sub_matches)) => {
                     let did = sub_matches.get_one::<String>("did").unwrap();
                     let _verbose = matches!(sub_matches.get_one::<bool>("verbose"), Some(true));
-                    let (res_meta, doc, doc_meta) = TrustchainAPI::resolve(did, resolver).await?;
+                    let verifier = method_verifier(sovrin, policy)?;
+                    let (res_meta, doc, doc_meta) =
+                        TrustchainAPI::resolve(did, verifier.resolver()).await?;
                     // Print results
                     println!("---");
                     println!("Document:");
@@ -163,20 +390,99 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }
                 Some(("verify", sub_matches)) => {
                     let did = sub_matches.get_one::<String>("did").unwrap();
-                    let root_event_time = match sub_matches.get_one::<String>("root_event_time") {
-                        Some(time) => time.parse::<u32>().unwrap(),
-                        None => cli_config().root_event_time,
+                    // Several root event times may be given with a threshold, for DIDs attested
+                    // through several roots.
+                    let root_event_times: Vec<u32> =
+                        match sub_matches.get_many::<u32>("root_event_time") {
+                            Some(times) => times.copied().collect(),
+                            None => vec![default_root_event_time],
+                        };
+                    let threshold = sub_matches.get_one::<usize>("threshold").copied();
+                    if root_event_times.len() > 1 && threshold.is_none() {
+                        return Err(
+                            "Several root event times are only allowed with '--threshold'.".into(),
+                        );
+                    }
+                    let root_event_time = root_event_times[0];
+                    // Time-bounded attestations are checked at the given time, or the current
+                    // time.
+                    let time = sub_matches.get_one::<Timestamp>("time").copied();
+                    if let Some(threshold) = threshold {
+                        let verifier = method_verifier(sovrin, policy)?;
+                        let root_event_times: Vec<Timestamp> =
+                            root_event_times.into_iter().map(Into::into).collect();
+                        let did_chains = TrustchainAPI::verify_multichain(
+                            did,
+                            threshold,
+                            &root_event_times,
+                            time,
+                            &verifier,
+                        )
+                        .await?;
+                        for did_chain in did_chains {
+                            println!("{did_chain}");
+                        }
+                    } else if let Some(output) = sub_matches.get_one::<String>("output") {
+                        // Export a chain proof, including the verification bundle of the ION root.
+                        let format = match sub_matches.get_one::<bool>("cbor") {
+                            Some(true) => ChainProofFormat::Cbor,
+                            _ => ChainProofFormat::Json,
+                        };
+                        let mut ion_verifier =
+                            TrustchainVerifier::new(trustchain_resolver(&ion_endpoint));
+                        if let Some(policy) = &policy {
+                            ion_verifier = ion_verifier.with_policy(policy.clone());
+                        }
+                        let chain_proof = TrustchainAPI::export_chain_proof(
+                            did,
+                            root_event_time.into(),
+                            &ion_verifier,
+                            format,
+                        )
+                        .await?;
+                        std::fs::write(output, chain_proof)?;
+                    } else if matches!(sub_matches.get_one::<bool>("report"), Some(true)) {
+                        let verifier = method_verifier(sovrin, policy)?;
+                        let report = TrustchainAPI::verify_report(
+                            did,
+                            root_event_time.into(),
+                            time,
+                            &verifier,
+                        )
+                        .await;
+                        println!("{}", &to_string_pretty(&report).unwrap());
+                    } else {
+                        let verifier = method_verifier(sovrin, policy)?;
+                        let did_chain =
+                            TrustchainAPI::verify(did, root_event_time.into(), time, &verifier)
+                                .await?;
+                        println!("{did_chain}");
+                    }
+                }
+                Some(("verify_proof", sub_matches)) => {
+                    let file = sub_matches.get_one::<String>("file").unwrap();
+                    let root_event_time = sub_matches
+                        .get_one::<u32>("root_event_time")
+                        .copied()
+                        .unwrap_or(default_root_event_time);
+                    let time = sub_matches.get_one::<Timestamp>("time").copied();
+                    let format = match sub_matches.get_one::<bool>("cbor") {
+                        Some(true) => ChainProofFormat::Cbor,
+                        _ => ChainProofFormat::Json,
                     };
-                    let did_chain =
-                        TrustchainAPI::verify(did, root_event_time.into(), &verifier).await?;
+                    let did_chain = TrustchainAPI::verify_chain_proof(
+                        &std::fs::read(file)?,
+                        format,
+                        root_event_time.into(),
+                        time,
+                        policy.as_ref(),
+                    )?;
                     println!("{did_chain}");
                 }
                 _ => panic!("Unrecognised DID subcommand."),
             }
         }
         Some(("vc", sub_matches)) => {
-            let verifier = TrustchainVerifier::new(trustchain_resolver(&endpoint));
-            let resolver = verifier.resolver();
             match sub_matches.subcommand() {
                 Some(("sign", sub_matches)) => {
                     let did = sub_matches.get_one::<String>("did").unwrap();
@@ -191,12 +497,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                             serde_json::from_reader(buffer).unwrap()
                         };
 
+                    let verifier = method_verifier(sovrin, policy)?;
                     let credential_with_proof = TrustchainAPI::sign(
                         credential,
                         did,
                         None,
                         key_id,
-                        resolver,
+                        verifier.resolver(),
                         &mut context_loader,
                     )
                     .await
@@ -205,10 +512,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }
                 Some(("verify", sub_matches)) => {
                     let verbose = sub_matches.get_one::<u8>("verbose");
-                    let root_event_time = match sub_matches.get_one::<String>("root_event_time") {
-                        Some(time) => time.parse::<u64>().unwrap(),
-                        None => cli_config().root_event_time.into(),
-                    };
+                    let root_event_time = sub_matches
+                        .get_one::<Timestamp>("root_event_time")
+                        .copied()
+                        .unwrap_or(default_root_event_time.into());
+                    // Time-bounded attestations are checked at the given time, or the current
+                    // time.
+                    let time = sub_matches.get_one::<Timestamp>("time").copied();
                     // Deserialize
                     let credential: Credential =
                         if let Some(path) = sub_matches.get_one::<String>("credential_file") {
@@ -218,10 +528,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                             serde_json::from_reader(buffer).unwrap()
                         };
                     // Verify credential
+                    let verifier = method_verifier(sovrin, policy)?;
                     let verify_result = TrustchainAPI::verify_credential(
                         &credential,
                         None,
                         root_event_time,
+                        time,
                         &verifier,
                         &mut context_loader,
                     )
@@ -266,13 +578,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         let issuer = credential
                             .get_issuer()
                             .expect("No issuer present in credential.");
-                        let chain = TrustchainAPI::verify(issuer, root_event_time, &verifier)
+                        let chain = TrustchainAPI::verify(issuer, root_event_time, time, &verifier)
                             .await
                             // Can unwrap as already verified above.
                             .unwrap();
                         if verbose_count > 1 {
                             let (_, doc, doc_meta) =
-                                resolver.resolve_as_result(issuer).await.unwrap();
+                                verifier.resolver().resolve_as_result(issuer).await.unwrap();
                             println!("---");
                             println!("Issuer DID doc:");
                             println!("{}", &to_string_pretty(&doc.as_ref().unwrap()).unwrap());
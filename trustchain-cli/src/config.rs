@@ -5,6 +5,9 @@ use std::fs;
 use toml;
 use trustchain_core::TRUSTCHAIN_CONFIG;
 use trustchain_ion::Endpoint;
+use trustchain_sovrin::network::SovrinNetwork;
+use trustchain_sovrin::resolver::{RequestConfig, SovrinResolver};
+use trustchain_sovrin::TrustchainSovrinError;
 
 lazy_static! {
     /// Lazy static reference to cli configuration loaded from `trustchain_config.toml`.
@@ -31,7 +34,47 @@ pub struct CLIConfig {
     /// Root event unix time for first Trustchain root on testnet.
     pub root_event_time: u32,
     pub ion_endpoint: Endpoint,
+    /// Ledger unix time of the root DID transaction on Sovrin, if different from the
+    /// `root_event_time`.
+    #[serde(default)]
+    pub sovrin_root_event_time: Option<u32>,
+    /// Path to the genesis transactions of Sovrin MainNet.
     pub sovrin_genesis_transactions: String,
+    /// Path to the genesis transactions of Sovrin StagingNet.
+    #[serde(default)]
+    pub sovrin_staging_genesis_transactions: Option<String>,
+    /// Path to the genesis transactions of Sovrin BuilderNet.
+    #[serde(default)]
+    pub sovrin_builder_genesis_transactions: Option<String>,
+    /// Timeout and retry settings for Sovrin ledger requests.
+    #[serde(default)]
+    pub sovrin_request: RequestConfig,
+    /// Maximum number of DIDs in a verified chain, if different from the default.
+    #[serde(default)]
+    pub max_chain_depth: Option<usize>,
+}
+
+impl CLIConfig {
+    /// Constructs a Sovrin resolver for each network with configured genesis transactions.
+    pub fn sovrin_resolver(&self) -> Result<SovrinResolver, TrustchainSovrinError> {
+        let mut resolver = SovrinResolver::new(self.sovrin_request.clone())
+            .with_network_file(SovrinNetwork::MainNet, &self.sovrin_genesis_transactions)?;
+        for (network, path) in [
+            (
+                SovrinNetwork::StagingNet,
+                &self.sovrin_staging_genesis_transactions,
+            ),
+            (
+                SovrinNetwork::BuilderNet,
+                &self.sovrin_builder_genesis_transactions,
+            ),
+        ] {
+            if let Some(path) = path {
+                resolver = resolver.with_network_file(network, path)?;
+            }
+        }
+        Ok(resolver)
+    }
 }
 
 /// Wrapper struct for parsing the `cli` table.
@@ -52,9 +95,11 @@ mod tests {
         root_event_time = 1666971942
         ion_endpoint.host = "http://127.0.0.1"
         ion_endpoint.port = 3000
-
-        [sovrin]
-        sovrin_genesis_transactions = ./TestNet.txn
+        sovrin_root_event_time = 1591357286
+        sovrin_genesis_transactions = "./mainNet.txn"
+        sovrin_staging_genesis_transactions = "./stagingNet.txn"
+        sovrin_request.timeout = 5
+        max_chain_depth = 8
 
         [non_core]
         key = "value"
@@ -67,7 +112,15 @@ mod tests {
             CLIConfig {
                 root_event_time: 1666971942,
                 ion_endpoint: Endpoint::new("http://127.0.0.1".to_string(), 3000),
-                sovrin_genesis_transactions: "./TestNet.txn".to_string(),
+                sovrin_root_event_time: Some(1591357286),
+                sovrin_genesis_transactions: "./mainNet.txn".to_string(),
+                sovrin_staging_genesis_transactions: Some("./stagingNet.txn".to_string()),
+                sovrin_builder_genesis_transactions: None,
+                sovrin_request: RequestConfig {
+                    timeout: 5,
+                    ..RequestConfig::default()
+                },
+                max_chain_depth: Some(8),
             }
         );
     }
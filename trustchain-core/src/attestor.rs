@@ -1,5 +1,9 @@
 //! DID attestor API.
+use crate::chain::ChainError;
+use crate::constraints::Constraints;
 use crate::subject::Subject;
+use crate::verifier::Timestamp;
+use serde::{Deserialize, Serialize};
 use ssi::did::Document;
 use thiserror::Error;
 
@@ -21,6 +25,145 @@ pub enum AttestorError {
     /// Failed to sign DID document.
     #[error("Signing error for Document with DID {0}: {1}.")]
     SigningError(String, String),
+    /// Claims, such as a validity period, are only supported for embedded proofs.
+    #[error("Claims cannot be attested with a detached proof for DID {0}.")]
+    DetachedClaims(String),
+}
+
+/// Format of the JWS proof produced by an attestation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProofFormat {
+    /// Compact JWS with the hash of the canonicalized document as payload.
+    #[default]
+    Embedded,
+    /// Detached JWS with the canonicalized document as unencoded payload
+    /// ([RFC 7797](https://www.rfc-editor.org/rfc/rfc7797)).
+    Detached,
+}
+
+/// Optional validity period of an attestation, as Unix times in seconds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Validity {
+    /// The time from which the attestation is valid.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_before: Option<Timestamp>,
+    /// The time after which the attestation has expired.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_after: Option<Timestamp>,
+}
+
+impl Validity {
+    /// Constructs a validity period from optional bounds.
+    pub fn new(not_before: Option<Timestamp>, not_after: Option<Timestamp>) -> Self {
+        Self {
+            not_before,
+            not_after,
+        }
+    }
+
+    /// Returns whether the validity period is unbounded.
+    pub fn is_unbounded(&self) -> bool {
+        self.not_before.is_none() && self.not_after.is_none()
+    }
+
+    /// Checks the attestation of a DID is valid at the given time.
+    pub fn check(&self, did: &str, time: Timestamp) -> Result<(), ChainError> {
+        match (self.not_before, self.not_after) {
+            (Some(not_before), _) if time < not_before => Err(ChainError::AttestationNotYetValid(
+                did.to_string(),
+                not_before,
+            )),
+            (_, Some(not_after)) if time > not_after => {
+                Err(ChainError::AttestationExpired(did.to_string(), not_after))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Claims signed in an embedded proof together with the hash of the attested document.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationClaims {
+    /// The validity period of the attestation.
+    #[serde(flatten)]
+    pub validity: Validity,
+    /// The delegation constraints on the attested DID and the DIDs downstream of it.
+    #[serde(default, skip_serializing_if = "Constraints::is_unconstrained")]
+    pub constraints: Constraints,
+}
+
+impl AttestationClaims {
+    /// Constructs claims from a validity period and delegation constraints.
+    pub fn new(validity: Validity, constraints: Constraints) -> Self {
+        Self {
+            validity,
+            constraints,
+        }
+    }
+
+    /// Returns whether there are no claims.
+    pub fn is_empty(&self) -> bool {
+        self.validity.is_unbounded() && self.constraints.is_unconstrained()
+    }
+}
+
+impl From<Validity> for AttestationClaims {
+    fn from(validity: Validity) -> Self {
+        Self::new(validity, Constraints::default())
+    }
+}
+
+/// The signed payload of an embedded proof: the hash of the canonicalized document, together
+/// with the claims of a time-bounded or constrained attestation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AttestationPayload {
+    /// The hash of an attestation valid forever and without constraints.
+    Hash(String),
+    /// The hash and claims of a time-bounded or constrained attestation.
+    Claims {
+        hash: String,
+        #[serde(flatten)]
+        claims: AttestationClaims,
+    },
+}
+
+impl AttestationPayload {
+    /// Constructs the payload for the hash of a canonicalized document and its claims.
+    pub fn new(hash: String, claims: &AttestationClaims) -> Self {
+        match claims.is_empty() {
+            true => Self::Hash(hash),
+            false => Self::Claims {
+                hash,
+                claims: claims.to_owned(),
+            },
+        }
+    }
+
+    /// Gets the hash of the canonicalized document.
+    pub fn hash(&self) -> &str {
+        match self {
+            Self::Hash(hash) | Self::Claims { hash, .. } => hash,
+        }
+    }
+
+    /// Gets the validity period.
+    pub fn validity(&self) -> Validity {
+        match self {
+            Self::Hash(_) => Validity::default(),
+            Self::Claims { claims, .. } => claims.validity,
+        }
+    }
+
+    /// Gets the delegation constraints.
+    pub fn constraints(&self) -> Constraints {
+        match self {
+            Self::Hash(_) => Constraints::default(),
+            Self::Claims { claims, .. } => claims.constraints.to_owned(),
+        }
+    }
 }
 
 /// An upstream entity that attests to a downstream DID.
@@ -28,5 +171,108 @@ pub trait Attestor: Subject {
     /// Attests to a DID Document. Subject attests to a DID document by signing the document with (one of) its private signing key(s).
     /// It doesn't matter which signing key you use, there's the option to pick one using the key index.
     /// Typically, the signer will be a controller, but not necessarily. However, every signer is the subject of its own DID.
-    fn attest(&self, doc: &Document, key_id: Option<&str>) -> Result<String, AttestorError>;
+    fn attest(&self, doc: &Document, key_id: Option<&str>) -> Result<String, AttestorError> {
+        self.attest_with_format(doc, key_id, ProofFormat::Embedded)
+    }
+    /// Attests to a DID Document, producing a proof in the given format.
+    fn attest_with_format(
+        &self,
+        doc: &Document,
+        key_id: Option<&str>,
+        format: ProofFormat,
+    ) -> Result<String, AttestorError> {
+        self.attest_with_options(doc, key_id, format, &AttestationClaims::default())
+    }
+    /// Attests to a DID Document for a validity period, embedding `notBefore` and `notAfter`
+    /// claims in the signed payload.
+    fn attest_with_validity(
+        &self,
+        doc: &Document,
+        key_id: Option<&str>,
+        validity: &Validity,
+    ) -> Result<String, AttestorError> {
+        self.attest_with_claims(doc, key_id, &AttestationClaims::from(*validity))
+    }
+    /// Attests to a DID Document, embedding the given claims (validity period and delegation
+    /// constraints) in the signed payload.
+    fn attest_with_claims(
+        &self,
+        doc: &Document,
+        key_id: Option<&str>,
+        claims: &AttestationClaims,
+    ) -> Result<String, AttestorError> {
+        self.attest_with_options(doc, key_id, ProofFormat::Embedded, claims)
+    }
+    /// Attests to a DID Document, producing a proof in the given format with the given claims.
+    fn attest_with_options(
+        &self,
+        doc: &Document,
+        key_id: Option<&str>,
+        format: ProofFormat,
+        claims: &AttestationClaims,
+    ) -> Result<String, AttestorError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attestation_payload() {
+        let hash = "EiAqvB0tVuStoBuYRqPiz1DSBQxoPzPJT0GQT1Ubrlzn_w".to_string();
+        let payload = AttestationPayload::new(hash.clone(), &AttestationClaims::default());
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap(),
+            serde_json::json!(hash)
+        );
+
+        let validity = Validity::new(Some(1000), Some(2000));
+        let payload = AttestationPayload::new(hash.clone(), &validity.into());
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"hash": hash, "notBefore": 1000, "notAfter": 2000})
+        );
+        let payload: AttestationPayload = serde_json::from_value(json).unwrap();
+        assert_eq!(payload.hash(), hash);
+        assert_eq!(payload.validity(), validity);
+        assert!(payload.constraints().is_unconstrained());
+
+        let constraints = Constraints {
+            max_depth: Some(1),
+            credential_types: Some(vec!["UniversityDegreeCredential".to_string()]),
+            service_endpoint_domain: None,
+        };
+        let claims = AttestationClaims::new(Validity::default(), constraints.clone());
+        let json = serde_json::to_value(AttestationPayload::new(hash.clone(), &claims)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "hash": hash,
+                "constraints": {
+                    "maxDepth": 1,
+                    "credentialTypes": ["UniversityDegreeCredential"]
+                }
+            })
+        );
+        let payload: AttestationPayload = serde_json::from_value(json).unwrap();
+        assert_eq!(payload.validity(), Validity::default());
+        assert_eq!(payload.constraints(), constraints);
+    }
+
+    #[test]
+    fn test_validity_check() {
+        let did = "did:example:test";
+        let validity = Validity::new(Some(1000), Some(2000));
+        assert!(validity.check(did, 1500).is_ok());
+        assert_eq!(
+            validity.check(did, 999),
+            Err(ChainError::AttestationNotYetValid(did.to_string(), 1000))
+        );
+        assert_eq!(
+            validity.check(did, 2001),
+            Err(ChainError::AttestationExpired(did.to_string(), 2000))
+        );
+        assert!(Validity::default().check(did, 0).is_ok());
+    }
 }
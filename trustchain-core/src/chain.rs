@@ -1,21 +1,29 @@
 //! Chain API and `DIDChain` type with default implementation.
+use crate::attestor::{AttestationPayload, Validity};
+use crate::constraints::{ConstraintViolation, Constraints};
 use crate::display::PrettyDID;
-use crate::resolver::TrustchainResolver;
-use crate::utils::{canonicalize, decode, decode_verify, extract_keys, hash};
+use crate::resolver::{ResolverResult, TrustchainResolver};
+use crate::revocation::{check_revocation_service, remove_revocation_service, RevocationList};
+use crate::utils::{canonicalize, current_time, decode_verify, detached_verify, hash, is_detached};
+use crate::verifier::Timestamp;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use ssi::did::{VerificationMethod, VerificationMethodMap};
 use ssi::did_resolve::Metadata;
+use ssi::jwk::JWK;
 use ssi::{did::Document, did_resolve::DocumentMetadata, one_or_many::OneOrMany};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::panic::AssertUnwindSafe;
 use thiserror::Error;
 
 /// An error relating to a DID chain.
-#[derive(Error, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Error, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ChainError {
     /// Resolution of DID failed.
     #[error("Failed to resolve DID: {0}.")]
     ResolutionFailure(String),
-    /// Multiple controllers for a DID.
+    /// Multiple controllers for a DID, which can only be verified in a `DIDMultiChain`.
     #[error("Found multiple controllers in DID: {0}.")]
     MultipleControllers(String),
     /// No proof value present.
@@ -27,6 +35,61 @@ pub enum ChainError {
     /// Failure to verify payload.
     #[error("Payload of JWT does not match reconstructed payload.")]
     InvalidPayload,
+    /// Failure to verify the proof of a DID by its upstream DID.
+    #[error("Invalid proof for DID: {0}. {1}")]
+    InvalidLink(String, Box<ChainError>),
+    /// A DID is revisited when following controllers, forming a cycle.
+    #[error("Controller cycle detected at DID: {0}.")]
+    CycleDetected(String),
+    /// The chain has more DIDs than the maximum depth.
+    #[error("Chain exceeds the maximum depth of {0} DIDs.")]
+    MaxDepthExceeded(usize),
+    /// A chain without any DIDs.
+    #[error("The chain contains no DIDs.")]
+    EmptyChain,
+    /// A DID in the chain is not controlled by the next DID up the chain.
+    #[error("DID: {0} is not controlled by the next DID up the chain.")]
+    BrokenLink(String),
+    /// Fewer controllers than the threshold validly attest a DID.
+    #[error("Only {1} controllers validly attest DID: {0}, below the threshold of {2}.")]
+    ThresholdNotMet(String, usize, usize),
+    /// The attestation of a DID is revoked by its upstream DID.
+    #[error("Attestation of DID: {0} is revoked by upstream DID: {1}.")]
+    RevokedAttestation(String, String),
+    /// The revocation list of an upstream DID cannot be verified.
+    #[error("Invalid revocation list for DID: {0}. {1}")]
+    InvalidRevocationList(String, String),
+    /// The attestation of a DID is not valid until a later time.
+    #[error("Attestation of DID: {0} is not valid before: {1}.")]
+    AttestationNotYetValid(String, Timestamp),
+    /// The attestation of a DID has expired.
+    #[error("Attestation of DID: {0} expired at: {1}.")]
+    AttestationExpired(String, Timestamp),
+    /// A delegation constraint of an attestation is violated downstream.
+    #[error("Delegation constraint violated: {0}")]
+    ConstraintViolation(ConstraintViolation),
+    /// A DID has more paths from the roots than the maximum.
+    #[error("DID has more than the maximum of {0} paths from the roots.")]
+    TooManyRootPaths(usize),
+    /// Resolving the upstream DIDs of a DID takes more than the maximum number of resolutions.
+    #[error("Resolving the upstream DIDs exceeds the maximum of {0} DIDs.")]
+    TooManyResolutions(usize),
+    /// A verification task panicked on the worker pool.
+    #[error("Verification task failed: {0}")]
+    VerificationTaskFailed(String),
+}
+
+/// Runs a verification task on the worker pool, returning a panic in the task as an error
+/// instead of unwinding through the pool.
+pub(crate) fn run_task<T>(task: impl FnOnce() -> Result<T, ChainError>) -> Result<T, ChainError> {
+    std::panic::catch_unwind(AssertUnwindSafe(task)).unwrap_or_else(|panic| {
+        let message = panic
+            .downcast_ref::<&str>()
+            .map(|msg| msg.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        Err(ChainError::VerificationTaskFailed(message))
+    })
 }
 
 /// A chain of DIDs.
@@ -45,8 +108,12 @@ pub trait Chain {
     fn downstream(&self, did: &str) -> Option<&String>;
     /// Gets data for the given DID.
     fn data(&self, did: &str) -> Option<&(Document, DocumentMetadata)>;
-    /// Verify all of the proofs in the chain.
-    fn verify_proofs(&self) -> Result<(), ChainError>;
+    /// Verify all of the proofs in the chain at the current time.
+    fn verify_proofs(&self) -> Result<(), ChainError> {
+        self.verify_proofs_at(current_time())
+    }
+    /// Verify all of the proofs in the chain at the given verification time.
+    fn verify_proofs_at(&self, time: Timestamp) -> Result<(), ChainError>;
     /// Returns a vector of DID strings ordered by the level in the chain, starting at the root (level 0).
     fn level_vec(&self) -> &Vec<String>;
     /// Returns a vector of Documents and Document Metadata for each DID ordered by the level in the chain, starting at the root (level 0).
@@ -62,26 +129,132 @@ pub trait Chain {
     }
 }
 
-/// Gets proof from DocumentMetadata.
-fn get_proof(doc_meta: &DocumentMetadata) -> Result<&str, ChainError> {
+/// Gets the proof value of a proof in DocumentMetadata.
+fn get_proof_value(proof: &HashMap<String, Metadata>) -> Option<&str> {
+    match proof.get("proofValue") {
+        Some(Metadata::String(proof_value)) => Some(proof_value),
+        _ => None,
+    }
+}
+
+/// Gets the proof by the given controller from DocumentMetadata.
+fn get_proof<'a>(doc_meta: &'a DocumentMetadata, controller: &str) -> Result<&'a str, ChainError> {
     // Get property set
-    if let Some(property_set) = doc_meta.property_set.as_ref() {
-        // Get proof
-        if let Some(Metadata::Map(proof)) = property_set.get("proof") {
-            // Get proof value
-            if let Some(Metadata::String(proof_value)) = proof.get("proofValue") {
-                Ok(proof_value)
-            } else {
-                Err(ChainError::FailureToGetProof)
-            }
-        } else {
-            Err(ChainError::FailureToGetProof)
+    let proof = doc_meta
+        .property_set
+        .as_ref()
+        .and_then(|property_set| property_set.get("proof"));
+    match proof {
+        // A single proof, by the only controller.
+        Some(Metadata::Map(proof)) => get_proof_value(proof),
+        // The proofs of several controllers, identified by the controller DID.
+        Some(Metadata::List(proofs)) => proofs.iter().find_map(|proof| match proof {
+            Metadata::Map(proof) => match proof.get("id") {
+                Some(Metadata::String(id)) if id == controller => get_proof_value(proof),
+                _ => None,
+            },
+            _ => None,
+        }),
+        _ => None,
+    }
+    .ok_or(ChainError::FailureToGetProof)
+}
+
+/// Verifies the proof of a DID by an upstream DID (one of its controllers) at the given time,
+/// returning the ID of the upstream verification method with a valid key.
+pub(crate) fn verify_attestation(
+    did_doc: &Document,
+    did_doc_meta: &DocumentMetadata,
+    udid_doc: &Document,
+    time: Timestamp,
+) -> Result<String, ChainError> {
+    let udid = &udid_doc.id;
+
+    // Extract the controller proof from the document metadata.
+    let proof = get_proof(did_doc_meta, udid)?;
+
+    // Reject the proof if the upstream DID has revoked it.
+    match RevocationList::from_document(udid_doc) {
+        Ok(Some(revocation_list)) if revocation_list.is_revoked(&did_doc.id, proof) => {
+            return Err(ChainError::RevokedAttestation(
+                did_doc.id.to_owned(),
+                udid.to_owned(),
+            ))
+        }
+        Ok(_) => (),
+        Err(err) => {
+            return Err(ChainError::InvalidRevocationList(
+                udid.to_owned(),
+                err.to_string(),
+            ))
         }
-    } else {
-        Err(ChainError::FailureToGetProof)
     }
+
+    // 1. Reconstruct the canonicalized document, as attested by the upstream DID. Each
+    // controller of a DID with several controllers attests with itself as the controller, and
+    // the revocation list of the DID is not part of the attested document, so it must be a
+    // well-formed revocation list service.
+    check_revocation_service(did_doc)
+        .map_err(|err| ChainError::InvalidRevocationList(did_doc.id.to_owned(), err.to_string()))?;
+    let mut did_doc = did_doc.clone();
+    did_doc.controller = Some(OneOrMany::One(udid.to_string()));
+    remove_revocation_service(&mut did_doc);
+    let doc_canon = canonicalize(&did_doc).unwrap();
+
+    // A detached JWS is signed over the canonicalized document itself, so it is verified in
+    // one step with the signature. Otherwise, check the embedded payload is its hash, and get
+    // the validity period of a time-bounded attestation.
+    let detached = is_detached(proof);
+    let validity = match detached {
+        true => Validity::default(),
+        false => match ssi::jwt::decode_unverified::<AttestationPayload>(proof) {
+            Ok(payload) if payload.hash() == hash(&doc_canon) => payload.validity(),
+            _ => return Err(ChainError::InvalidPayload),
+        },
+    };
+
+    // 2. Check the keys
+    // Get keys with the IDs of their verification methods
+    let keys: Vec<(String, &JWK)> = udid_doc
+        .verification_method
+        .iter()
+        .flatten()
+        .filter_map(|vm| match vm {
+            VerificationMethod::Map(VerificationMethodMap {
+                public_key_jwk: Some(key),
+                ..
+            }) => Some((vm.get_id(udid), key)),
+            _ => None,
+        })
+        .collect();
+
+    // Check at least one key valid, trying the keys in parallel.
+    let valid_keys: Vec<bool> = keys
+        .par_iter()
+        .map(|(_, key)| {
+            run_task(|| {
+                Ok(match detached {
+                    true => detached_verify(proof, &doc_canon, key).is_ok(),
+                    false => decode_verify(proof, key).is_ok(),
+                })
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    let key_id = keys
+        .into_iter()
+        .zip(valid_keys)
+        .find_map(|((key_id, _), valid)| valid.then_some(key_id))
+        .ok_or(ChainError::InvalidKeys)?;
+
+    // 3. Check the attestation is valid at the verification time, once it is known to be
+    // genuinely signed by the upstream DID.
+    validity.check(&did_doc.id, time)?;
+    Ok(key_id)
 }
 
+/// Default maximum number of DIDs in a chain, bounding the resolutions made to build it.
+pub const DEFAULT_MAX_CHAIN_DEPTH: usize = 32;
+
 /// Max width in chars for printing
 const MAX_WIDTH: usize = 79;
 
@@ -122,44 +295,86 @@ impl fmt::Display for DIDChain {
 impl DIDChain {
     // Public constructor.
     pub async fn new(did: &str, resolver: &dyn TrustchainResolver) -> Result<Self, ChainError> {
-        // Construct an empty chain.
+        Self::new_with_max_depth(did, resolver, DEFAULT_MAX_CHAIN_DEPTH).await
+    }
+
+    /// Constructs a chain of at most `max_depth` DIDs from the given DID to its root.
+    pub async fn new_with_max_depth(
+        did: &str,
+        resolver: &dyn TrustchainResolver,
+        max_depth: usize,
+    ) -> Result<Self, ChainError> {
+        let (levels, error) = Self::resolve_levels(did, resolver, max_depth).await;
+        if let Some(err) = error {
+            return Err(err);
+        }
+
+        // Every level was resolved, from the passed DID up to the root.
         let mut chain = DIDChain::empty();
+        for (_, resolved) in levels {
+            if let Ok((_, Some(ddoc), Some(ddoc_meta))) = resolved {
+                chain.prepend((ddoc, ddoc_meta));
+            }
+        }
+        chain.level_vec.reverse();
+        Ok(chain)
+    }
+
+    /// Resolves at most `max_depth` DIDs from the given DID up towards its root, returning the
+    /// resolution result of each DID (from the given DID upwards) and the error that stopped
+    /// the chain short of a root, if any.
+    pub(crate) async fn resolve_levels(
+        did: &str,
+        resolver: &dyn TrustchainResolver,
+        max_depth: usize,
+    ) -> (Vec<(String, ResolverResult)>, Option<ChainError>) {
+        let mut levels = Vec::new();
+        let mut visited: HashSet<String> = HashSet::new();
 
         // Start from the passed DID.
         let mut ddid: String = did.to_string();
 
         // Loop up the DID chain until the root is reached or an error occurs.
         loop {
+            // Stop before resolving beyond the maximum depth.
+            if levels.len() >= max_depth {
+                return (levels, Some(ChainError::MaxDepthExceeded(max_depth)));
+            }
+
             // Resolve the current DID.
             let resolved = resolver.resolve_as_result(&ddid).await;
-
-            if let Ok((_, Some(ddoc), Some(ddoc_meta))) = resolved {
-                // Clone the controller information before moving ddoc into the chain.
-                let controller = ddoc.controller.to_owned();
-
-                // Prepend the current DID to the chain.
-                chain.prepend((ddoc, ddoc_meta));
-
-                // Extract the controller from the DID document.
-                // If there is no controller, this is the root.
-                // If there is more than one controller, return an error.
-                let udid = match controller {
-                    None => {
-                        chain.level_vec.reverse();
-                        return Ok(chain);
-                    }
-                    Some(x) => match x.to_owned() {
-                        OneOrMany::One(udid) => udid,
-                        OneOrMany::Many(_) => return Err(ChainError::MultipleControllers(ddid)),
-                    },
-                };
-
-                // If ddid is not the root, return to start of loop on the controller's DID.
-                ddid = udid;
-            } else {
+            let controller = match &resolved {
+                Ok((_, Some(ddoc), Some(_))) => {
+                    visited.insert(ddoc.id.to_owned());
+                    ddoc.controller.to_owned()
+                }
                 // If any resolution attempt fails, return an error.
-                return Err(ChainError::ResolutionFailure(ddid));
+                _ => {
+                    levels.push((ddid.to_owned(), resolved));
+                    return (levels, Some(ChainError::ResolutionFailure(ddid)));
+                }
+            };
+            visited.insert(ddid.to_owned());
+            levels.push((ddid.to_owned(), resolved));
+
+            // Extract the controller from the DID document.
+            // If there is no controller, this is the root.
+            // If there is more than one controller, return an error.
+            let udid = match controller {
+                None => return (levels, None),
+                Some(OneOrMany::One(udid)) => udid,
+                Some(OneOrMany::Many(_)) => {
+                    return (levels, Some(ChainError::MultipleControllers(ddid)))
+                }
+            };
+
+            // A controller already in the chain (including the DID itself) forms a cycle.
+            if visited.contains(&udid) {
+                return (levels, Some(ChainError::CycleDetected(udid)));
             }
+
+            // If ddid is not the root, return to start of loop on the controller's DID.
+            ddid = udid;
         }
     }
 
@@ -171,6 +386,97 @@ impl DIDChain {
         }
     }
 
+    /// Constructs a chain from resolved DIDs ordered from the root to the leaf.
+    pub(crate) fn from_levels(levels: Vec<(Document, DocumentMetadata)>) -> Self {
+        let mut chain = DIDChain::empty();
+        // Levels are pushed in order, so the chain is not reversed as when building upwards.
+        for level in levels {
+            chain.prepend(level);
+        }
+        chain
+    }
+
+    /// Constructs a chain from resolved DIDs ordered from the root to the leaf, checking that the
+    /// first DID is a root and that every other DID is controlled by the one before it.
+    pub fn try_from_levels(levels: Vec<(Document, DocumentMetadata)>) -> Result<Self, ChainError> {
+        if levels.is_empty() {
+            return Err(ChainError::EmptyChain);
+        }
+        let mut udid: Option<&str> = None;
+        let mut visited: HashSet<&str> = HashSet::new();
+        for (doc, _) in levels.iter() {
+            let controlled = match (&doc.controller, udid) {
+                (None, None) => true,
+                (Some(OneOrMany::One(controller)), Some(udid)) => controller == udid,
+                _ => false,
+            };
+            if !controlled {
+                return Err(ChainError::BrokenLink(doc.id.to_owned()));
+            }
+            if !visited.insert(doc.id.as_str()) {
+                return Err(ChainError::CycleDetected(doc.id.to_owned()));
+            }
+            udid = Some(doc.id.as_str());
+        }
+        Ok(Self::from_levels(levels))
+    }
+
+    /// Verifies the proof of a DID in the chain by its upstream DID at the given time.
+    fn verify_link(&self, did: &str, udid: &str, time: Timestamp) -> Result<(), ChainError> {
+        // Get the DID & its data, and the upstream DID data.
+        let (did_doc, did_doc_meta) = self.data(did).unwrap();
+        let (udid_doc, _) = self.data(udid).unwrap();
+        verify_attestation(did_doc, did_doc_meta, udid_doc, time).map(|_| ())
+    }
+
+    /// Gets the delegation constraints signed in the attestation of a DID by its upstream DID,
+    /// without verifying the attestation. The root and DIDs attested with a detached proof are
+    /// unconstrained.
+    pub fn constraints(&self, did: &str) -> Constraints {
+        let Some((_, doc_meta)) = self.data(did) else {
+            return Constraints::default();
+        };
+        let Some(udid) = self.upstream(did) else {
+            return Constraints::default();
+        };
+        get_proof(doc_meta, udid)
+            .ok()
+            .filter(|proof| !is_detached(proof))
+            .and_then(|proof| ssi::jwt::decode_unverified::<AttestationPayload>(proof).ok())
+            .map(|payload| payload.constraints())
+            .unwrap_or_default()
+    }
+
+    /// Verifies the delegation constraints of every attestation in the chain against the
+    /// attested DID and the DIDs downstream of it.
+    pub(crate) fn verify_constraints(&self) -> Result<(), ChainError> {
+        (1..self.len()).try_for_each(|level| {
+            self.check_constraints(level)
+                .map_err(ChainError::ConstraintViolation)
+        })
+    }
+
+    /// Checks the delegation constraints of the attestation of the DID at the given level
+    /// against it and the DIDs downstream of it.
+    pub(crate) fn check_constraints(&self, level: usize) -> Result<(), ConstraintViolation> {
+        let docs: Vec<&Document> = self.level_vec[level..]
+            .iter()
+            .map(|did| &self.did_map[did].0)
+            .collect();
+        self.constraints(&self.level_vec[level])
+            .check_downstream(&docs)
+    }
+
+    /// Verifies that the types of a credential issued by the leaf DID are allowed by the
+    /// delegation constraints of every attestation in the (verified) chain.
+    pub fn verify_credential_types(&self, types: &[String]) -> Result<(), ChainError> {
+        self.level_vec.iter().skip(1).try_for_each(|did| {
+            self.constraints(did)
+                .check_credential_types(self.leaf(), types)
+                .map_err(ChainError::ConstraintViolation)
+        })
+    }
+
     /// Prepend a DID to the chain.
     fn prepend(&mut self, tuple: (Document, DocumentMetadata)) {
         let (doc, doc_meta) = tuple;
@@ -206,62 +512,31 @@ impl Chain for DIDChain {
         self.level_vec.last().expect("Empty chain!")
     }
 
-    fn verify_proofs(&self) -> Result<(), ChainError> {
-        // TODO: verify signatures in parallel.
-
-        // Start from the leaf node.
-        let mut did = self.leaf();
-
-        while did != self.root() {
-            // 0. Get the DID & its data.
-            let (did_doc, did_doc_meta) = self.data(did).unwrap();
-
-            // Get the upstream DID & its data.
-            let udid = &self.upstream(did).unwrap();
-            let (udid_doc, _) = self.data(udid).unwrap();
-
-            // Extract the controller proof from the document metadata.
-            let proof = get_proof(did_doc_meta)?;
-
-            // TODO: consider whether to use detached JWS instead making verification one step.
-            // 1. Reconstruct the actual payload.
-            let actual_payload = hash(&canonicalize(&did_doc).unwrap());
-
-            // Decode the payload from the proof
-            let decoded_payload = decode(proof);
-
-            if let Ok(decoded_payload) = decoded_payload {
-                if actual_payload != decoded_payload {
-                    return Err(ChainError::InvalidPayload);
-                }
-            } else {
-                return Err(ChainError::InvalidPayload);
-            }
-
-            // 2. Check the keys
-            // Get keys
-            let keys = extract_keys(udid_doc);
-
-            // Check at least one key valid
-            let mut one_valid_key = false;
-            for key in &keys {
-                match decode_verify(proof, key) {
-                    Ok(_) => {
-                        one_valid_key = true;
-                        break;
-                    }
-                    Err(_) => continue,
-                };
-            }
-            match one_valid_key {
-                true => (),
-                false => return Err(ChainError::InvalidKeys),
-            }
-
-            // 3. Set: did <- udid
-            did = udid;
+    fn verify_proofs_at(&self, time: Timestamp) -> Result<(), ChainError> {
+        // Links as (DID, upstream DID) pairs, from the root to the leaf.
+        let links: Vec<(&String, &String)> = self
+            .level_vec
+            .windows(2)
+            .map(|pair| (&pair[1], &pair[0]))
+            .collect();
+
+        // Verify the links in parallel.
+        let results: Vec<Result<(), ChainError>> = links
+            .par_iter()
+            .map(|(did, udid)| run_task(|| self.verify_link(did, udid, time)))
+            .collect();
+
+        // Report the failed link closest to the leaf, as when walking from the leaf to the root.
+        match results
+            .into_iter()
+            .zip(links)
+            .rev()
+            .find_map(|(result, (did, _))| result.err().map(|err| (did, err)))
+        {
+            Some((did, err)) => Err(ChainError::InvalidLink(did.to_owned(), Box::new(err))),
+            // With every attestation verified, enforce their delegation constraints.
+            None => self.verify_constraints(),
         }
-        Ok(())
     }
 
     /// Returns the DID immediately upstream from the given DID in the chain.
@@ -292,15 +567,18 @@ impl Chain for DIDChain {
 
 #[cfg(test)]
 mod tests {
-    use ssi::jwk::JWK;
+    use ssi::jwk::{Algorithm, JWK};
 
     use super::*;
+    use crate::attestor::AttestationClaims;
     use crate::data::{
-        TEST_DID_CHAIN, TEST_ROOT_DOCUMENT, TEST_ROOT_DOCUMENT_METADATA, TEST_ROOT_PLUS_1_DOCUMENT,
-        TEST_ROOT_PLUS_1_DOCUMENT_METADATA, TEST_ROOT_PLUS_2_DOCUMENT,
+        ROOT_PLUS_1_SIGNING_KEY, TEST_DID_CHAIN, TEST_ROOT_DOCUMENT, TEST_ROOT_DOCUMENT_METADATA,
+        TEST_ROOT_PLUS_1_DOCUMENT, TEST_ROOT_PLUS_1_DOCUMENT_METADATA, TEST_ROOT_PLUS_2_DOCUMENT,
         TEST_ROOT_PLUS_2_DOCUMENT_METADATA, TEST_TRUSTCHAIN_DOCUMENT,
         TEST_TRUSTCHAIN_DOCUMENT_METADATA,
     };
+    use crate::test_resolver::FixtureResolver;
+    use crate::utils::{detached_sign, extract_keys, generate_key};
 
     const ROOT_SIGNING_KEYS: &str = r#"
     [
@@ -321,9 +599,15 @@ mod tests {
         let root_plus_2_doc_meta: DocumentMetadata =
             serde_json::from_str(TEST_ROOT_PLUS_2_DOCUMENT_METADATA)?;
 
-        let root_proof = get_proof(&root_doc_meta);
-        let root_plus_1_proof = get_proof(&root_plus_1_doc_meta);
-        let root_plus_2_proof = get_proof(&root_plus_2_doc_meta);
+        let root_proof = get_proof(&root_doc_meta, "");
+        let root_plus_1_proof = get_proof(
+            &root_plus_1_doc_meta,
+            "did:ion:test:EiCClfEdkTv_aM3UnBBhlOV89LlGhpQAbfeZLFdFxVFkEg",
+        );
+        let root_plus_2_proof = get_proof(
+            &root_plus_2_doc_meta,
+            "did:ion:test:EiBVpjUxXeSRJpvj2TewlX9zNF3GKMCKWwGmKBZqF6pk_A",
+        );
 
         assert!(root_proof.is_err());
         assert!(root_plus_1_proof.is_ok());
@@ -460,6 +744,280 @@ mod tests {
         assert!(target.verify_proofs().is_err());
     }
 
+    #[test]
+    fn test_verify_proofs_failed_link() {
+        // The leaf is not signed by the keys of the substituted level one DID.
+        let target = test_invalid_chain().unwrap();
+        assert_eq!(
+            target.verify_proofs(),
+            Err(ChainError::InvalidLink(
+                "did:ion:test:EiAtHHKFJWAk5AsM3tgCut3OiBY4ekHTf66AAjoysXL65Q".to_string(),
+                Box::new(ChainError::InvalidKeys)
+            ))
+        );
+
+        // The result does not depend on the order in which the links are verified.
+        for _ in 0..10 {
+            assert_eq!(
+                target.verify_proofs(),
+                test_invalid_chain().unwrap().verify_proofs()
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_proofs_detached() -> Result<(), Box<dyn std::error::Error>> {
+        // Re-sign the level two DID as a detached JWS by the level one DID.
+        let udid_key: JWK = serde_json::from_str(ROOT_PLUS_1_SIGNING_KEY)?;
+        let (doc, _) = resolved_fixture(
+            TEST_ROOT_PLUS_2_DOCUMENT,
+            TEST_ROOT_PLUS_2_DOCUMENT_METADATA,
+        );
+        let proof = detached_sign(Algorithm::ES256K, &canonicalize(&doc)?, &udid_key)?;
+        let mut doc_meta: serde_json::Value =
+            serde_json::from_str(TEST_ROOT_PLUS_2_DOCUMENT_METADATA)?;
+        doc_meta["proof"]["proofValue"] = serde_json::Value::String(proof);
+
+        let mut target = DIDChain::empty();
+        target.prepend((doc.clone(), serde_json::from_value(doc_meta.clone())?));
+        target.prepend(resolved_fixture(
+            TEST_ROOT_PLUS_1_DOCUMENT,
+            TEST_ROOT_PLUS_1_DOCUMENT_METADATA,
+        ));
+        target.prepend(resolved_fixture(
+            TEST_ROOT_DOCUMENT,
+            TEST_ROOT_DOCUMENT_METADATA,
+        ));
+        target.level_vec.reverse();
+        assert!(target.verify_proofs().is_ok());
+
+        // The detached signature does not verify for a modified document.
+        let mut modified_doc = doc;
+        modified_doc.service = None;
+        target.did_map.insert(
+            modified_doc.id.clone(),
+            (modified_doc, serde_json::from_value(doc_meta)?),
+        );
+        assert_eq!(
+            target.verify_proofs(),
+            Err(ChainError::InvalidLink(
+                "did:ion:test:EiAtHHKFJWAk5AsM3tgCut3OiBY4ekHTf66AAjoysXL65Q".to_string(),
+                Box::new(ChainError::InvalidKeys)
+            ))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_proofs_time_bounded() -> Result<(), Box<dyn std::error::Error>> {
+        let root_plus_2 = "did:ion:test:EiAtHHKFJWAk5AsM3tgCut3OiBY4ekHTf66AAjoysXL65Q";
+        let udid_key: JWK = serde_json::from_str(ROOT_PLUS_1_SIGNING_KEY)?;
+
+        // Re-sign the level two DID by the level one DID, valid between two times.
+        let target = |key: &JWK| -> Result<DIDChain, Box<dyn std::error::Error>> {
+            let (doc, _) = resolved_fixture(
+                TEST_ROOT_PLUS_2_DOCUMENT,
+                TEST_ROOT_PLUS_2_DOCUMENT_METADATA,
+            );
+            let payload = AttestationPayload::new(
+                hash(&canonicalize(&doc)?),
+                &Validity::new(Some(1000), Some(2000)).into(),
+            );
+            let proof = ssi::jwt::encode_sign(Algorithm::ES256K, &payload, key)?;
+            let mut doc_meta: serde_json::Value =
+                serde_json::from_str(TEST_ROOT_PLUS_2_DOCUMENT_METADATA)?;
+            doc_meta["proof"]["proofValue"] = serde_json::Value::String(proof);
+            let mut target = test_chain();
+            target
+                .did_map
+                .insert(doc.id.clone(), (doc, serde_json::from_value(doc_meta)?));
+            Ok(target)
+        };
+        let valid_target = target(&udid_key)?;
+        assert!(valid_target.verify_proofs_at(1500).is_ok());
+        assert_eq!(
+            valid_target.verify_proofs_at(999),
+            Err(ChainError::InvalidLink(
+                root_plus_2.to_string(),
+                Box::new(ChainError::AttestationNotYetValid(
+                    root_plus_2.to_string(),
+                    1000
+                ))
+            ))
+        );
+        assert_eq!(
+            valid_target.verify_proofs(),
+            Err(ChainError::InvalidLink(
+                root_plus_2.to_string(),
+                Box::new(ChainError::AttestationExpired(
+                    root_plus_2.to_string(),
+                    2000
+                ))
+            ))
+        );
+
+        // A forged time-bounded proof is rejected for its keys, not its expiry.
+        assert_eq!(
+            target(&generate_key())?.verify_proofs(),
+            Err(ChainError::InvalidLink(
+                root_plus_2.to_string(),
+                Box::new(ChainError::InvalidKeys)
+            ))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_proofs_constrained() -> Result<(), Box<dyn std::error::Error>> {
+        let root_plus_2 = "did:ion:test:EiAtHHKFJWAk5AsM3tgCut3OiBY4ekHTf66AAjoysXL65Q";
+        let udid_key: JWK = serde_json::from_str(ROOT_PLUS_1_SIGNING_KEY)?;
+
+        // Re-sign the level two DID by the level one DID with delegation constraints.
+        let target = |constraints: Constraints| -> Result<DIDChain, Box<dyn std::error::Error>> {
+            let (doc, _) = resolved_fixture(
+                TEST_ROOT_PLUS_2_DOCUMENT,
+                TEST_ROOT_PLUS_2_DOCUMENT_METADATA,
+            );
+            let payload = AttestationPayload::new(
+                hash(&canonicalize(&doc)?),
+                &AttestationClaims::new(Validity::default(), constraints),
+            );
+            let proof = ssi::jwt::encode_sign(Algorithm::ES256K, &payload, &udid_key)?;
+            let mut doc_meta: serde_json::Value =
+                serde_json::from_str(TEST_ROOT_PLUS_2_DOCUMENT_METADATA)?;
+            doc_meta["proof"]["proofValue"] = serde_json::Value::String(proof);
+            let mut target = test_chain();
+            target
+                .did_map
+                .insert(doc.id.clone(), (doc, serde_json::from_value(doc_meta)?));
+            Ok(target)
+        };
+
+        let constraints = Constraints {
+            max_depth: Some(0),
+            credential_types: Some(vec!["UniversityDegreeCredential".to_string()]),
+            service_endpoint_domain: Some("identity.foundation".to_string()),
+        };
+        let valid_target = target(constraints.clone())?;
+        assert!(valid_target.verify_proofs().is_ok());
+        assert_eq!(valid_target.constraints(root_plus_2), constraints);
+        assert!(valid_target
+            .constraints(valid_target.root())
+            .is_unconstrained());
+        assert!(valid_target
+            .verify_credential_types(&[
+                "VerifiableCredential".to_string(),
+                "UniversityDegreeCredential".to_string()
+            ])
+            .is_ok());
+        assert_eq!(
+            valid_target.verify_credential_types(&["IssuerCredential".to_string()]),
+            Err(ChainError::ConstraintViolation(
+                ConstraintViolation::CredentialTypeNotAllowed(
+                    root_plus_2.to_string(),
+                    "IssuerCredential".to_string()
+                )
+            ))
+        );
+
+        let invalid_target = target(Constraints {
+            service_endpoint_domain: Some("example.com".to_string()),
+            ..Default::default()
+        })?;
+        assert_eq!(
+            invalid_target.verify_proofs(),
+            Err(ChainError::ConstraintViolation(
+                ConstraintViolation::ServiceEndpointOutsideDomain(
+                    root_plus_2.to_string(),
+                    "https://identity.foundation/ion/trustchain-root-plus-2".to_string(),
+                    "example.com".to_string()
+                )
+            ))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_proofs_revoked() {
+        let root_plus_1 = "did:ion:test:EiBVpjUxXeSRJpvj2TewlX9zNF3GKMCKWwGmKBZqF6pk_A";
+        let root_plus_2 = "did:ion:test:EiAtHHKFJWAk5AsM3tgCut3OiBY4ekHTf66AAjoysXL65Q";
+        let udid_key: JWK = serde_json::from_str(ROOT_PLUS_1_SIGNING_KEY).unwrap();
+
+        // The level one DID publishes a revocation list, without invalidating its own proof.
+        let mut revocation_list = RevocationList::default();
+        let mut target = test_chain();
+        let (mut udid_doc, udid_doc_meta) = target.data(root_plus_1).unwrap().clone();
+        udid_doc
+            .service
+            .get_or_insert_with(Vec::new)
+            .push(revocation_list.to_service(root_plus_1, &udid_key).unwrap());
+        target.did_map.insert(
+            root_plus_1.to_string(),
+            (udid_doc.clone(), udid_doc_meta.clone()),
+        );
+        assert!(target.verify_proofs().is_ok());
+
+        // Revoking the level two DID rejects its link.
+        revocation_list.revoke_did(root_plus_2);
+        remove_revocation_service(&mut udid_doc);
+        udid_doc
+            .service
+            .get_or_insert_with(Vec::new)
+            .push(revocation_list.to_service(root_plus_1, &udid_key).unwrap());
+        target.did_map.insert(
+            root_plus_1.to_string(),
+            (udid_doc.clone(), udid_doc_meta.clone()),
+        );
+        assert_eq!(
+            target.verify_proofs(),
+            Err(ChainError::InvalidLink(
+                root_plus_2.to_string(),
+                Box::new(ChainError::RevokedAttestation(
+                    root_plus_2.to_string(),
+                    root_plus_1.to_string()
+                ))
+            ))
+        );
+
+        // A revocation list not signed by the level one DID is rejected.
+        remove_revocation_service(&mut udid_doc);
+        udid_doc.service.get_or_insert_with(Vec::new).push(
+            RevocationList::default()
+                .to_service(root_plus_1, &generate_key())
+                .unwrap(),
+        );
+        target
+            .did_map
+            .insert(root_plus_1.to_string(), (udid_doc, udid_doc_meta));
+        assert!(matches!(
+            target.verify_proofs(),
+            Err(ChainError::InvalidLink(_, err)) if matches!(*err, ChainError::InvalidRevocationList(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_verify_proofs_malformed_revocation_service() {
+        let root_plus_1 = "did:ion:test:EiBVpjUxXeSRJpvj2TewlX9zNF3GKMCKWwGmKBZqF6pk_A";
+        let udid_key: JWK = serde_json::from_str(ROOT_PLUS_1_SIGNING_KEY).unwrap();
+
+        // A service with the ID of the revocation list service but another type is not stripped
+        // from the attested document as a revocation list, but rejected.
+        let mut target = test_chain();
+        let (mut udid_doc, udid_doc_meta) = target.data(root_plus_1).unwrap().clone();
+        let mut service = RevocationList::default()
+            .to_service(root_plus_1, &udid_key)
+            .unwrap();
+        service.type_ = OneOrMany::One("LinkedDomains".to_string());
+        udid_doc.service.get_or_insert_with(Vec::new).push(service);
+        target
+            .did_map
+            .insert(root_plus_1.to_string(), (udid_doc, udid_doc_meta));
+        assert!(matches!(
+            target.verify_proofs(),
+            Err(ChainError::InvalidLink(_, err)) if matches!(*err, ChainError::InvalidRevocationList(ref did, _) if did == root_plus_1)
+        ));
+    }
+
     #[test]
     fn test_level() {
         // Test the level returned for each node in the test chain
@@ -563,6 +1121,22 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_run_task() {
+        assert_eq!(run_task(|| Ok(1)), Ok(1));
+        assert_eq!(
+            run_task::<()>(|| Err(ChainError::InvalidKeys)),
+            Err(ChainError::InvalidKeys)
+        );
+        // A panic in the task is returned as an error.
+        assert_eq!(
+            run_task::<()>(|| panic!("Task panicked.")),
+            Err(ChainError::VerificationTaskFailed(
+                "Task panicked.".to_string()
+            ))
+        );
+    }
+
     #[test]
     fn test_print_chain() -> Result<(), Box<dyn std::error::Error>> {
         let target = test_chain();
@@ -593,4 +1167,92 @@ mod tests {
             "did:ion:test:EiAtHHKFJWAk5AsM3tgCut3OiBY4ekHTf66AAjoysXL65Q"
         );
     }
+
+    #[test]
+    fn test_try_from_levels() {
+        let chain = test_chain();
+        let levels = chain.to_vec();
+        let rebuilt = DIDChain::try_from_levels(levels.clone()).unwrap();
+        assert_eq!(rebuilt.level_vec(), chain.level_vec());
+        assert!(rebuilt.verify_proofs().is_ok());
+
+        // Levels must start at the root.
+        let mut reversed = levels.clone();
+        reversed.reverse();
+        assert_eq!(
+            DIDChain::try_from_levels(reversed).unwrap_err(),
+            ChainError::BrokenLink(chain.leaf().to_string())
+        );
+
+        // Every DID must be controlled by the one before it.
+        let mut skipped = levels;
+        skipped.remove(1);
+        assert_eq!(
+            DIDChain::try_from_levels(skipped).unwrap_err(),
+            ChainError::BrokenLink(chain.leaf().to_string())
+        );
+
+        assert_eq!(
+            DIDChain::try_from_levels(vec![]).unwrap_err(),
+            ChainError::EmptyChain
+        );
+    }
+
+    #[tokio::test]
+    async fn test_new_max_depth() {
+        let resolver = FixtureResolver::new(vec![
+            resolved_fixture(TEST_ROOT_DOCUMENT, TEST_ROOT_DOCUMENT_METADATA),
+            resolved_fixture(
+                TEST_ROOT_PLUS_1_DOCUMENT,
+                TEST_ROOT_PLUS_1_DOCUMENT_METADATA,
+            ),
+            resolved_fixture(
+                TEST_ROOT_PLUS_2_DOCUMENT,
+                TEST_ROOT_PLUS_2_DOCUMENT_METADATA,
+            ),
+        ]);
+        let leaf = "did:ion:test:EiAtHHKFJWAk5AsM3tgCut3OiBY4ekHTf66AAjoysXL65Q";
+        let chain = DIDChain::new(leaf, &resolver).await.unwrap();
+        assert_eq!(chain.len(), 3);
+        assert!(DIDChain::new_with_max_depth(leaf, &resolver, 3)
+            .await
+            .is_ok());
+        assert_eq!(
+            DIDChain::new_with_max_depth(leaf, &resolver, 2)
+                .await
+                .unwrap_err(),
+            ChainError::MaxDepthExceeded(2)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_new_cycle() {
+        // The level one DID is controlled by the level two DID, which it controls.
+        let root_plus_1 = "did:ion:test:EiBVpjUxXeSRJpvj2TewlX9zNF3GKMCKWwGmKBZqF6pk_A";
+        let leaf = "did:ion:test:EiAtHHKFJWAk5AsM3tgCut3OiBY4ekHTf66AAjoysXL65Q";
+        let (mut doc, doc_meta) = resolved_fixture(
+            TEST_ROOT_PLUS_1_DOCUMENT,
+            TEST_ROOT_PLUS_1_DOCUMENT_METADATA,
+        );
+        doc.controller = Some(OneOrMany::One(leaf.to_string()));
+        let resolver = FixtureResolver::new(vec![
+            (doc.clone(), doc_meta.clone()),
+            resolved_fixture(
+                TEST_ROOT_PLUS_2_DOCUMENT,
+                TEST_ROOT_PLUS_2_DOCUMENT_METADATA,
+            ),
+        ]);
+        assert_eq!(
+            DIDChain::new(leaf, &resolver).await.unwrap_err(),
+            ChainError::CycleDetected(leaf.to_string())
+        );
+
+        // A DID controlled by itself.
+        doc.controller = Some(OneOrMany::One(root_plus_1.to_string()));
+        let resolver = FixtureResolver::new(vec![(doc, doc_meta)]);
+        assert_eq!(
+            DIDChain::new(root_plus_1, &resolver).await.unwrap_err(),
+            ChainError::CycleDetected(root_plus_1.to_string())
+        );
+    }
 }
@@ -0,0 +1,266 @@
+//! Delegation constraints on the DIDs downstream of an attestation.
+use crate::utils::HasEndpoints;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use ssi::did::{Document, ServiceEndpoint};
+use thiserror::Error;
+
+/// The credential type included in every verifiable credential, which is always allowed.
+const VERIFIABLE_CREDENTIAL_TYPE: &str = "VerifiableCredential";
+
+/// A violation of the delegation constraints of an attestation.
+#[derive(Error, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConstraintViolation {
+    /// More DIDs are downstream of the attested DID than allowed.
+    #[error("DID: {0} has {2} DIDs downstream, exceeding the maximum depth of {1}.")]
+    DepthExceeded(String, usize, usize),
+    /// A service endpoint is outside the required domain.
+    #[error("DID: {0} has service endpoint: {1} outside the required domain: {2}.")]
+    ServiceEndpointOutsideDomain(String, String, String),
+    /// A credential type is not allowed for the issuer.
+    #[error("Issuer DID: {0} is not allowed to issue credentials of type: {1}.")]
+    CredentialTypeNotAllowed(String, String),
+}
+
+/// Constraints on an attested DID and every DID downstream of it, signed in the attestation,
+/// similar to the path length and name constraints of X.509 certificates.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Constraints {
+    /// Maximum number of DIDs further down the chain from the attested DID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<usize>,
+    /// Credential types that the attested DID and the DIDs downstream of it may issue.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credential_types: Option<Vec<String>>,
+    /// Domain of the service endpoints of the attested DID and the DIDs downstream of it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service_endpoint_domain: Option<String>,
+}
+
+impl Constraints {
+    /// Returns whether there are no constraints.
+    pub fn is_unconstrained(&self) -> bool {
+        self.max_depth.is_none()
+            && self.credential_types.is_none()
+            && self.service_endpoint_domain.is_none()
+    }
+
+    /// Checks the DIDs from the attested DID down to the leaf of a chain against the depth and
+    /// service endpoint domain constraints.
+    pub fn check_downstream(&self, docs: &[&Document]) -> Result<(), ConstraintViolation> {
+        let Some((attested_doc, downstream)) = docs.split_first() else {
+            return Ok(());
+        };
+        if let Some(max_depth) = self.max_depth {
+            if downstream.len() > max_depth {
+                return Err(ConstraintViolation::DepthExceeded(
+                    attested_doc.id.to_owned(),
+                    max_depth,
+                    downstream.len(),
+                ));
+            }
+        }
+        if let Some(domain) = &self.service_endpoint_domain {
+            for doc in docs {
+                for endpoint in doc.get_endpoints().into_iter().flatten() {
+                    for uri in endpoint_uris(&endpoint) {
+                        if !is_in_domain(&uri, domain) {
+                            return Err(ConstraintViolation::ServiceEndpointOutsideDomain(
+                                doc.id.to_owned(),
+                                uri,
+                                domain.to_owned(),
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the types of a credential issued by a DID downstream of the attested DID against
+    /// the credential type constraint.
+    pub fn check_credential_types(
+        &self,
+        issuer: &str,
+        types: &[String],
+    ) -> Result<(), ConstraintViolation> {
+        let Some(allowed_types) = &self.credential_types else {
+            return Ok(());
+        };
+        match types.iter().find(|type_| {
+            type_.as_str() != VERIFIABLE_CREDENTIAL_TYPE && !allowed_types.contains(type_)
+        }) {
+            Some(type_) => Err(ConstraintViolation::CredentialTypeNotAllowed(
+                issuer.to_owned(),
+                type_.to_owned(),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Returns the URIs of a service endpoint, including every URI nested in a map endpoint (such as
+/// the `uri` of a DIDComm endpoint or the `origins` of a linked domains endpoint).
+fn endpoint_uris(endpoint: &ServiceEndpoint) -> Vec<String> {
+    fn collect_uris(value: &Value, uris: &mut Vec<String>) {
+        match value {
+            Value::String(string) if string.contains("://") => uris.push(string.to_owned()),
+            Value::Array(values) => values.iter().for_each(|value| collect_uris(value, uris)),
+            Value::Object(map) => map.values().for_each(|value| collect_uris(value, uris)),
+            _ => (),
+        }
+    }
+    match endpoint {
+        ServiceEndpoint::URI(uri) => vec![uri.to_owned()],
+        ServiceEndpoint::Map(value) => {
+            let mut uris = Vec::new();
+            collect_uris(value, &mut uris);
+            uris
+        }
+    }
+}
+
+/// Returns whether the host of a URI is the domain or one of its subdomains.
+fn is_in_domain(uri: &str, domain: &str) -> bool {
+    let authority = uri
+        .split_once("://")
+        .map_or(uri, |(_, rest)| rest)
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default();
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host)
+        .split(':')
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    let domain = domain.to_lowercase();
+    host == domain || host.ends_with(&format!(".{domain}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{TEST_ROOT_PLUS_1_DOCUMENT, TEST_ROOT_PLUS_2_DOCUMENT};
+
+    #[test]
+    fn test_is_in_domain() {
+        assert!(is_in_domain(
+            "https://identity.foundation/ion",
+            "identity.foundation"
+        ));
+        assert!(is_in_domain(
+            "https://a.identity.foundation:443",
+            "IDENTITY.foundation"
+        ));
+        assert!(is_in_domain(
+            "https://user@identity.foundation?q",
+            "identity.foundation"
+        ));
+        assert!(!is_in_domain(
+            "https://identity.foundation.evil.com",
+            "identity.foundation"
+        ));
+        assert!(!is_in_domain(
+            "https://evilidentity.foundation",
+            "identity.foundation"
+        ));
+    }
+
+    #[test]
+    fn test_check_downstream() {
+        let root_plus_1 = Document::from_json(TEST_ROOT_PLUS_1_DOCUMENT).unwrap();
+        let root_plus_2 = Document::from_json(TEST_ROOT_PLUS_2_DOCUMENT).unwrap();
+        let docs = [&root_plus_1, &root_plus_2];
+        assert!(Constraints::default().check_downstream(&docs).is_ok());
+
+        let constraints = Constraints {
+            max_depth: Some(1),
+            service_endpoint_domain: Some("identity.foundation".to_string()),
+            ..Default::default()
+        };
+        assert!(constraints.check_downstream(&docs).is_ok());
+
+        let constraints = Constraints {
+            max_depth: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(
+            constraints.check_downstream(&docs),
+            Err(ConstraintViolation::DepthExceeded(
+                root_plus_1.id.to_owned(),
+                0,
+                1
+            ))
+        );
+
+        let constraints = Constraints {
+            service_endpoint_domain: Some("example.com".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            constraints.check_downstream(&docs[1..]),
+            Err(ConstraintViolation::ServiceEndpointOutsideDomain(
+                root_plus_2.id.to_owned(),
+                "https://identity.foundation/ion/trustchain-root-plus-2".to_string(),
+                "example.com".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_check_downstream_map_endpoint() {
+        let mut doc = Document::from_json(TEST_ROOT_PLUS_2_DOCUMENT).unwrap();
+        doc.service = Some(vec![serde_json::from_value(serde_json::json!({
+            "id": "#linked-domain",
+            "type": "LinkedDomains",
+            "serviceEndpoint": {
+                "origins": ["https://identity.foundation", "https://example.com"]
+            }
+        }))
+        .unwrap()]);
+        let constraints = Constraints {
+            service_endpoint_domain: Some("identity.foundation".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            constraints.check_downstream(&[&doc]),
+            Err(ConstraintViolation::ServiceEndpointOutsideDomain(
+                doc.id.to_owned(),
+                "https://example.com".to_string(),
+                "identity.foundation".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_check_credential_types() {
+        let issuer = "did:example:issuer";
+        let types = vec![
+            VERIFIABLE_CREDENTIAL_TYPE.to_string(),
+            "UniversityDegreeCredential".to_string(),
+        ];
+        assert!(Constraints::default()
+            .check_credential_types(issuer, &types)
+            .is_ok());
+
+        let mut constraints = Constraints {
+            credential_types: Some(vec!["UniversityDegreeCredential".to_string()]),
+            ..Default::default()
+        };
+        assert!(constraints.check_credential_types(issuer, &types).is_ok());
+
+        constraints.credential_types = Some(vec!["IssuerCredential".to_string()]);
+        assert_eq!(
+            constraints.check_credential_types(issuer, &types),
+            Err(ConstraintViolation::CredentialTypeNotAllowed(
+                issuer.to_string(),
+                "UniversityDegreeCredential".to_string()
+            ))
+        );
+    }
+}
@@ -2,6 +2,7 @@
 pub mod attestor;
 pub mod chain;
 pub mod commitment;
+pub mod constraints;
 pub mod controller;
 pub mod data;
 pub mod display;
@@ -9,8 +10,14 @@ pub mod graph;
 pub mod holder;
 pub mod issuer;
 pub mod key_manager;
+pub mod multichain;
+pub mod policy;
+pub mod report;
 pub mod resolver;
+pub mod revocation;
 pub mod subject;
+#[cfg(test)]
+pub(crate) mod test_resolver;
 pub mod utils;
 pub mod vc;
 pub mod verifier;
@@ -30,3 +37,9 @@ pub const TRUSTCHAIN_PROOF_SERVICE_ID_VALUE: &str = "trustchain-controller-proof
 
 /// The value of the type for the service containing a Trustchain controller proof within a DID document.
 pub const TRUSTCHAIN_PROOF_SERVICE_TYPE_VALUE: &str = "TrustchainProofService";
+
+/// The value used for identifying a service containing a Trustchain revocation list within a DID document.
+pub const TRUSTCHAIN_REVOCATION_SERVICE_ID_VALUE: &str = "trustchain-revocation-list";
+
+/// The value of the type for the service containing a Trustchain revocation list within a DID document.
+pub const TRUSTCHAIN_REVOCATION_SERVICE_TYPE_VALUE: &str = "TrustchainRevocationList";
@@ -0,0 +1,619 @@
+//! `DIDMultiChain` type for DIDs attested by a threshold of several controllers.
+use crate::chain::{run_task, verify_attestation, ChainError, DIDChain, DEFAULT_MAX_CHAIN_DEPTH};
+use crate::resolver::TrustchainResolver;
+use crate::utils::current_time;
+use crate::verifier::Timestamp;
+use rayon::prelude::*;
+use ssi::did::Document;
+use ssi::did_resolve::DocumentMetadata;
+use ssi::one_or_many::OneOrMany;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Maximum number of paths from the roots to the leaf of a `DIDMultiChain`, bounding the work
+/// of verifying a DID whose controllers share many upstream DIDs.
+pub const MAX_ROOT_PATHS: usize = 64;
+
+/// Maximum number of DIDs resolved for a `DIDMultiChain`, bounding the work of resolving the
+/// upstream DIDs of a DID whose controllers list many DIDs.
+pub const MAX_RESOLUTIONS: usize = 256;
+
+/// Gets the distinct controllers of a DID document, in the order listed.
+fn get_controllers(doc: &Document) -> Vec<&String> {
+    let controllers: Vec<&String> = match doc.controller.as_ref() {
+        None => Vec::new(),
+        Some(OneOrMany::One(controller)) => vec![controller],
+        Some(OneOrMany::Many(controllers)) => controllers.iter().collect(),
+    };
+    let mut seen = HashSet::new();
+    controllers
+        .into_iter()
+        .filter(|controller| seen.insert(*controller))
+        .collect()
+}
+
+/// A struct for the DIDs upstream of a DID, through each of the controllers of every DID.
+///
+/// A DID holds one attestation per controller. The leaf, and any upstream DID with several
+/// controllers, is valid only if the attestations of at least `k` distinct controllers are
+/// valid, for a threshold `k`, however many controllers it lists. An upstream DID with a
+/// single controller is verified as in a `DIDChain`.
+#[derive(Debug, Clone)]
+pub struct DIDMultiChain {
+    // A map from DID strings to resolved tuples.
+    did_map: HashMap<String, (Document, DocumentMetadata)>,
+
+    // The DID from which the upstream DIDs were resolved.
+    leaf: String,
+
+    // The number of valid attestations required for the leaf and DIDs with several controllers.
+    threshold: usize,
+
+    // The controllers whose attestations count towards the threshold of the leaf, if pinned.
+    trusted_controllers: Option<HashSet<String>>,
+}
+
+impl DIDMultiChain {
+    /// Resolves the given DID and its upstream DIDs through all of their controllers. A
+    /// threshold of zero is treated as one.
+    pub async fn new(
+        did: &str,
+        resolver: &dyn TrustchainResolver,
+        threshold: usize,
+    ) -> Result<Self, ChainError> {
+        Self::new_with_max_depth(did, resolver, threshold, DEFAULT_MAX_CHAIN_DEPTH).await
+    }
+
+    /// Resolves the given DID and its upstream DIDs through all of their controllers, failing
+    /// if an upstream DID is not within `max_depth` levels of the given DID (itself included),
+    /// or if more than [`MAX_RESOLUTIONS`] DIDs are resolved. A controller that fails to resolve
+    /// invalidates only its own attestation, which counts against the threshold when verified.
+    pub async fn new_with_max_depth(
+        did: &str,
+        resolver: &dyn TrustchainResolver,
+        threshold: usize,
+        max_depth: usize,
+    ) -> Result<Self, ChainError> {
+        let mut did_map = HashMap::<String, (Document, DocumentMetadata)>::new();
+        let mut unresolved = HashSet::<String>::new();
+
+        // Resolve each DID once, from the passed DID up to the roots, with its depth.
+        let mut queue = VecDeque::from([(did.to_string(), 1)]);
+        while let Some((ddid, depth)) = queue.pop_front() {
+            if did_map.contains_key(&ddid) || unresolved.contains(&ddid) {
+                continue;
+            }
+            // Stop before resolving beyond the maximum depth or number of DIDs.
+            if depth > max_depth {
+                return Err(ChainError::MaxDepthExceeded(max_depth));
+            }
+            if did_map.len() + unresolved.len() == MAX_RESOLUTIONS {
+                return Err(ChainError::TooManyResolutions(MAX_RESOLUTIONS));
+            }
+            match resolver.resolve_as_result(&ddid).await {
+                Ok((_, Some(ddoc), Some(ddoc_meta))) => {
+                    queue.extend(
+                        get_controllers(&ddoc)
+                            .into_iter()
+                            .map(|udid| (udid.to_owned(), depth + 1)),
+                    );
+                    did_map.insert(ddid, (ddoc, ddoc_meta));
+                }
+                // The passed DID must resolve.
+                _ if ddid == did => return Err(ChainError::ResolutionFailure(ddid)),
+                // An upstream DID that fails to resolve fails the links to it.
+                _ => {
+                    unresolved.insert(ddid);
+                }
+            }
+        }
+        Ok(Self {
+            did_map,
+            leaf: did.to_string(),
+            threshold: threshold.max(1),
+            trusted_controllers: None,
+        })
+    }
+
+    /// Pins the controllers whose attestations count towards the threshold of the leaf, so that
+    /// the leaf cannot meet the threshold by listing other controllers.
+    pub fn with_trusted_controllers<I, S>(mut self, controllers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.trusted_controllers = Some(controllers.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Gets the DID from which the upstream DIDs were resolved.
+    pub fn leaf(&self) -> &str {
+        &self.leaf
+    }
+
+    /// Gets the number of valid attestations required for the leaf and for DIDs with several
+    /// controllers.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Gets data for the given DID.
+    pub fn data(&self, did: &str) -> Option<&(Document, DocumentMetadata)> {
+        self.did_map.get(did)
+    }
+
+    /// Gets the distinct controllers (upstream DIDs) of the given DID.
+    pub fn controllers(&self, did: &str) -> Vec<&String> {
+        self.data(did)
+            .map(|(doc, _)| get_controllers(doc))
+            .unwrap_or_default()
+    }
+
+    /// Verifies that every DID from the leaf up to the roots is attested by at least the
+    /// threshold of its controllers, and returns the chains from each root to the leaf along
+    /// the valid attestations.
+    pub fn verify_proofs(&self) -> Result<Vec<DIDChain>, ChainError> {
+        self.verify_proofs_at(current_time())
+    }
+
+    /// Verifies the attestations as in [`verify_proofs`](Self::verify_proofs), at the given
+    /// verification time.
+    pub fn verify_proofs_at(&self, time: Timestamp) -> Result<Vec<DIDChain>, ChainError> {
+        // Links as (DID, upstream DID) pairs.
+        let links: Vec<(&String, &String)> = self
+            .did_map
+            .keys()
+            .flat_map(|did| {
+                self.controllers(did)
+                    .into_iter()
+                    .map(move |udid| (did, udid))
+            })
+            .collect();
+
+        // Verify the links in parallel.
+        let results: HashMap<(&String, &String), Result<(), ChainError>> = links
+            .par_iter()
+            .map(|&(did, udid)| ((did, udid), run_task(|| self.verify_link(did, udid, time))))
+            .collect();
+
+        let root_paths = self.root_paths(&self.leaf, &mut RootPathSearch::new(&results))?;
+        let chains: Vec<DIDChain> = root_paths
+            .into_iter()
+            .map(|path| {
+                DIDChain::from_levels(
+                    path.into_iter()
+                        .map(|did| self.did_map[did].clone())
+                        .collect(),
+                )
+            })
+            .collect();
+
+        // The delegation constraints of the attestations must hold along every chain.
+        chains.iter().try_for_each(DIDChain::verify_constraints)?;
+        Ok(chains)
+    }
+
+    /// Verifies the proof of a DID by one of its controllers at the given time.
+    fn verify_link(&self, did: &str, udid: &str, time: Timestamp) -> Result<(), ChainError> {
+        let (did_doc, did_doc_meta) = self
+            .data(did)
+            .ok_or_else(|| ChainError::ResolutionFailure(did.to_string()))?;
+        let (udid_doc, _) = self
+            .data(udid)
+            .ok_or_else(|| ChainError::ResolutionFailure(udid.to_string()))?;
+        verify_attestation(did_doc, did_doc_meta, udid_doc, time).map(|_| ())
+    }
+
+    /// Gets the paths from the roots to the given DID along valid attestations, if the DID
+    /// and each of its upstream DIDs on the paths meet the threshold.
+    fn root_paths<'a>(
+        &'a self,
+        did: &'a String,
+        search: &mut RootPathSearch<'a, '_>,
+    ) -> Result<Vec<Vec<&'a String>>, ChainError> {
+        if let Some(result) = search.memo.get(did) {
+            return result.clone();
+        }
+        let cycles = search.cycles;
+        let result = self.search_root_paths(did, search);
+        // A result found without meeting a cycle does not depend on the path to the DID.
+        if search.cycles == cycles {
+            search.memo.insert(did, result.clone());
+        }
+        result
+    }
+
+    /// Searches the paths from the roots to the given DID, as in
+    /// [`root_paths`](Self::root_paths).
+    fn search_root_paths<'a>(
+        &'a self,
+        did: &'a String,
+        search: &mut RootPathSearch<'a, '_>,
+    ) -> Result<Vec<Vec<&'a String>>, ChainError> {
+        // A DID without a controller is a root.
+        let udids = self.controllers(did);
+        if udids.is_empty() {
+            return Ok(vec![vec![did]]);
+        }
+        let links = search.links;
+
+        // Only the attestations of pinned controllers count towards the threshold of the leaf.
+        let udids: Vec<&String> = match &self.trusted_controllers {
+            Some(trusted) if did == &self.leaf => udids
+                .into_iter()
+                .filter(|udid| trusted.contains(*udid))
+                .collect(),
+            _ => udids,
+        };
+        // The threshold holds however many controllers are listed, except along a single link.
+        let required = if did == &self.leaf || udids.len() > 1 {
+            self.threshold
+        } else {
+            1
+        };
+
+        search.path.push(did);
+        let mut root_paths = Vec::new();
+        let mut valid = 0;
+        let mut last_err = None;
+        for udid in &udids {
+            let result = match &links[&(did, *udid)] {
+                // A controller downstream of the DID forms a cycle and cannot attest it.
+                Ok(()) if search.path.contains(udid) => {
+                    search.cycles += 1;
+                    Err(ChainError::CycleDetected(udid.to_string()))
+                }
+                Ok(()) => self.root_paths(udid, search),
+                Err(err) => Err(ChainError::InvalidLink(
+                    did.to_owned(),
+                    Box::new(err.clone()),
+                )),
+            };
+            match result {
+                Ok(upstream_paths) => {
+                    valid += 1;
+                    root_paths.extend(upstream_paths.into_iter().map(|mut upstream_path| {
+                        upstream_path.push(did);
+                        upstream_path
+                    }));
+                    if root_paths.len() > MAX_ROOT_PATHS {
+                        search.path.pop();
+                        return Err(ChainError::TooManyRootPaths(MAX_ROOT_PATHS));
+                    }
+                }
+                // Too many paths is not an invalid attestation, but fails the whole search.
+                Err(err @ ChainError::TooManyRootPaths(_)) => {
+                    search.path.pop();
+                    return Err(err);
+                }
+                Err(err) => last_err = Some(err),
+            }
+        }
+        search.path.pop();
+
+        match (valid >= required, last_err) {
+            (true, _) => Ok(root_paths),
+            // Report the failure of a single controller as in a `DIDChain`.
+            (false, Some(err)) if udids.len() == 1 && required == 1 => Err(err),
+            (false, _) => Err(ChainError::ThresholdNotMet(did.to_owned(), valid, required)),
+        }
+    }
+}
+
+/// The state of a search for the paths from the roots to a DID in a `DIDMultiChain`.
+struct RootPathSearch<'a, 'b> {
+    // The verification result of each (DID, upstream DID) link.
+    links: &'b HashMap<(&'a String, &'a String), Result<(), ChainError>>,
+    // The DIDs from the leaf to the DID being searched.
+    path: Vec<&'a String>,
+    // The root paths of each DID already searched, shared by the paths through the DID.
+    memo: HashMap<&'a String, Result<Vec<Vec<&'a String>>, ChainError>>,
+    // The number of cycles detected, as root paths found through a cycle are not memoised.
+    cycles: usize,
+}
+
+impl<'a, 'b> RootPathSearch<'a, 'b> {
+    fn new(links: &'b HashMap<(&'a String, &'a String), Result<(), ChainError>>) -> Self {
+        Self {
+            links,
+            path: Vec::new(),
+            memo: HashMap::new(),
+            cycles: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::Chain;
+    use crate::data::{
+        ROOT_PLUS_1_SIGNING_KEY, TEST_ROOT_DOCUMENT, TEST_ROOT_DOCUMENT_METADATA,
+        TEST_ROOT_PLUS_1_DOCUMENT, TEST_ROOT_PLUS_1_DOCUMENT_METADATA, TEST_ROOT_PLUS_2_DOCUMENT,
+    };
+    use crate::test_resolver::FixtureResolver;
+    use crate::utils::{canonicalize, hash};
+    use serde_json::json;
+    use ssi::jwk::JWK;
+
+    const SECOND_ROOT_DID: &str = "did:example:second_root";
+
+    // Helper function returns a resolved tuple.
+    fn resolved_fixture(doc: &str, doc_meta: &str) -> (Document, DocumentMetadata) {
+        (
+            serde_json::from_str(doc).unwrap(),
+            serde_json::from_str(doc_meta).unwrap(),
+        )
+    }
+
+    // Helper function returns a root DID with a single verification key.
+    fn root_fixture(did: &str, key: &JWK) -> (Document, DocumentMetadata) {
+        let doc = json!({
+            "@context": "https://www.w3.org/ns/did/v1",
+            "id": did,
+            "verificationMethod": [{
+                "id": format!("{}#key-1", did),
+                "type": "JsonWebSignature2020",
+                "controller": did,
+                "publicKeyJwk": key.to_public()
+            }]
+        });
+        (
+            serde_json::from_value(doc).unwrap(),
+            serde_json::from_value(json!({})).unwrap(),
+        )
+    }
+
+    // Helper function returns the attestation of a DID document by one of its controllers.
+    fn attest(doc: &Document, controller: &str, key: &JWK) -> serde_json::Value {
+        let mut doc = doc.clone();
+        doc.controller = Some(OneOrMany::One(controller.to_string()));
+        let payload = hash(&canonicalize(&doc).unwrap());
+        let proof_value = ssi::jwt::encode_sign(key.get_algorithm().unwrap(), &payload, key);
+        json!({
+            "id": controller,
+            "type": "JsonWebSignature2020",
+            "proofValue": proof_value.unwrap()
+        })
+    }
+
+    // Helper function returns a multichain from the level one DID and a second root to a leaf.
+    fn multichain_fixture(second_root_key: &JWK, threshold: usize) -> DIDMultiChain {
+        let root_plus_1_key: JWK = serde_json::from_str(ROOT_PLUS_1_SIGNING_KEY).unwrap();
+        let (root_plus_1_doc, root_plus_1_doc_meta) = resolved_fixture(
+            TEST_ROOT_PLUS_1_DOCUMENT,
+            TEST_ROOT_PLUS_1_DOCUMENT_METADATA,
+        );
+        let second_root = root_fixture(SECOND_ROOT_DID, second_root_key);
+
+        // The leaf is attested by both the level one DID and the second root.
+        let mut doc: Document = serde_json::from_str(TEST_ROOT_PLUS_2_DOCUMENT).unwrap();
+        doc.controller = Some(OneOrMany::Many(vec![
+            root_plus_1_doc.id.to_owned(),
+            SECOND_ROOT_DID.to_string(),
+        ]));
+        let proofs = vec![
+            attest(&doc, &root_plus_1_doc.id, &root_plus_1_key),
+            attest(&doc, SECOND_ROOT_DID, second_root_key),
+        ];
+        let doc_meta: DocumentMetadata =
+            serde_json::from_value(json!({ "proof": proofs })).unwrap();
+
+        let mut did_map = HashMap::new();
+        for (doc, doc_meta) in [
+            resolved_fixture(TEST_ROOT_DOCUMENT, TEST_ROOT_DOCUMENT_METADATA),
+            (root_plus_1_doc, root_plus_1_doc_meta),
+            second_root,
+            (doc, doc_meta),
+        ] {
+            did_map.insert(doc.id.to_owned(), (doc, doc_meta));
+        }
+        DIDMultiChain {
+            did_map,
+            leaf: "did:ion:test:EiAtHHKFJWAk5AsM3tgCut3OiBY4ekHTf66AAjoysXL65Q".to_string(),
+            threshold,
+            trusted_controllers: None,
+        }
+    }
+
+    #[test]
+    fn test_verify_proofs() {
+        let second_root_key = JWK::generate_ed25519().unwrap();
+        let target = multichain_fixture(&second_root_key, 2);
+        assert_eq!(target.controllers(target.leaf()).len(), 2);
+
+        // Both attestations of the leaf are valid, giving a chain to each root.
+        let chains = target.verify_proofs().unwrap();
+        assert_eq!(chains.len(), 2);
+        assert_eq!(
+            chains[0].root(),
+            "did:ion:test:EiCClfEdkTv_aM3UnBBhlOV89LlGhpQAbfeZLFdFxVFkEg"
+        );
+        assert_eq!(chains[0].len(), 3);
+        assert_eq!(chains[1].root(), SECOND_ROOT_DID);
+        assert_eq!(chains[1].len(), 2);
+        for chain in chains {
+            assert_eq!(chain.leaf(), target.leaf());
+            assert!(chain.verify_proofs().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_verify_proofs_threshold() {
+        // The second root publishes a different key to the one attesting the leaf.
+        let mut target = multichain_fixture(&JWK::generate_ed25519().unwrap(), 2);
+        let (second_root_doc, _) = root_fixture(SECOND_ROOT_DID, &JWK::generate_ed25519().unwrap());
+        target.did_map.get_mut(SECOND_ROOT_DID).unwrap().0 = second_root_doc;
+        assert_eq!(
+            target.verify_proofs().unwrap_err(),
+            ChainError::ThresholdNotMet(target.leaf.to_owned(), 1, 2)
+        );
+
+        // One valid attestation meets a threshold of one.
+        target.threshold = 1;
+        let chains = target.verify_proofs().unwrap();
+        assert_eq!(chains.len(), 1);
+        assert_eq!(
+            chains[0].root(),
+            "did:ion:test:EiCClfEdkTv_aM3UnBBhlOV89LlGhpQAbfeZLFdFxVFkEg"
+        );
+    }
+
+    #[test]
+    fn test_verify_proofs_threshold_single_controller() {
+        let second_root_key = JWK::generate_ed25519().unwrap();
+        let mut target = multichain_fixture(&second_root_key, 2);
+
+        // Listing a single controller (twice) does not lower the threshold of the leaf.
+        let (doc, _) = target.did_map.get_mut(&target.leaf).unwrap();
+        let controller = get_controllers(doc)[0].to_owned();
+        doc.controller = Some(OneOrMany::Many(vec![controller.clone(), controller]));
+        assert_eq!(target.controllers(&target.leaf).len(), 1);
+        assert_eq!(
+            target.verify_proofs().unwrap_err(),
+            ChainError::ThresholdNotMet(target.leaf.to_owned(), 1, 2)
+        );
+    }
+
+    // Helper function returns a multichain of the given number of layers of two DIDs, each
+    // controlled by both DIDs of the layer above, with all links valid.
+    fn lattice_fixture(layers: usize) -> (DIDMultiChain, Vec<(String, String)>) {
+        let did = |layer: usize, i: usize| format!("did:example:{}-{}", layer, i);
+        let mut did_map = HashMap::new();
+        let mut links = Vec::new();
+        for layer in 0..=layers {
+            // The last layer is the leaf alone.
+            let width = if layer == layers { 1 } else { 2 };
+            for i in 0..width {
+                let controllers: Vec<String> = match layer {
+                    0 => vec![],
+                    _ => (0..2).map(|j| did(layer - 1, j)).collect(),
+                };
+                for controller in &controllers {
+                    links.push((did(layer, i), controller.to_owned()));
+                }
+                let doc = json!({
+                    "@context": "https://www.w3.org/ns/did/v1",
+                    "id": did(layer, i),
+                    "controller": controllers
+                });
+                did_map.insert(
+                    did(layer, i),
+                    (
+                        serde_json::from_value(doc).unwrap(),
+                        serde_json::from_value(json!({})).unwrap(),
+                    ),
+                );
+            }
+        }
+        let multichain = DIDMultiChain {
+            did_map,
+            leaf: did(layers, 0),
+            threshold: 1,
+            trusted_controllers: None,
+        };
+        (multichain, links)
+    }
+
+    #[test]
+    fn test_root_paths_max() {
+        // Each layer doubles the paths from the roots: 2^6 paths are within the maximum.
+        let (target, links) = lattice_fixture(6);
+        let results = links
+            .iter()
+            .map(|(did, udid)| ((did, udid), Ok(())))
+            .collect();
+        let root_paths = target
+            .root_paths(&target.leaf, &mut RootPathSearch::new(&results))
+            .unwrap();
+        assert_eq!(root_paths.len(), MAX_ROOT_PATHS);
+        assert!(root_paths.iter().all(|path| path.len() == 7));
+
+        let (target, links) = lattice_fixture(7);
+        let results = links
+            .iter()
+            .map(|(did, udid)| ((did, udid), Ok(())))
+            .collect();
+        assert_eq!(
+            target
+                .root_paths(&target.leaf, &mut RootPathSearch::new(&results))
+                .unwrap_err(),
+            ChainError::TooManyRootPaths(MAX_ROOT_PATHS)
+        );
+    }
+
+    #[test]
+    fn test_verify_proofs_trusted_controllers() {
+        let second_root_key = JWK::generate_ed25519().unwrap();
+        let target =
+            multichain_fixture(&second_root_key, 1).with_trusted_controllers([SECOND_ROOT_DID]);
+
+        // Only the attestation of the pinned controller counts.
+        let chains = target.verify_proofs().unwrap();
+        assert_eq!(chains.len(), 1);
+        assert_eq!(chains[0].root(), SECOND_ROOT_DID);
+
+        let target = target.with_trusted_controllers(["did:example:untrusted"]);
+        assert_eq!(
+            target.verify_proofs().unwrap_err(),
+            ChainError::ThresholdNotMet(target.leaf.to_owned(), 0, 1)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_new_unresolved_controller() {
+        // The second root of the leaf cannot be resolved.
+        let mut did_map = multichain_fixture(&JWK::generate_ed25519().unwrap(), 1).did_map;
+        did_map.remove(SECOND_ROOT_DID);
+        let resolver = FixtureResolver(did_map);
+        let leaf = "did:ion:test:EiAtHHKFJWAk5AsM3tgCut3OiBY4ekHTf66AAjoysXL65Q";
+
+        // The attestation of the resolved controller meets a threshold of one.
+        let target = DIDMultiChain::new(leaf, &resolver, 1).await.unwrap();
+        assert_eq!(target.controllers(leaf).len(), 2);
+        assert!(target.data(SECOND_ROOT_DID).is_none());
+        let chains = target.verify_proofs().unwrap();
+        assert_eq!(chains.len(), 1);
+        assert_eq!(
+            chains[0].root(),
+            "did:ion:test:EiCClfEdkTv_aM3UnBBhlOV89LlGhpQAbfeZLFdFxVFkEg"
+        );
+
+        // The unresolved controller counts against a threshold of two.
+        let target = DIDMultiChain::new(leaf, &resolver, 2).await.unwrap();
+        assert_eq!(
+            target.verify_proofs().unwrap_err(),
+            ChainError::ThresholdNotMet(leaf.to_string(), 1, 2)
+        );
+
+        // The leaf itself must resolve.
+        assert_eq!(
+            DIDMultiChain::new(SECOND_ROOT_DID, &resolver, 1)
+                .await
+                .unwrap_err(),
+            ChainError::ResolutionFailure(SECOND_ROOT_DID.to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_new_max_resolutions() {
+        // A leaf listing more controllers than can be resolved, none of which resolve.
+        let controllers: Vec<String> = (0..MAX_RESOLUTIONS)
+            .map(|i| format!("did:example:controller-{}", i))
+            .collect();
+        let doc = json!({
+            "@context": "https://www.w3.org/ns/did/v1",
+            "id": "did:example:leaf",
+            "controller": controllers
+        });
+        let resolver = FixtureResolver::new(vec![(
+            serde_json::from_value(doc).unwrap(),
+            serde_json::from_value(json!({})).unwrap(),
+        )]);
+        assert_eq!(
+            DIDMultiChain::new("did:example:leaf", &resolver, 1)
+                .await
+                .unwrap_err(),
+            ChainError::TooManyResolutions(MAX_RESOLUTIONS)
+        );
+    }
+}
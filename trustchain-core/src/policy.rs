@@ -0,0 +1,321 @@
+//! Declarative trust policy evaluated against verified DID chains.
+use crate::chain::{Chain, DIDChain};
+use serde::{Deserialize, Serialize};
+use ssi::did::{Document, VerificationMethod, VerificationMethodMap};
+use ssi::jwk::Algorithm;
+use ssi::one_or_many::OneOrMany;
+use std::path::Path;
+use thiserror::Error;
+
+/// An error relating to a trust policy.
+#[derive(Error, Debug)]
+pub enum PolicyError {
+    /// Failed to read a policy file.
+    #[error("Failed to read trust policy file: {0}")]
+    FailedToRead(std::io::Error),
+    /// Failed to parse a policy.
+    #[error("Failed to parse trust policy: {0}")]
+    FailedToParse(String),
+}
+
+impl From<std::io::Error> for PolicyError {
+    fn from(err: std::io::Error) -> Self {
+        PolicyError::FailedToRead(err)
+    }
+}
+
+/// A violation of a trust policy by a DID chain.
+#[derive(Error, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PolicyViolation {
+    /// The root DID is not an allowed root.
+    #[error("Root DID: {0} is not an allowed root.")]
+    RootNotAllowed(String),
+    /// The chain has more DIDs than allowed.
+    #[error("Chain of {0} DIDs exceeds the maximum length of {1}.")]
+    ChainTooLong(usize, usize),
+    /// A required DID is not at its level.
+    #[error("Required DID: {1} is not at level {0}.")]
+    MissingRequiredDID(usize, String),
+    /// A forbidden DID is at its level.
+    #[error("Forbidden DID: {1} is at level {0}.")]
+    ForbiddenDID(usize, String),
+    /// The verified DID has no service of a required type.
+    #[error("DID: {0} has no service of required type: {1}.")]
+    MissingServiceType(String, String),
+    /// A DID in the chain has a key with an algorithm that is not allowed.
+    #[error("DID: {0} has a key with algorithm: {1}, which is not allowed.")]
+    KeyAlgorithmNotAllowed(String, String),
+    /// A DID in the chain has a verification method without a public key JWK, so the
+    /// algorithm of its key cannot be checked.
+    #[error("DID: {0} has verification method: {1}, whose key algorithm cannot be inspected.")]
+    UninspectableVerificationMethod(String, String),
+}
+
+/// A DID at a level of a chain, where level 0 is the root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LevelRule {
+    pub level: usize,
+    pub did: String,
+}
+
+/// A trust policy, loaded from TOML or JSON, restricting the chains accepted on verification.
+///
+/// Every rule is optional, and the default policy accepts any chain.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TrustPolicy {
+    /// Root DIDs accepted, or any root if not set.
+    #[serde(default)]
+    pub allowed_roots: Option<Vec<String>>,
+    /// Maximum number of DIDs in the chain, including the root.
+    #[serde(default)]
+    pub max_chain_length: Option<usize>,
+    /// DIDs that must be at the given levels.
+    #[serde(default)]
+    pub required_dids: Vec<LevelRule>,
+    /// DIDs that must not be at the given levels.
+    #[serde(default)]
+    pub forbidden_dids: Vec<LevelRule>,
+    /// Service types the verified (leaf) DID must have.
+    #[serde(default)]
+    pub required_service_types: Vec<String>,
+    /// Algorithms accepted for the keys of every DID in the chain, or any if not set.
+    #[serde(default)]
+    pub allowed_key_algorithms: Option<Vec<Algorithm>>,
+}
+
+impl TrustPolicy {
+    /// Parses a policy from TOML.
+    pub fn from_toml(s: &str) -> Result<Self, PolicyError> {
+        toml::from_str(s).map_err(|err| PolicyError::FailedToParse(err.to_string()))
+    }
+
+    /// Parses a policy from JSON.
+    pub fn from_json(s: &str) -> Result<Self, PolicyError> {
+        serde_json::from_str(s).map_err(|err| PolicyError::FailedToParse(err.to_string()))
+    }
+
+    /// Reads a policy from a file, parsed as JSON if it has a `.json` extension and as TOML
+    /// otherwise.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, PolicyError> {
+        let contents = std::fs::read_to_string(&path)?;
+        match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Self::from_json(&contents),
+            _ => Self::from_toml(&contents),
+        }
+    }
+
+    /// Evaluates the policy against a DID chain, returning every violation.
+    pub fn violations(&self, chain: &DIDChain) -> Vec<PolicyViolation> {
+        let mut violations = Vec::new();
+        let levels = chain.level_vec();
+
+        if let Some(allowed_roots) = &self.allowed_roots {
+            if !allowed_roots.iter().any(|root| root == chain.root()) {
+                violations.push(PolicyViolation::RootNotAllowed(chain.root().to_string()));
+            }
+        }
+        if let Some(max_chain_length) = self.max_chain_length {
+            if chain.len() > max_chain_length {
+                violations.push(PolicyViolation::ChainTooLong(chain.len(), max_chain_length));
+            }
+        }
+        for rule in self.required_dids.iter() {
+            if levels.get(rule.level) != Some(&rule.did) {
+                violations.push(PolicyViolation::MissingRequiredDID(
+                    rule.level,
+                    rule.did.to_owned(),
+                ));
+            }
+        }
+        for rule in self.forbidden_dids.iter() {
+            if levels.get(rule.level) == Some(&rule.did) {
+                violations.push(PolicyViolation::ForbiddenDID(
+                    rule.level,
+                    rule.did.to_owned(),
+                ));
+            }
+        }
+        if let Some((leaf_doc, _)) = chain.data(chain.leaf()) {
+            for service_type in self.required_service_types.iter() {
+                if !has_service_type(leaf_doc, service_type) {
+                    violations.push(PolicyViolation::MissingServiceType(
+                        leaf_doc.id.to_owned(),
+                        service_type.to_owned(),
+                    ));
+                }
+            }
+        }
+        if let Some(allowed_key_algorithms) = &self.allowed_key_algorithms {
+            for (doc, _) in chain.to_vec() {
+                for verification_method in verification_methods(&doc) {
+                    let VerificationMethod::Map(VerificationMethodMap {
+                        public_key_jwk: Some(key),
+                        ..
+                    }) = verification_method
+                    else {
+                        // A key that cannot be inspected is not known to use an allowed algorithm.
+                        violations.push(PolicyViolation::UninspectableVerificationMethod(
+                            doc.id.to_owned(),
+                            verification_method.get_id(&doc.id),
+                        ));
+                        continue;
+                    };
+                    let algorithm = key.get_algorithm();
+                    if !algorithm.is_some_and(|alg| allowed_key_algorithms.contains(&alg)) {
+                        violations.push(PolicyViolation::KeyAlgorithmNotAllowed(
+                            doc.id.to_owned(),
+                            algorithm
+                                .map(|alg| format!("{alg:?}"))
+                                .unwrap_or_else(|| "unknown".to_string()),
+                        ));
+                    }
+                }
+            }
+        }
+        violations
+    }
+}
+
+/// Returns the verification methods of a DID document, together with those embedded in its
+/// verification relationships. References from the relationships to verification methods of the
+/// document are not repeated.
+fn verification_methods(doc: &Document) -> Vec<&VerificationMethod> {
+    let embedded = [
+        &doc.authentication,
+        &doc.assertion_method,
+        &doc.key_agreement,
+        &doc.capability_invocation,
+        &doc.capability_delegation,
+    ]
+    .into_iter()
+    .flatten()
+    .flatten()
+    .filter(|vm| matches!(vm, VerificationMethod::Map(_)));
+    doc.verification_method
+        .iter()
+        .flatten()
+        .chain(embedded)
+        .collect()
+}
+
+/// Returns whether a DID document has a service of the given type.
+fn has_service_type(doc: &Document, service_type: &str) -> bool {
+    doc.service
+        .iter()
+        .flatten()
+        .any(|service| match &service.type_ {
+            OneOrMany::One(type_) => type_ == service_type,
+            OneOrMany::Many(types) => types.iter().any(|type_| type_ == service_type),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::TEST_DID_CHAIN;
+
+    const ROOT: &str = "did:ion:test:EiCClfEdkTv_aM3UnBBhlOV89LlGhpQAbfeZLFdFxVFkEg";
+    const ROOT_PLUS_1: &str = "did:ion:test:EiBVpjUxXeSRJpvj2TewlX9zNF3GKMCKWwGmKBZqF6pk_A";
+
+    const TEST_POLICY_TOML: &str = r#"
+    allowed_roots = ["did:ion:test:EiCClfEdkTv_aM3UnBBhlOV89LlGhpQAbfeZLFdFxVFkEg"]
+    max_chain_length = 3
+    allowed_key_algorithms = ["ES256K"]
+
+    [[required_dids]]
+    level = 1
+    did = "did:ion:test:EiBVpjUxXeSRJpvj2TewlX9zNF3GKMCKWwGmKBZqF6pk_A"
+    "#;
+
+    fn test_chain() -> DIDChain {
+        serde_json::from_str(TEST_DID_CHAIN).unwrap()
+    }
+
+    #[test]
+    fn test_from_toml_and_json() {
+        let policy = TrustPolicy::from_toml(TEST_POLICY_TOML).unwrap();
+        assert_eq!(policy.allowed_roots, Some(vec![ROOT.to_string()]));
+        assert_eq!(policy.max_chain_length, Some(3));
+        assert_eq!(policy.allowed_key_algorithms, Some(vec![Algorithm::ES256K]));
+        assert_eq!(
+            policy.required_dids,
+            vec![LevelRule {
+                level: 1,
+                did: ROOT_PLUS_1.to_string()
+            }]
+        );
+        let json = serde_json::to_string(&policy).unwrap();
+        assert_eq!(TrustPolicy::from_json(&json).unwrap(), policy);
+        assert_eq!(TrustPolicy::from_toml("").unwrap(), TrustPolicy::default());
+        assert!(matches!(
+            TrustPolicy::from_toml("unknown_rule = true"),
+            Err(PolicyError::FailedToParse(_))
+        ));
+    }
+
+    #[test]
+    fn test_violations() {
+        let chain = test_chain();
+        let policy = TrustPolicy::from_toml(TEST_POLICY_TOML).unwrap();
+        assert!(policy.violations(&chain).is_empty());
+        assert!(TrustPolicy::default().violations(&chain).is_empty());
+
+        let policy = TrustPolicy {
+            allowed_roots: Some(vec![ROOT_PLUS_1.to_string()]),
+            max_chain_length: Some(2),
+            required_dids: vec![LevelRule {
+                level: 2,
+                did: ROOT_PLUS_1.to_string(),
+            }],
+            forbidden_dids: vec![LevelRule {
+                level: 0,
+                did: ROOT.to_string(),
+            }],
+            required_service_types: vec!["UnknownServiceType".to_string()],
+            allowed_key_algorithms: Some(vec![Algorithm::EdDSA]),
+        };
+        let violations = policy.violations(&chain);
+        assert_eq!(
+            violations[..5],
+            [
+                PolicyViolation::RootNotAllowed(ROOT.to_string()),
+                PolicyViolation::ChainTooLong(3, 2),
+                PolicyViolation::MissingRequiredDID(2, ROOT_PLUS_1.to_string()),
+                PolicyViolation::ForbiddenDID(0, ROOT.to_string()),
+                PolicyViolation::MissingServiceType(
+                    chain.leaf().to_string(),
+                    "UnknownServiceType".to_string()
+                ),
+            ]
+        );
+        // Every DID in the chain has a secp256k1 key.
+        assert!(violations[5..]
+            .iter()
+            .all(|v| matches!(v, PolicyViolation::KeyAlgorithmNotAllowed(_, _))));
+        assert_eq!(violations.len(), 8);
+    }
+
+    #[test]
+    fn test_violations_uninspectable_verification_method() {
+        let mut levels = test_chain().to_vec();
+        let (leaf_doc, _) = levels.last_mut().unwrap();
+        let key_id = format!("{}#external-key", leaf_doc.id);
+        leaf_doc
+            .verification_method
+            .get_or_insert_with(Vec::new)
+            .push(VerificationMethod::DIDURL(key_id.parse().unwrap()));
+        let leaf = leaf_doc.id.to_owned();
+        let chain = DIDChain::from_levels(levels);
+
+        let policy = TrustPolicy::from_toml(TEST_POLICY_TOML).unwrap();
+        assert_eq!(
+            policy.violations(&chain),
+            vec![PolicyViolation::UninspectableVerificationMethod(
+                leaf, key_id
+            )]
+        );
+        assert!(TrustPolicy::default().violations(&chain).is_empty());
+    }
+}
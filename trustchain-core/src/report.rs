@@ -0,0 +1,488 @@
+//! Structured per-level verification report for DID chains.
+use crate::chain::{run_task, verify_attestation, ChainError, DIDChain};
+use crate::constraints::ConstraintViolation;
+use crate::policy::{PolicyViolation, TrustPolicy};
+use crate::resolver::TrustchainResolver;
+use crate::utils::current_time;
+use crate::verifier::Timestamp;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use ssi::did::Document;
+use ssi::did_resolve::{DocumentMetadata, ResolutionMetadata};
+use ssi::one_or_many::OneOrMany;
+
+/// The status of the proof of a DID by its upstream DID (controller).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProofStatus {
+    /// The proof is valid.
+    Valid,
+    /// The DID is a root, verified by its timestamp instead of a proof.
+    Root,
+    /// No proof could be retrieved from the document metadata.
+    Missing,
+    /// The payload of the proof does not match the reconstructed document.
+    InvalidPayload,
+    /// No key of the upstream DID verifies the proof.
+    InvalidKeys,
+    /// The proof is revoked by the upstream DID.
+    Revoked,
+    /// The revocation list of the upstream DID could not be verified.
+    InvalidRevocationList,
+    /// The attestation is not valid until a later time.
+    NotYetValid,
+    /// The attestation has expired.
+    Expired,
+    /// The proof is valid, but a delegation constraint it signs is violated by the DID or the
+    /// DIDs downstream of it.
+    ConstraintViolation(ConstraintViolation),
+    /// The proof could not be verified, as the DID or its upstream DID was not resolved.
+    NotVerified,
+}
+
+impl From<&ChainError> for ProofStatus {
+    fn from(err: &ChainError) -> Self {
+        match err {
+            ChainError::FailureToGetProof => ProofStatus::Missing,
+            ChainError::InvalidPayload => ProofStatus::InvalidPayload,
+            ChainError::InvalidKeys => ProofStatus::InvalidKeys,
+            ChainError::RevokedAttestation(_, _) => ProofStatus::Revoked,
+            ChainError::InvalidRevocationList(_, _) => ProofStatus::InvalidRevocationList,
+            ChainError::AttestationNotYetValid(_, _) => ProofStatus::NotYetValid,
+            ChainError::AttestationExpired(_, _) => ProofStatus::Expired,
+            ChainError::ConstraintViolation(violation) => {
+                ProofStatus::ConstraintViolation(violation.to_owned())
+            }
+            _ => ProofStatus::NotVerified,
+        }
+    }
+}
+
+/// The verification result for a single DID in a chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LevelReport {
+    /// The DID at this level.
+    pub did: String,
+    /// The resolution metadata, if the DID was resolved.
+    pub resolution_metadata: Option<ResolutionMetadata>,
+    /// The DID document metadata, if the DID was resolved.
+    pub document_metadata: Option<DocumentMetadata>,
+    /// The reason resolution failed, if it did.
+    pub resolution_error: Option<String>,
+    /// The upstream DID (controller), if any.
+    pub controller: Option<String>,
+    /// The ID of the upstream verification method whose key verified the proof.
+    pub key_id: Option<String>,
+    /// The status of the proof by the upstream DID.
+    pub proof: ProofStatus,
+}
+
+impl LevelReport {
+    fn new(did: &str) -> Self {
+        Self {
+            did: did.to_string(),
+            resolution_metadata: None,
+            document_metadata: None,
+            resolution_error: None,
+            controller: None,
+            key_id: None,
+            proof: ProofStatus::NotVerified,
+        }
+    }
+
+    /// Returns whether the DID at this level is verified by its upstream DID, or is a root.
+    pub fn is_valid(&self) -> bool {
+        matches!(self.proof, ProofStatus::Valid | ProofStatus::Root)
+    }
+}
+
+/// The verification result for the timestamp of a root DID.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimestampReport {
+    /// The root DID.
+    pub did: String,
+    /// The expected root timestamp.
+    pub expected: Timestamp,
+    /// The verified timestamp of the root DID, if one was obtained.
+    pub actual: Option<Timestamp>,
+    /// The reason verification of the timestamp failed, if it did.
+    pub error: Option<String>,
+}
+
+impl TimestampReport {
+    /// Returns whether the root timestamp is verified.
+    pub fn is_valid(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// A report of the verification of every level in a DID chain, from the root (or the most
+/// upstream DID reached) to the verified DID.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainVerificationReport {
+    /// The verified (leaf) DID.
+    pub did: String,
+    /// The results for each DID in the chain, starting at the root.
+    pub levels: Vec<LevelReport>,
+    /// The result for the root timestamp, if a root was reached.
+    pub timestamp: Option<TimestampReport>,
+    /// The reason the chain could not be built, if it could not.
+    pub error: Option<String>,
+    /// The violations of the trust policy by the chain, if it was built.
+    #[serde(default)]
+    pub policy_violations: Vec<PolicyViolation>,
+}
+
+impl ChainVerificationReport {
+    /// Builds a report by resolving the chain from the given DID up to its root and verifying
+    /// the proof at each level. The root timestamp is not verified.
+    pub async fn new(did: &str, resolver: &dyn TrustchainResolver, max_depth: usize) -> Self {
+        Self::new_at(did, resolver, max_depth, None, current_time()).await
+    }
+
+    /// Builds a report as in [`ChainVerificationReport::new`], verifying the proofs at the given
+    /// verification time and evaluating the trust policy, if any, against the chain when it was
+    /// built up to its root.
+    pub async fn new_at(
+        did: &str,
+        resolver: &dyn TrustchainResolver,
+        max_depth: usize,
+        policy: Option<&TrustPolicy>,
+        time: Timestamp,
+    ) -> Self {
+        // Levels from the given DID upwards, with the resolved documents, as when building a
+        // `DIDChain`.
+        let (resolved, error) = DIDChain::resolve_levels(did, resolver, max_depth).await;
+        let mut levels: Vec<(LevelReport, Option<Document>)> = resolved
+            .into_iter()
+            .map(|(ddid, result)| {
+                let mut level = LevelReport::new(&ddid);
+                match result {
+                    Ok((res_meta, Some(ddoc), Some(ddoc_meta))) => {
+                        level.resolution_metadata = Some(res_meta);
+                        level.document_metadata = Some(ddoc_meta);
+                        match &ddoc.controller {
+                            // If there is no controller, this is the root.
+                            None => level.proof = ProofStatus::Root,
+                            Some(OneOrMany::One(udid)) => level.controller = Some(udid.to_owned()),
+                            Some(OneOrMany::Many(_)) => (),
+                        }
+                        (level, Some(ddoc))
+                    }
+                    Ok((res_meta, _, _)) => {
+                        level.resolution_error = Some(
+                            res_meta
+                                .error
+                                .to_owned()
+                                .unwrap_or_else(|| "Missing document or metadata.".to_string()),
+                        );
+                        level.resolution_metadata = Some(res_meta);
+                        (level, None)
+                    }
+                    Err(err) => {
+                        level.resolution_error = Some(err.to_string());
+                        (level, None)
+                    }
+                }
+            })
+            .collect();
+
+        // Verify the proof of each level by the next level up, in parallel.
+        let links: Vec<Option<(&Document, &DocumentMetadata, &Document)>> = levels
+            .iter()
+            .zip(levels.iter().skip(1).map(Some).chain([None]))
+            .map(|((level, ddoc), upstream)| {
+                let (_, Some(udoc)) = upstream? else {
+                    return None;
+                };
+                // The next level up is the controller, except at a detected cycle.
+                if level.controller.as_deref() != Some(udoc.id.as_str()) {
+                    return None;
+                }
+                Some((ddoc.as_ref()?, level.document_metadata.as_ref()?, udoc))
+            })
+            .collect();
+        let results: Vec<Option<Result<String, ChainError>>> = links
+            .par_iter()
+            .map(|link| {
+                link.map(|(ddoc, ddoc_meta, udoc)| {
+                    run_task(|| verify_attestation(ddoc, ddoc_meta, udoc, time))
+                })
+            })
+            .collect();
+        for ((level, _), result) in levels.iter_mut().zip(results) {
+            match result {
+                Some(Ok(key_id)) => {
+                    level.proof = ProofStatus::Valid;
+                    level.key_id = Some(key_id);
+                }
+                Some(Err(err)) => level.proof = ProofStatus::from(&err),
+                None => (),
+            }
+        }
+
+        // Order the levels starting at the root.
+        levels.reverse();
+
+        // The chain, if every level up to the root was resolved.
+        let chain = match error {
+            None => levels
+                .iter()
+                .map(|(level, ddoc)| Some((ddoc.clone()?, level.document_metadata.clone()?)))
+                .collect::<Option<Vec<(Document, DocumentMetadata)>>>()
+                .map(DIDChain::from_levels),
+            Some(_) => None,
+        };
+
+        // Enforce the delegation constraints of the valid attestations against the chain.
+        if let Some(chain) = &chain {
+            for (index, (level, _)) in levels.iter_mut().enumerate().skip(1) {
+                if level.proof != ProofStatus::Valid {
+                    continue;
+                }
+                if let Err(violation) = chain.check_constraints(index) {
+                    level.proof = ProofStatus::ConstraintViolation(violation);
+                }
+            }
+        }
+
+        // Evaluate the policy against the chain.
+        let policy_violations = match (policy, &chain) {
+            (Some(policy), Some(chain)) => policy.violations(chain),
+            _ => Vec::new(),
+        };
+
+        Self {
+            did: did.to_string(),
+            levels: levels.into_iter().map(|(level, _)| level).collect(),
+            timestamp: None,
+            error: error.map(|err| err.to_string()),
+            policy_violations,
+        }
+    }
+
+    /// Gets the root DID, if the chain was built up to a root.
+    pub fn root(&self) -> Option<&str> {
+        self.levels
+            .first()
+            .filter(|level| level.proof == ProofStatus::Root)
+            .map(|level| level.did.as_str())
+    }
+
+    /// Returns whether the chain was built, every level is verified, the root timestamp is
+    /// verified and the chain does not violate the trust policy.
+    pub fn is_valid(&self) -> bool {
+        self.error.is_none()
+            && self.policy_violations.is_empty()
+            && self.levels.iter().all(LevelReport::is_valid)
+            && self
+                .timestamp
+                .as_ref()
+                .is_some_and(TimestampReport::is_valid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attestor::{AttestationClaims, AttestationPayload, Validity};
+    use crate::chain::DEFAULT_MAX_CHAIN_DEPTH;
+    use crate::constraints::Constraints;
+    use crate::data::{
+        ROOT_PLUS_1_SIGNING_KEY, TEST_ROOT_DOCUMENT, TEST_ROOT_DOCUMENT_METADATA,
+        TEST_ROOT_PLUS_1_DOCUMENT, TEST_ROOT_PLUS_1_DOCUMENT_METADATA, TEST_ROOT_PLUS_2_DOCUMENT,
+        TEST_ROOT_PLUS_2_DOCUMENT_METADATA,
+    };
+    use crate::test_resolver::FixtureResolver;
+    use crate::utils::{canonicalize, hash};
+    use ssi::jwk::{Algorithm, JWK};
+
+    const ROOT: &str = "did:ion:test:EiCClfEdkTv_aM3UnBBhlOV89LlGhpQAbfeZLFdFxVFkEg";
+    const ROOT_PLUS_1: &str = "did:ion:test:EiBVpjUxXeSRJpvj2TewlX9zNF3GKMCKWwGmKBZqF6pk_A";
+    const ROOT_PLUS_2: &str = "did:ion:test:EiAtHHKFJWAk5AsM3tgCut3OiBY4ekHTf66AAjoysXL65Q";
+
+    #[tokio::test]
+    async fn test_new() {
+        let resolver = FixtureResolver::from_json(&[
+            (TEST_ROOT_DOCUMENT, TEST_ROOT_DOCUMENT_METADATA),
+            (
+                TEST_ROOT_PLUS_1_DOCUMENT,
+                TEST_ROOT_PLUS_1_DOCUMENT_METADATA,
+            ),
+            (
+                TEST_ROOT_PLUS_2_DOCUMENT,
+                TEST_ROOT_PLUS_2_DOCUMENT_METADATA,
+            ),
+        ]);
+        let report =
+            ChainVerificationReport::new(ROOT_PLUS_2, &resolver, DEFAULT_MAX_CHAIN_DEPTH).await;
+        assert!(report.error.is_none());
+        assert_eq!(report.root(), Some(ROOT));
+        let dids: Vec<&str> = report.levels.iter().map(|l| l.did.as_str()).collect();
+        assert_eq!(dids, vec![ROOT, ROOT_PLUS_1, ROOT_PLUS_2]);
+        assert_eq!(report.levels[0].proof, ProofStatus::Root);
+        assert!(report.levels[0].key_id.is_none());
+        for (level, controller) in report.levels[1..].iter().zip([ROOT, ROOT_PLUS_1]) {
+            assert_eq!(level.proof, ProofStatus::Valid);
+            assert_eq!(level.controller.as_deref(), Some(controller));
+            assert!(level.key_id.as_ref().unwrap().starts_with(controller));
+        }
+        // Not valid until the root timestamp is verified.
+        assert!(!report.is_valid());
+    }
+
+    #[tokio::test]
+    async fn test_new_at() {
+        let resolver = FixtureResolver::from_json(&[
+            (TEST_ROOT_DOCUMENT, TEST_ROOT_DOCUMENT_METADATA),
+            (
+                TEST_ROOT_PLUS_1_DOCUMENT,
+                TEST_ROOT_PLUS_1_DOCUMENT_METADATA,
+            ),
+            (
+                TEST_ROOT_PLUS_2_DOCUMENT,
+                TEST_ROOT_PLUS_2_DOCUMENT_METADATA,
+            ),
+        ]);
+        let policy = TrustPolicy {
+            max_chain_length: Some(2),
+            ..Default::default()
+        };
+        let report = ChainVerificationReport::new_at(
+            ROOT_PLUS_2,
+            &resolver,
+            DEFAULT_MAX_CHAIN_DEPTH,
+            Some(&policy),
+            current_time(),
+        )
+        .await;
+        assert!(report.levels.iter().all(LevelReport::is_valid));
+        assert_eq!(
+            report.policy_violations,
+            vec![PolicyViolation::ChainTooLong(3, 2)]
+        );
+
+        // The policy is not evaluated against a chain that was not built.
+        let report = ChainVerificationReport::new_at(
+            ROOT_PLUS_2,
+            &resolver,
+            1,
+            Some(&policy),
+            current_time(),
+        )
+        .await;
+        assert!(report.policy_violations.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_new_constraint_violation() {
+        // Re-sign the level two DID by the level one DID, constraining its service endpoints to
+        // another domain.
+        let mut resolver = FixtureResolver::from_json(&[
+            (TEST_ROOT_DOCUMENT, TEST_ROOT_DOCUMENT_METADATA),
+            (
+                TEST_ROOT_PLUS_1_DOCUMENT,
+                TEST_ROOT_PLUS_1_DOCUMENT_METADATA,
+            ),
+            (
+                TEST_ROOT_PLUS_2_DOCUMENT,
+                TEST_ROOT_PLUS_2_DOCUMENT_METADATA,
+            ),
+        ]);
+        let udid_key: JWK = serde_json::from_str(ROOT_PLUS_1_SIGNING_KEY).unwrap();
+        let doc = Document::from_json(TEST_ROOT_PLUS_2_DOCUMENT).unwrap();
+        let constraints = Constraints {
+            service_endpoint_domain: Some("example.com".to_string()),
+            ..Default::default()
+        };
+        let payload = AttestationPayload::new(
+            hash(&canonicalize(&doc).unwrap()),
+            &AttestationClaims::new(Validity::default(), constraints),
+        );
+        let proof = ssi::jwt::encode_sign(Algorithm::ES256K, &payload, &udid_key).unwrap();
+        let mut doc_meta: serde_json::Value =
+            serde_json::from_str(TEST_ROOT_PLUS_2_DOCUMENT_METADATA).unwrap();
+        doc_meta["proof"]["proofValue"] = serde_json::Value::String(proof);
+        resolver.0.get_mut(ROOT_PLUS_2).unwrap().1 = serde_json::from_value(doc_meta).unwrap();
+
+        let report =
+            ChainVerificationReport::new(ROOT_PLUS_2, &resolver, DEFAULT_MAX_CHAIN_DEPTH).await;
+        assert!(report.error.is_none());
+        assert_eq!(report.levels[1].proof, ProofStatus::Valid);
+        assert_eq!(
+            report.levels[2].proof,
+            ProofStatus::ConstraintViolation(ConstraintViolation::ServiceEndpointOutsideDomain(
+                ROOT_PLUS_2.to_string(),
+                "https://identity.foundation/ion/trustchain-root-plus-2".to_string(),
+                "example.com".to_string()
+            ))
+        );
+        // The proof itself is genuine.
+        assert!(report.levels[2].key_id.is_some());
+        assert!(!report.levels[2].is_valid());
+    }
+
+    #[tokio::test]
+    async fn test_new_invalid_link() {
+        // Replace the proof of the level one DID with the proof of the level two DID.
+        let mut resolver = FixtureResolver::from_json(&[
+            (TEST_ROOT_DOCUMENT, TEST_ROOT_DOCUMENT_METADATA),
+            (
+                TEST_ROOT_PLUS_1_DOCUMENT,
+                TEST_ROOT_PLUS_1_DOCUMENT_METADATA,
+            ),
+            (
+                TEST_ROOT_PLUS_2_DOCUMENT,
+                TEST_ROOT_PLUS_2_DOCUMENT_METADATA,
+            ),
+        ]);
+        let leaf_meta = resolver.0.get(ROOT_PLUS_2).unwrap().1.clone();
+        resolver.0.get_mut(ROOT_PLUS_1).unwrap().1 = leaf_meta;
+        let report =
+            ChainVerificationReport::new(ROOT_PLUS_2, &resolver, DEFAULT_MAX_CHAIN_DEPTH).await;
+        assert!(report.error.is_none());
+        assert_eq!(report.levels[1].proof, ProofStatus::InvalidPayload);
+        assert!(report.levels[1].key_id.is_none());
+        assert_eq!(report.levels[2].proof, ProofStatus::Valid);
+        assert!(!report.is_valid());
+    }
+
+    #[tokio::test]
+    async fn test_new_resolution_failure() {
+        // The root cannot be resolved.
+        let resolver = FixtureResolver::from_json(&[
+            (
+                TEST_ROOT_PLUS_1_DOCUMENT,
+                TEST_ROOT_PLUS_1_DOCUMENT_METADATA,
+            ),
+            (
+                TEST_ROOT_PLUS_2_DOCUMENT,
+                TEST_ROOT_PLUS_2_DOCUMENT_METADATA,
+            ),
+        ]);
+        let report =
+            ChainVerificationReport::new(ROOT_PLUS_2, &resolver, DEFAULT_MAX_CHAIN_DEPTH).await;
+        assert_eq!(
+            report.error,
+            Some(ChainError::ResolutionFailure(ROOT.to_string()).to_string())
+        );
+        assert!(report.root().is_none());
+        assert_eq!(report.levels.len(), 3);
+        assert_eq!(report.levels[0].did, ROOT);
+        assert!(report.levels[0].resolution_error.is_some());
+        assert_eq!(report.levels[1].proof, ProofStatus::NotVerified);
+        assert_eq!(report.levels[2].proof, ProofStatus::Valid);
+
+        // The maximum depth is reached before the root.
+        let report = ChainVerificationReport::new(ROOT_PLUS_2, &resolver, 1).await;
+        assert_eq!(
+            report.error,
+            Some(ChainError::MaxDepthExceeded(1).to_string())
+        );
+        assert_eq!(report.levels.len(), 1);
+        assert_eq!(report.levels[0].proof, ProofStatus::NotVerified);
+    }
+}
@@ -1,14 +1,19 @@
 //! DID resolution and `DIDResolver` implementation.
+use crate::utils::get_did_method;
 use crate::TRUSTCHAIN_PROOF_SERVICE_ID_VALUE;
 use async_trait::async_trait;
+use did_jwk::DIDJWK;
 use did_method_key::DIDKey;
+use did_web::DIDWeb;
 use serde_json::Value;
 use ssi::did::{Document, Service, ServiceEndpoint};
 use ssi::did_resolve::{
     DIDResolver, DocumentMetadata, Metadata, ResolutionInputMetadata, ResolutionMetadata,
+    ERROR_METHOD_NOT_SUPPORTED,
 };
 use ssi::one_or_many::OneOrMany;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 use thiserror::Error;
 
 /// An error relating to Trustchain resolution.
@@ -20,7 +25,7 @@ pub enum ResolverError {
     /// Failed to convert to Trustchain document and metadata.
     #[error("Failed to convert to Trustchain document and metadata: {0}")]
     FailedToConvertToTrustchain(String),
-    /// Multiple Trustchain proof service entries are present.
+    /// Multiple Trustchain proof service entries are present with the same ID or controller.
     #[error("Multiple Trustchain proof service entries are present.")]
     MultipleTrustchainProofService,
     /// No Trustchain proof service is present.
@@ -35,6 +40,9 @@ pub enum ResolverError {
     /// DID is not found.
     #[error("DID: {0} is not found.")]
     DIDNotFound(String),
+    /// No resolver is available for the DID method.
+    #[error("DID method not supported for DID: {0}")]
+    MethodNotSupported(String),
     /// General resolver error with resolution metadata.
     #[error("Resolver error with resolution metadata.")]
     FailureWithMetadata(ResolutionMetadata),
@@ -66,33 +74,47 @@ fn add_controller(mut doc: Document, controller_did: &str) -> Result<Document, R
     Ok(doc)
 }
 
-/// Gets a result of an index of a single Trustchain proof service, otherwise relevant error.
-fn get_proof_idx(doc: &Document) -> Result<usize, ResolverError> {
+/// Checks whether the fragment of a service ID identifies a Trustchain proof service. A DID
+/// with several controllers has one proof service per controller, with distinct IDs such as
+/// `#trustchain-controller-proof` and `#trustchain-controller-proof-2`.
+fn is_proof_service_fragment(fragment: &str) -> bool {
+    match fragment.strip_prefix(TRUSTCHAIN_PROOF_SERVICE_ID_VALUE) {
+        Some(suffix) => suffix.is_empty() || suffix.starts_with('-'),
+        None => false,
+    }
+}
+
+/// Gets a result of the indices of the Trustchain proof services, otherwise relevant error.
+fn get_proof_idxs(doc: &Document) -> Result<Vec<usize>, ResolverError> {
     let mut idxs: Vec<usize> = Vec::new();
-    let fragment = TRUSTCHAIN_PROOF_SERVICE_ID_VALUE;
+    let mut ids: HashSet<&str> = HashSet::new();
+    let mut controllers: HashSet<&String> = HashSet::new();
     for (idx, service) in doc.service.iter().flatten().enumerate() {
         if let [service_fragment, _] = service.id.rsplitn(2, '#').collect::<Vec<&str>>().as_slice()
         {
-            if service_fragment == &fragment {
+            if is_proof_service_fragment(service_fragment) {
+                // Each controller may only have a single proof service.
+                let is_duplicate = !ids.insert(service_fragment)
+                    || get_from_proof_service(service, "controller")
+                        .map_or(false, |controller| !controllers.insert(controller));
+                if is_duplicate {
+                    return Err(ResolverError::MultipleTrustchainProofService);
+                }
                 idxs.push(idx);
             }
         }
     }
     match idxs.len() {
         0 => Err(ResolverError::NoTrustchainProofService),
-        1 => Ok(idxs[0]),
-        _ => Err(ResolverError::MultipleTrustchainProofService),
+        _ => Ok(idxs),
     }
 }
 
-/// Gets a result of a reference to a single Trustchain proof service, otherwise relevant error.
-fn get_proof_service(doc: &Document) -> Result<&Service, ResolverError> {
-    // Extract proof service as an owned service
-    let idxs = get_proof_idx(doc);
-    match idxs {
-        Ok(idx) => Ok(&doc.service.as_ref().unwrap()[idx]),
-        Err(e) => Err(e),
-    }
+/// Gets a result of references to the Trustchain proof services, otherwise relevant error.
+fn get_proof_services(doc: &Document) -> Result<Vec<&Service>, ResolverError> {
+    let idxs = get_proof_idxs(doc)?;
+    let services = doc.service.as_ref().unwrap();
+    Ok(idxs.into_iter().map(|idx| &services[idx]).collect())
 }
 
 /// Gets the value of a key in a Trustchain proof service.
@@ -108,64 +130,121 @@ fn get_from_proof_service<'a>(proof_service: &'a Service, key: &str) -> Option<&
     value
 }
 
-/// Adds a proof from a DID Document to DocumentMetadata.
+/// Gets the proof of a Trustchain proof service as DocumentMetadata.
+fn proof_metadata(proof_service: &Service) -> Option<Metadata> {
+    // Get proof value and controller (uDID)
+    let proof_value = get_from_proof_service(proof_service, "proofValue")?;
+    let controller = get_from_proof_service(proof_service, "controller")?;
+
+    // Make new HashMap; add keys and values
+    let mut proof_hash_map: HashMap<String, Metadata> = HashMap::new();
+    proof_hash_map.insert(String::from("id"), Metadata::String(controller.to_owned()));
+    proof_hash_map.insert(
+        String::from("type"),
+        Metadata::String("JsonWebSignature2020".to_string()),
+    );
+    proof_hash_map.insert(
+        String::from("proofValue"),
+        Metadata::String(proof_value.to_owned()),
+    );
+    Some(Metadata::Map(proof_hash_map))
+}
+
+/// Adds the proofs from a DID Document to DocumentMetadata. A single proof is added as a map
+/// and the proofs of several controllers as a list of maps.
 fn add_proof(doc: &Document, mut doc_meta: DocumentMetadata) -> DocumentMetadata {
-    // Get proof service
-    let proof_service = get_proof_service(doc);
+    // Get proof services
+    let proof_services = get_proof_services(doc);
 
     // Handle result
-    if let Ok(proof_service) = proof_service {
-        // Get proof value and controller (uDID)
-        let proof_value = get_from_proof_service(proof_service, "proofValue");
-        let controller = get_from_proof_service(proof_service, "controller");
-        // If not None, add to new HashMap
-        if let (Some(property_set), Some(proof_value), Some(controller)) =
-            (doc_meta.property_set.as_mut(), proof_value, controller)
-        {
-            // Make new HashMap; add keys and values
-            let mut proof_hash_map: HashMap<String, Metadata> = HashMap::new();
-            proof_hash_map.insert(String::from("id"), Metadata::String(controller.to_owned()));
-            proof_hash_map.insert(
-                String::from("type"),
-                Metadata::String("JsonWebSignature2020".to_string()),
-            );
-            proof_hash_map.insert(
-                String::from("proofValue"),
-                Metadata::String(proof_value.to_owned()),
-            );
-
-            // Insert new HashMap of Metadata::Map()
-            property_set.insert(String::from("proof"), Metadata::Map(proof_hash_map));
-            return doc_meta;
+    if let (Ok(proof_services), Some(property_set)) =
+        (proof_services, doc_meta.property_set.as_mut())
+    {
+        let proofs: Option<Vec<Metadata>> =
+            proof_services.into_iter().map(proof_metadata).collect();
+        // Insert the proofs, unless any of them is incomplete
+        match proofs {
+            Some(mut proofs) if proofs.len() == 1 => {
+                property_set.insert(String::from("proof"), proofs.remove(0));
+            }
+            Some(proofs) => {
+                property_set.insert(String::from("proof"), Metadata::List(proofs));
+            }
+            None => (),
         }
     }
-    // If there are zero or multiple proof services, do nothing
+    // If there are no valid proof services, do nothing
     doc_meta
 }
 
-/// Removes Trustchain proof service from passed document if it exists.
+/// Removes Trustchain proof services from passed document if they exist.
 fn remove_proof_service(mut doc: Document) -> Document {
-    if doc.service.is_some() {
-        let idx_result = get_proof_idx(&doc);
-        if let Ok(idx) = idx_result {
-            let services = doc.service.as_mut().unwrap();
-            services.remove(idx);
-            if services.is_empty() {
-                doc.service = None;
-            }
+    if let Some(services) = doc.service.as_mut() {
+        services.retain(|service| {
+            !matches!(
+                service.id.rsplitn(2, '#').collect::<Vec<&str>>().as_slice(),
+                [service_fragment, _] if is_proof_service_fragment(service_fragment)
+            )
+        });
+        if services.is_empty() {
+            doc.service = None;
         }
     }
     doc
 }
 
+/// Gets the fragment ID of the Trustchain proof service of a controller in a DID document as
+/// stored by its DID method, and whether the controller already has a proof service with that
+/// ID. A controller without one is given the first free ID of `trustchain-controller-proof`,
+/// `trustchain-controller-proof-2`, `trustchain-controller-proof-3` and so on.
+pub fn proof_service_id(doc: &Document, controller: &str) -> (String, bool) {
+    let mut ids: HashSet<&str> = HashSet::new();
+    for service in doc.service.iter().flatten() {
+        if let [service_fragment, _] = service.id.rsplitn(2, '#').collect::<Vec<&str>>().as_slice()
+        {
+            if is_proof_service_fragment(service_fragment) {
+                if get_from_proof_service(service, "controller").map(String::as_str)
+                    == Some(controller)
+                {
+                    return (service_fragment.to_string(), true);
+                }
+                ids.insert(service_fragment);
+            }
+        }
+    }
+    let mut id = TRUSTCHAIN_PROOF_SERVICE_ID_VALUE.to_string();
+    let mut n = 1;
+    while ids.contains(id.as_str()) {
+        n += 1;
+        id = format!("{}-{}", TRUSTCHAIN_PROOF_SERVICE_ID_VALUE, n);
+    }
+    (id, false)
+}
+
+/// Adds the controller property to a resolved DID document with several controllers, one for
+/// each Trustchain proof service.
+fn add_controllers(mut doc: Document, controller_dids: &[&str]) -> Result<Document, ResolverError> {
+    if let [controller_did] = controller_dids {
+        return add_controller(doc, controller_did);
+    }
+    // Check controller is empty and if not throw error.
+    if doc.controller.is_some() {
+        return Err(ResolverError::ControllerAlreadyPresent);
+    }
+    doc.controller = Some(OneOrMany::Many(
+        controller_dids.iter().map(|did| did.to_string()).collect(),
+    ));
+    Ok(doc)
+}
+
 /// Converts a DID Document from a resolved DID to the Trustchain resolved format.
-fn transform_doc(doc: &Document, controller_did: &str) -> Document {
+fn transform_doc(doc: &Document, controller_dids: &[&str]) -> Document {
     // Clone the passed DID document.
     let doc_clone = doc.clone();
 
-    // Add controller
-    let doc_clone =
-        add_controller(doc_clone, controller_did).expect("Controller already present in document.");
+    // Add controllers
+    let doc_clone = add_controllers(doc_clone, controller_dids)
+        .expect("Controller already present in document.");
 
     // Remove the proof service from the document.
     remove_proof_service(doc_clone)
@@ -183,19 +262,27 @@ fn transform_as_result(
     sidetree_doc: Document,
     sidetree_doc_meta: DocumentMetadata,
 ) -> Result<(ResolutionMetadata, Document, DocumentMetadata), ResolverError> {
-    // Get controller DID
-    let service = get_proof_service(&sidetree_doc);
+    // Get controller DIDs
+    let services = get_proof_services(&sidetree_doc);
 
-    // Return immediately multiple proof services present
-    if let Err(ResolverError::MultipleTrustchainProofService) = service {
+    // Return immediately if several proof services are present for the same controller
+    if let Err(ResolverError::MultipleTrustchainProofService) = services {
         return Err(ResolverError::MultipleTrustchainProofService);
     };
 
-    if let Ok(service) = service {
-        let controller_did = get_from_proof_service(service, "controller");
+    if let Ok(services) = services {
+        let controller_dids: Vec<&str> = services
+            .into_iter()
+            .map(|service| get_from_proof_service(service, "controller").map(String::as_str))
+            .collect::<Option<_>>()
+            .ok_or_else(|| {
+                ResolverError::FailedToConvertToTrustchain(
+                    "Missing controller in Trustchain proof service.".to_string(),
+                )
+            })?;
 
         // Convert doc
-        let doc = transform_doc(&sidetree_doc, controller_did.unwrap().as_str());
+        let doc = transform_doc(&sidetree_doc, &controller_dids);
 
         // Convert metadata
         let doc_meta = transform_doc_metadata(&sidetree_doc, sidetree_doc_meta);
@@ -289,6 +376,8 @@ pub trait TrustchainResolver: DIDResolver + AsDIDResolver {
                 Err(ResolverError::NonExistentDID(did.to_string()))
             } else if did_res_meta_error == "notFound" {
                 Err(ResolverError::DIDNotFound(did.to_string()))
+            } else if did_res_meta_error == ERROR_METHOD_NOT_SUPPORTED {
+                Err(ResolverError::MethodNotSupported(did.to_string()))
             } else if did_res_meta_error.contains("Failed to convert to Trustchain") {
                 Err(ResolverError::FailedToConvertToTrustchain(
                     did_res_meta_error
@@ -325,12 +414,9 @@ pub trait TrustchainResolver: DIDResolver + AsDIDResolver {
         Option<Document>,
         Option<DocumentMetadata>,
     ) {
-        // TODO: remove upon handling with DIDMethods
-        if did.starts_with("did:key:") {
-            let did_key_resolver = DIDKey;
-            return did_key_resolver
-                .resolve(did, &ResolutionInputMetadata::default())
-                .await;
+        // DIDs of methods without a ledger are resolved directly, with no extended transform.
+        if let Some(resolver) = generic_resolver(did) {
+            return self.transform(resolver.resolve(did, input_metadata).await);
         }
 
         let resolved = self.wrapped_resolver().resolve(did, input_metadata).await;
@@ -359,6 +445,111 @@ pub trait TrustchainResolver: DIDResolver + AsDIDResolver {
     }
 }
 
+/// Gets the resolver for a DID of a method without a ledger (`did:key`, `did:web` and
+/// `did:jwk`), which is resolved the same way whatever the Trustchain resolver in use.
+pub fn generic_resolver(did: &str) -> Option<&'static dyn DIDResolver> {
+    match get_did_method(did)? {
+        "key" => Some(&DIDKey),
+        "web" => Some(&DIDWeb),
+        "jwk" => Some(&DIDJWK),
+        _ => None,
+    }
+}
+
+/// Trustchain resolver dispatching by DID method to registered backends, so that a chain may
+/// span several DID methods (e.g. a root DID on ION with downstream DIDs on Sovrin).
+///
+/// Each backend performs its own Trustchain resolution. DIDs of a method with no registered
+/// backend fall back to the [`generic_resolver`] of the method, if any.
+#[derive(Default)]
+pub struct DIDMethodResolver {
+    resolvers: HashMap<String, Arc<dyn TrustchainResolver + Send + Sync>>,
+}
+
+impl DIDMethodResolver {
+    /// Constructs a resolver with no registered backend.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a backend resolver for DIDs of the given methods (e.g. `["sov", "indy"]`).
+    pub fn with_resolver<T>(mut self, methods: &[&str], resolver: T) -> Self
+    where
+        T: TrustchainResolver + Send + Sync + 'static,
+    {
+        let resolver: Arc<dyn TrustchainResolver + Send + Sync> = Arc::new(resolver);
+        for method in methods {
+            self.resolvers
+                .insert(method.to_string(), Arc::clone(&resolver));
+        }
+        self
+    }
+
+    /// Gets the DID methods with a registered backend.
+    pub fn methods(&self) -> Vec<&str> {
+        let mut methods: Vec<&str> = self.resolvers.keys().map(|m| m.as_str()).collect();
+        methods.sort();
+        methods
+    }
+
+    /// Gets the registered backend for the method of a DID.
+    pub fn method_resolver(&self, did: &str) -> Option<&dyn TrustchainResolver> {
+        self.resolvers
+            .get(get_did_method(did)?)
+            .map(|resolver| resolver.as_ref() as &dyn TrustchainResolver)
+    }
+}
+
+#[async_trait]
+impl DIDResolver for DIDMethodResolver {
+    async fn resolve(
+        &self,
+        did: &str,
+        input_metadata: &ResolutionInputMetadata,
+    ) -> (
+        ResolutionMetadata,
+        Option<Document>,
+        Option<DocumentMetadata>,
+    ) {
+        self.trustchain_resolve(did, input_metadata).await
+    }
+}
+
+#[async_trait]
+impl TrustchainResolver for DIDMethodResolver {
+    /// Resolution is dispatched to the backends in `trustchain_resolve`, so the method
+    /// resolver is its own wrapped resolver.
+    fn wrapped_resolver(&self) -> &dyn DIDResolver {
+        self
+    }
+
+    async fn trustchain_resolve(
+        &self,
+        did: &str,
+        input_metadata: &ResolutionInputMetadata,
+    ) -> (
+        ResolutionMetadata,
+        Option<Document>,
+        Option<DocumentMetadata>,
+    ) {
+        if let Some(resolver) = self.method_resolver(did) {
+            return resolver.resolve(did, input_metadata).await;
+        }
+        match generic_resolver(did) {
+            Some(resolver) => self.transform(resolver.resolve(did, input_metadata).await),
+            None => (
+                ResolutionMetadata {
+                    error: Some(ERROR_METHOD_NOT_SUPPORTED.to_string()),
+                    content_type: None,
+                    property_set: None,
+                },
+                None,
+                None,
+            ),
+        }
+    }
+}
+
 // To facilitate trait upcasting: https://stackoverflow.com/a/28664881
 pub trait AsDIDResolver {
     fn as_did_resolver(&self) -> &dyn DIDResolver;
@@ -458,8 +649,8 @@ mod tests {
     }
 
     #[test]
-    fn test_get_proof_service() {
-        // Test get_proof_service method on a sidetree-resolved DID document.
+    fn test_get_proof_services() {
+        // Test get_proof_services method on a sidetree-resolved DID document.
 
         // Load a Sidetree-resolved DID Document.
         let did_doc =
@@ -469,7 +660,7 @@ mod tests {
         assert_eq!(did_doc.service.as_ref().unwrap().len(), 1_usize);
 
         // Get the service property containing the Trustchain proof.
-        let proof_service = get_proof_service(&did_doc).unwrap();
+        let proof_service = get_proof_services(&did_doc).unwrap()[0];
 
         // Check the contents of the proof service property.
         assert_eq!(proof_service.id, format!("#trustchain-controller-proof"));
@@ -480,8 +671,8 @@ mod tests {
     }
 
     #[test]
-    fn test_get_proof_service_only() {
-        // Test get_proof_service method when non-proof service is present.
+    fn test_get_proof_services_only() {
+        // Test get_proof_services method when non-proof service is present.
 
         // Load a Sidetree-resolved DID Document.
         let did_doc = Document::from_json(TEST_SIDETREE_DOCUMENT_SERVICE_AND_PROOF)
@@ -491,7 +682,7 @@ mod tests {
         assert_eq!(did_doc.service.as_ref().unwrap().len(), 2_usize);
 
         // Get the service property containing the Trustchain proof.
-        let proof_service = get_proof_service(&did_doc).unwrap();
+        let proof_service = get_proof_services(&did_doc).unwrap()[0];
 
         // Check the contents of the proof service property.
         assert_eq!(proof_service.id, format!("#trustchain-controller-proof"));
@@ -502,8 +693,8 @@ mod tests {
     }
 
     #[test]
-    fn test_get_proof_service_fail_multiple_proof_services() {
-        // Test get_proof_service method with failure as multiple proof services present.
+    fn test_get_proof_services_fail_multiple_proof_services() {
+        // Test get_proof_services method with failure as multiple proof services present.
 
         // Construct a DID Document with muliple proof services.
         let did_doc = Document::from_json(TEST_SIDETREE_DOCUMENT_MULTIPLE_PROOF)
@@ -512,7 +703,7 @@ mod tests {
         // Check that two services are present in the DID document.
         assert_eq!(did_doc.service.as_ref().unwrap().len(), 2_usize);
 
-        let result = get_proof_service(&did_doc);
+        let result = get_proof_services(&did_doc);
 
         // Expect an error due to the presence of multiple proof services.
         assert!(matches!(
@@ -522,8 +713,8 @@ mod tests {
     }
 
     #[test]
-    fn test_get_proof_service_fail_no_proof_services() {
-        // Test get_proof_service method with failure as no proof services present.
+    fn test_get_proof_services_fail_no_proof_services() {
+        // Test get_proof_services method with failure as no proof services present.
 
         // Construct a DID Document with a service but no proof services.
         let did_doc = Document::from_json(TEST_SIDETREE_DOCUMENT_SERVICE_NOT_PROOF)
@@ -532,7 +723,7 @@ mod tests {
         // Check that a service is present in the DID document.
         assert!(did_doc.service.is_some());
 
-        let result = get_proof_service(&did_doc);
+        let result = get_proof_services(&did_doc);
 
         // // Expect an error due to the absence of any proof services.
         assert!(matches!(
@@ -542,8 +733,8 @@ mod tests {
     }
 
     #[test]
-    fn test_get_proof_service_fail_no_services() {
-        // Test get_proof_service method with failure as no services present.
+    fn test_get_proof_services_fail_no_services() {
+        // Test get_proof_services method with failure as no services present.
 
         // Construct a DID Document with no proof services.
         let did_doc =
@@ -552,7 +743,7 @@ mod tests {
         // Check that no services are present in the DID document.
         assert!(did_doc.service.is_none());
 
-        let result = get_proof_service(&did_doc);
+        let result = get_proof_services(&did_doc);
 
         // Expect an error due to the absence of any proof services.
         assert!(matches!(
@@ -570,7 +761,7 @@ mod tests {
             Document::from_json(TEST_SIDETREE_DOCUMENT).expect("Document failed to load.");
 
         // Get a reference to the proof service.
-        let service = get_proof_service(&did_doc).unwrap();
+        let service = get_proof_services(&did_doc).unwrap()[0];
 
         // Get the controller DID from the proof service.
         let controller = get_from_proof_service(service, "controller").unwrap();
@@ -645,11 +836,11 @@ mod tests {
             Document::from_json(TEST_SIDETREE_DOCUMENT).expect("Document failed to load.");
 
         // Get the controller from the proof service property in the Sidetree-resolved DID document.
-        let proof_service = get_proof_service(&did_doc).unwrap();
+        let proof_service = get_proof_services(&did_doc).unwrap()[0];
         let controller = get_from_proof_service(proof_service, "controller").unwrap();
 
         // Transform the DID document by resolving into Trustchain format.
-        let actual = transform_doc(&did_doc, controller.as_str());
+        let actual = transform_doc(&did_doc, &[controller.as_str()]);
 
         // Canonicalise the result and compare with the expected Trustchain format.
         let canon_actual_doc = canonicalize(&actual).expect("Failed to canonicalize.");
@@ -736,4 +927,169 @@ mod tests {
             Err(ResolverError::MultipleTrustchainProofService)
         ));
     }
+
+    #[test]
+    fn transform_as_result_with_multiple_controllers() {
+        // Test Trustchain resolution of a DID with a proof service for each of two controllers.
+        let second_controller = "did:ion:test:EiBVpjUxXeSRJpvj2TewlX9zNF3GKMCKWwGmKBZqF6pk_A";
+        let mut input_doc: Value = serde_json::from_str(TEST_SIDETREE_DOCUMENT).unwrap();
+        let mut proof_service = input_doc["service"][0].clone();
+        proof_service["id"] = Value::from("#trustchain-controller-proof-2");
+        proof_service["serviceEndpoint"]["controller"] = Value::from(second_controller);
+        input_doc["service"]
+            .as_array_mut()
+            .unwrap()
+            .push(proof_service);
+        let input_doc: Document = serde_json::from_value(input_doc).unwrap();
+        let input_doc_meta: DocumentMetadata =
+            serde_json::from_str(TEST_SIDETREE_DOCUMENT_METADATA)
+                .expect("Document failed to load.");
+        let input_res_meta = ResolutionMetadata {
+            error: None,
+            content_type: None,
+            property_set: None,
+        };
+
+        let (_, doc, doc_meta) =
+            transform_as_result(input_res_meta, input_doc.clone(), input_doc_meta.clone()).unwrap();
+
+        // Both controllers are added and both proof services removed.
+        assert_eq!(
+            doc.controller,
+            Some(OneOrMany::Many(vec![
+                "did:ion:test:EiCBr7qGDecjkR2yUBhn3aNJPUR3TSEOlkpNcL0Q5Au9ZQ".to_string(),
+                second_controller.to_string()
+            ]))
+        );
+        assert!(doc.service.is_none());
+
+        // The proofs are added as a list.
+        match doc_meta.property_set.unwrap().get("proof") {
+            Some(Metadata::List(proofs)) => {
+                assert_eq!(proofs.len(), 2);
+                match &proofs[1] {
+                    Metadata::Map(proof) => assert!(matches!(
+                        proof.get("id"),
+                        Some(Metadata::String(id)) if id == second_controller
+                    )),
+                    _ => panic!("Expected a proof map."),
+                }
+            }
+            _ => panic!("Expected a list of proofs."),
+        }
+
+        // Two proof services for the same controller are rejected.
+        let mut input_doc = input_doc;
+        input_doc.service.as_mut().unwrap()[1] = input_doc.service.as_ref().unwrap()[0].clone();
+        input_doc.service.as_mut().unwrap()[1].id = "#trustchain-controller-proof-2".to_string();
+        assert!(matches!(
+            transform_as_result(
+                ResolutionMetadata {
+                    error: None,
+                    content_type: None,
+                    property_set: None,
+                },
+                input_doc,
+                input_doc_meta
+            ),
+            Err(ResolverError::MultipleTrustchainProofService)
+        ));
+    }
+
+    #[test]
+    fn test_proof_service_id() {
+        let controller = "did:ion:test:EiCBr7qGDecjkR2yUBhn3aNJPUR3TSEOlkpNcL0Q5Au9ZQ";
+        let second_controller = "did:ion:test:EiBVpjUxXeSRJpvj2TewlX9zNF3GKMCKWwGmKBZqF6pk_A";
+        let third_controller = "did:ion:test:EiAtHHKFJWAk5AsM3tgCut3OiBY4ekHTf66AAjoysXL65Q";
+        let mut doc: Value = serde_json::from_str(TEST_SIDETREE_DOCUMENT).unwrap();
+        let mut proof_service = doc["service"][0].clone();
+        proof_service["id"] = Value::from("#trustchain-controller-proof-2");
+        proof_service["serviceEndpoint"]["controller"] = Value::from(second_controller);
+        doc["service"].as_array_mut().unwrap().push(proof_service);
+        let mut doc: Document = serde_json::from_value(doc).unwrap();
+
+        // Each controller keeps the ID of its own proof service.
+        assert_eq!(
+            proof_service_id(&doc, controller),
+            ("trustchain-controller-proof".to_string(), true)
+        );
+        assert_eq!(
+            proof_service_id(&doc, second_controller),
+            ("trustchain-controller-proof-2".to_string(), true)
+        );
+
+        // A new controller is given the first free ID.
+        assert_eq!(
+            proof_service_id(&doc, third_controller),
+            ("trustchain-controller-proof-3".to_string(), false)
+        );
+        doc.service.as_mut().unwrap().remove(0);
+        assert_eq!(
+            proof_service_id(&doc, third_controller),
+            ("trustchain-controller-proof".to_string(), false)
+        );
+    }
+
+    /// Resolver returning a fixed Trustchain document and metadata for any DID.
+    struct TestResolver;
+
+    #[async_trait]
+    impl DIDResolver for TestResolver {
+        async fn resolve(
+            &self,
+            _did: &str,
+            _input_metadata: &ResolutionInputMetadata,
+        ) -> (
+            ResolutionMetadata,
+            Option<Document>,
+            Option<DocumentMetadata>,
+        ) {
+            (
+                ResolutionMetadata {
+                    error: None,
+                    content_type: None,
+                    property_set: None,
+                },
+                Some(Document::from_json(TEST_TRUSTCHAIN_DOCUMENT).unwrap()),
+                Some(serde_json::from_str(TEST_TRUSTCHAIN_DOCUMENT_METADATA).unwrap()),
+            )
+        }
+    }
+
+    impl TrustchainResolver for TestResolver {
+        fn wrapped_resolver(&self) -> &dyn DIDResolver {
+            self
+        }
+    }
+
+    #[tokio::test]
+    async fn test_did_method_resolver() {
+        let resolver = DIDMethodResolver::new().with_resolver(&["ion", "sov"], TestResolver);
+        assert_eq!(resolver.methods(), vec!["ion", "sov"]);
+
+        // DIDs of registered methods are resolved by the backend.
+        for did in [
+            "did:ion:test:EiCClfEdkTv_aM3UnBBhlOV89LlGhpQAbfeZLFdFxVFkEg",
+            "did:sov:BF4pE6ugcqNWt5kpJUE1Ro",
+        ] {
+            let (_, doc, doc_meta) = resolver.resolve_as_result(did).await.unwrap();
+            assert_eq!(
+                doc.unwrap(),
+                Document::from_json(TEST_TRUSTCHAIN_DOCUMENT).unwrap()
+            );
+            assert!(doc_meta.is_some());
+        }
+
+        // did:key DIDs are resolved without a registered backend.
+        let did = "did:key:z6MkhG98a8j2d3jqia13vrWqzHwHAgKTv9NjYEgdV3ndbEdD";
+        let (_, doc, _) = resolver.resolve_as_result(did).await.unwrap();
+        assert_eq!(doc.unwrap().id, did);
+
+        // Other methods are not supported.
+        assert!(resolver.method_resolver("did:example:123").is_none());
+        assert!(matches!(
+            resolver.resolve_as_result("did:example:123").await,
+            Err(ResolverError::MethodNotSupported(_))
+        ));
+    }
 }
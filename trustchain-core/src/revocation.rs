@@ -0,0 +1,404 @@
+//! Revocation of attestations by upstream DIDs.
+//!
+//! Revocation lists are published by ION DIDs only, with a Sidetree update operation adding the
+//! revocation list service (see `trustchain_ion::revoke`). Sovrin DIDs cannot publish a list, so
+//! revoking with the `--sovrin` CLI flag is an error, but the lists of ION DIDs upstream of a
+//! Sovrin DID are checked in any chain.
+use crate::utils::{extract_keys, get_did_suffix, hash};
+use crate::verifier::Timestamp;
+use crate::{TRUSTCHAIN_REVOCATION_SERVICE_ID_VALUE, TRUSTCHAIN_REVOCATION_SERVICE_TYPE_VALUE};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use ssi::did::{Document, Service, ServiceEndpoint};
+use ssi::jwk::JWK;
+use ssi::one_or_many::OneOrMany;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use thiserror::Error;
+
+/// An error relating to a revocation list.
+#[derive(Error, Debug)]
+pub enum RevocationError {
+    /// The signing key has no algorithm.
+    #[error("Signing key has no algorithm.")]
+    NoAlgorithm,
+    /// A service with the ID of the revocation list service is not a single revocation list
+    /// service with only a signed revocation list as its endpoint.
+    #[error("Malformed revocation list service in DID: {0}.")]
+    MalformedService(String),
+    /// None of the keys of the DID verify the signed revocation list.
+    #[error("No keys of DID: {0} are valid for its revocation list.")]
+    InvalidKeys(String),
+    /// The revocation list was issued before the latest list seen for the DID.
+    #[error(
+        "Revocation list of DID: {0} issued at: {1} is older than the latest seen, issued at: {2}."
+    )]
+    RolledBack(String, Timestamp, Timestamp),
+    /// Wrapped JWS error.
+    #[error("JWS error: {0}")]
+    JWSError(ssi::jws::Error),
+}
+
+impl From<ssi::jws::Error> for RevocationError {
+    fn from(err: ssi::jws::Error) -> Self {
+        RevocationError::JWSError(err)
+    }
+}
+
+/// A list of attestations revoked by an upstream DID, identified either by the suffix of the
+/// downstream DID or by the hash of the proof.
+///
+/// The list is signed by the upstream DID and published as a service in its own document.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevocationList {
+    /// Suffixes of downstream DIDs whose attestations are all revoked.
+    #[serde(default)]
+    pub did_suffixes: Vec<String>,
+    /// Hashes of revoked proofs.
+    #[serde(default)]
+    pub proof_hashes: Vec<String>,
+    /// Time at which the list was issued. A verifier keeping a [`RevocationHistory`] rejects a
+    /// list issued before the latest list it has seen for the same DID, so an outdated list
+    /// cannot be replayed to undo revocations.
+    #[serde(default)]
+    pub issued: Timestamp,
+}
+
+impl RevocationList {
+    /// Revokes every attestation of a downstream DID, returning whether it was not already revoked.
+    pub fn revoke_did(&mut self, did: &str) -> bool {
+        let suffix = get_did_suffix(did).to_string();
+        if self.did_suffixes.contains(&suffix) {
+            return false;
+        }
+        self.did_suffixes.push(suffix);
+        true
+    }
+
+    /// Revokes a single proof, returning whether it was not already revoked.
+    pub fn revoke_proof(&mut self, proof: &str) -> bool {
+        self.revoke_proof_hash(&hash(proof))
+    }
+
+    /// Revokes a single proof by its hash, returning whether it was not already revoked.
+    pub fn revoke_proof_hash(&mut self, proof_hash: &str) -> bool {
+        if self.proof_hashes.iter().any(|h| h == proof_hash) {
+            return false;
+        }
+        self.proof_hashes.push(proof_hash.to_string());
+        true
+    }
+
+    /// Returns whether the attestation of a downstream DID by the given proof is revoked.
+    pub fn is_revoked(&self, did: &str, proof: &str) -> bool {
+        let suffix = get_did_suffix(did);
+        self.did_suffixes.iter().any(|s| s == suffix) || self.proof_hashes.contains(&hash(proof))
+    }
+
+    /// Signs the revocation list as a JWT.
+    pub fn sign(&self, key: &JWK) -> Result<String, RevocationError> {
+        let algorithm = key.get_algorithm().ok_or(RevocationError::NoAlgorithm)?;
+        Ok(ssi::jwt::encode_sign(algorithm, self, key)?)
+    }
+
+    /// Returns the endpoint of a revocation list service, containing the signed revocation list.
+    pub fn to_service_endpoint(&self, key: &JWK) -> Result<ServiceEndpoint, RevocationError> {
+        let mut obj = serde_json::Map::new();
+        obj.insert("proofValue".to_string(), Value::from(self.sign(key)?));
+        Ok(ServiceEndpoint::Map(Value::Object(obj)))
+    }
+
+    /// Returns a revocation list service for the DID document of the signing DID.
+    pub fn to_service(&self, did: &str, key: &JWK) -> Result<Service, RevocationError> {
+        Ok(Service {
+            id: format!("{}#{}", did, TRUSTCHAIN_REVOCATION_SERVICE_ID_VALUE),
+            type_: OneOrMany::One(TRUSTCHAIN_REVOCATION_SERVICE_TYPE_VALUE.to_string()),
+            service_endpoint: Some(OneOrMany::One(self.to_service_endpoint(key)?)),
+            property_set: None,
+        })
+    }
+
+    /// Gets the revocation list published in a DID document, verified against the keys of the
+    /// document, or `None` if the document has no revocation list service.
+    pub fn from_document(doc: &Document) -> Result<Option<Self>, RevocationError> {
+        let proof_value = match get_proof_value(doc)? {
+            Some(proof_value) => proof_value,
+            None => return Ok(None),
+        };
+        let revocation_list = extract_keys(doc)
+            .iter()
+            .find_map(|key| ssi::jwt::decode_verify::<Self>(proof_value, key).ok())
+            .ok_or(RevocationError::InvalidKeys(doc.id.to_owned()))?;
+        Ok(Some(revocation_list))
+    }
+
+    /// Gets the revocation list published in a DID document without verifying its signature,
+    /// or `None` if the document has no readable revocation list. Used to carry the revocations
+    /// of a list that no longer verifies, e.g. after a key rotation, over to a new list.
+    pub fn from_document_unverified(doc: &Document) -> Option<Self> {
+        let proof_value = get_proof_value(doc).ok()??;
+        ssi::jwt::decode_unverified::<Self>(proof_value).ok()
+    }
+}
+
+/// The issue times of the latest revocation lists seen by a verifier for each DID, to reject an
+/// outdated list replayed to undo revocations. A history is kept by a verifier for its own
+/// lifetime (see `Verifier::revocation_history`), so the lists seen by separate verifiers are
+/// not shared.
+#[derive(Debug, Default)]
+pub struct RevocationHistory {
+    latest_issued: Mutex<HashMap<String, Timestamp>>,
+}
+
+impl RevocationHistory {
+    /// Constructs an empty history.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks a revocation list of a DID was not issued before the latest list seen for the DID,
+    /// recording its issue time otherwise.
+    pub fn check(&self, did: &str, list: &RevocationList) -> Result<(), RevocationError> {
+        let mut latest_issued = self.latest_issued.lock().unwrap();
+        let latest = latest_issued.entry(did.to_string()).or_default();
+        if list.issued < *latest {
+            return Err(RevocationError::RolledBack(
+                did.to_string(),
+                list.issued,
+                *latest,
+            ));
+        }
+        *latest = list.issued;
+        Ok(())
+    }
+}
+
+/// Gets the signed revocation list from the revocation list service of a DID document, or `None`
+/// if the document has no revocation list service. Any other service with the ID of the
+/// revocation list service is rejected, rather than ignored, as it is removed from the document
+/// attested by upstream DIDs.
+fn get_proof_value(doc: &Document) -> Result<Option<&str>, RevocationError> {
+    let mut services = doc
+        .service
+        .iter()
+        .flatten()
+        .filter(|service| has_revocation_service_id(service));
+    let service = match services.next() {
+        Some(service) => service,
+        None => return Ok(None),
+    };
+    if services.next().is_some() {
+        return Err(RevocationError::MalformedService(doc.id.to_owned()));
+    }
+    revocation_service_proof_value(service)
+        .map(Some)
+        .ok_or(RevocationError::MalformedService(doc.id.to_owned()))
+}
+
+/// Checks that any service with the ID of the revocation list service in a DID document is a
+/// well-formed revocation list service, without verifying the list.
+pub fn check_revocation_service(doc: &Document) -> Result<(), RevocationError> {
+    get_proof_value(doc).map(|_| ())
+}
+
+/// Returns whether a DID document has a service with the ID of the revocation list service,
+/// whether or not it is well-formed and its list can be verified.
+pub fn has_revocation_service(doc: &Document) -> bool {
+    doc.service
+        .iter()
+        .flatten()
+        .any(|service| has_revocation_service_id(service))
+}
+
+/// Removes the revocation list service from a DID document. The revocation list is signed
+/// separately, so it is excluded from the document attested by upstream DIDs and can be updated
+/// without a new attestation. Only a well-formed revocation list service is removed (see
+/// [`check_revocation_service`]).
+pub fn remove_revocation_service(doc: &mut Document) {
+    if let Some(services) = doc.service.as_mut() {
+        services.retain(|service| revocation_service_proof_value(service).is_none());
+        if services.is_empty() {
+            doc.service = None;
+        }
+    }
+}
+
+/// Returns whether a service has the ID of the Trustchain revocation list service.
+fn has_revocation_service_id(service: &Service) -> bool {
+    matches!(
+        service.id.rsplit_once('#'),
+        Some((_, fragment)) if fragment == TRUSTCHAIN_REVOCATION_SERVICE_ID_VALUE
+    )
+}
+
+/// Gets the signed revocation list of a Trustchain revocation list service: a service with the
+/// ID and type of the revocation list service, and an endpoint with only a proof value.
+fn revocation_service_proof_value(service: &Service) -> Option<&str> {
+    if !has_revocation_service_id(service)
+        || !matches!(&service.type_, OneOrMany::One(type_) if type_ == TRUSTCHAIN_REVOCATION_SERVICE_TYPE_VALUE)
+        || service
+            .property_set
+            .as_ref()
+            .map_or(false, |property_set| !property_set.is_empty())
+    {
+        return None;
+    }
+    match service.service_endpoint.as_ref() {
+        Some(OneOrMany::One(ServiceEndpoint::Map(Value::Object(map)))) if map.len() == 1 => {
+            map.get("proofValue").and_then(|value| value.as_str())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{ROOT_PLUS_1_SIGNING_KEY, TEST_ROOT_PLUS_1_DOCUMENT, TEST_ROOT_PLUS_2_JWT};
+    use crate::utils::generate_key;
+
+    const ROOT_PLUS_2: &str = "did:ion:test:EiAtHHKFJWAk5AsM3tgCut3OiBY4ekHTf66AAjoysXL65Q";
+
+    #[test]
+    fn test_revoke() {
+        let mut list = RevocationList::default();
+        assert!(!list.is_revoked(ROOT_PLUS_2, TEST_ROOT_PLUS_2_JWT));
+
+        assert!(list.revoke_proof(TEST_ROOT_PLUS_2_JWT));
+        assert!(!list.revoke_proof(TEST_ROOT_PLUS_2_JWT));
+        assert!(list.is_revoked(ROOT_PLUS_2, TEST_ROOT_PLUS_2_JWT));
+        assert!(!list.is_revoked(ROOT_PLUS_2, "another.proof.value"));
+
+        assert!(list.revoke_did(ROOT_PLUS_2));
+        assert!(!list.revoke_did(ROOT_PLUS_2));
+        assert_eq!(
+            list.did_suffixes,
+            vec!["EiAtHHKFJWAk5AsM3tgCut3OiBY4ekHTf66AAjoysXL65Q".to_string()]
+        );
+        assert!(list.is_revoked(ROOT_PLUS_2, "another.proof.value"));
+    }
+
+    #[test]
+    fn test_from_document() {
+        let key: JWK = serde_json::from_str(ROOT_PLUS_1_SIGNING_KEY).unwrap();
+        let mut doc = Document::from_json(TEST_ROOT_PLUS_1_DOCUMENT).unwrap();
+        assert!(RevocationList::from_document(&doc).unwrap().is_none());
+
+        let mut list = RevocationList::default();
+        list.revoke_did(ROOT_PLUS_2);
+        let service = list.to_service(&doc.id, &key).unwrap();
+        doc.service.get_or_insert_with(Vec::new).push(service);
+        assert_eq!(RevocationList::from_document(&doc).unwrap(), Some(list));
+
+        // The attested document is unchanged by the revocation list service.
+        remove_revocation_service(&mut doc);
+        assert_eq!(doc, Document::from_json(TEST_ROOT_PLUS_1_DOCUMENT).unwrap());
+
+        // A revocation list signed by a key of another DID is rejected.
+        let mut other_doc = Document::from_json(TEST_ROOT_PLUS_1_DOCUMENT).unwrap();
+        let other_list = RevocationList::default();
+        let other_service = other_list
+            .to_service(&other_doc.id, &generate_key())
+            .unwrap();
+        other_doc
+            .service
+            .get_or_insert_with(Vec::new)
+            .push(other_service);
+        assert!(matches!(
+            RevocationList::from_document(&other_doc),
+            Err(RevocationError::InvalidKeys(_))
+        ));
+        // Its revocations can still be read, to carry them over to a new list.
+        assert!(has_revocation_service(&other_doc));
+        assert_eq!(
+            RevocationList::from_document_unverified(&other_doc),
+            Some(other_list)
+        );
+    }
+
+    #[test]
+    fn test_from_document_malformed_service() {
+        let key: JWK = serde_json::from_str(ROOT_PLUS_1_SIGNING_KEY).unwrap();
+        let doc = Document::from_json(TEST_ROOT_PLUS_1_DOCUMENT).unwrap();
+        let service = RevocationList::default().to_service(&doc.id, &key).unwrap();
+        let with_services = |services: Vec<Service>| {
+            let mut doc = doc.clone();
+            doc.service.get_or_insert_with(Vec::new).extend(services);
+            doc
+        };
+        let well_formed = with_services(vec![service.clone()]);
+        assert!(check_revocation_service(&well_formed).is_ok());
+
+        // A service with another type.
+        let mut wrong_type = service.clone();
+        wrong_type.type_ = OneOrMany::One("LinkedDomains".to_string());
+        // An endpoint with more than the signed revocation list.
+        let mut extra_key = service.clone();
+        if let Some(OneOrMany::One(ServiceEndpoint::Map(Value::Object(map)))) =
+            extra_key.service_endpoint.as_mut()
+        {
+            map.insert("uri".to_string(), Value::from("https://example.com"));
+        }
+        // An endpoint without a signed revocation list.
+        let mut no_proof_value = service.clone();
+        no_proof_value.service_endpoint = Some(OneOrMany::One(ServiceEndpoint::URI(
+            "https://example.com".to_string(),
+        )));
+        for services in [
+            vec![wrong_type],
+            vec![extra_key],
+            vec![no_proof_value],
+            vec![service.clone(), service.clone()],
+        ] {
+            let doc = with_services(services);
+            assert!(matches!(
+                RevocationList::from_document(&doc),
+                Err(RevocationError::MalformedService(_))
+            ));
+            assert!(check_revocation_service(&doc).is_err());
+            assert!(has_revocation_service(&doc));
+        }
+
+        // Only a well-formed revocation list service is removed from the attested document.
+        let mut doc = with_services(vec![{
+            let mut service = service;
+            service.type_ = OneOrMany::One("LinkedDomains".to_string());
+            service
+        }]);
+        let expected = doc.clone();
+        remove_revocation_service(&mut doc);
+        assert_eq!(doc, expected);
+        let mut doc = well_formed;
+        remove_revocation_service(&mut doc);
+        assert_eq!(doc, Document::from_json(TEST_ROOT_PLUS_1_DOCUMENT).unwrap());
+    }
+
+    #[test]
+    fn test_revocation_history() {
+        let history = RevocationHistory::new();
+        let mut list = RevocationList {
+            issued: 1000,
+            ..Default::default()
+        };
+        assert!(history.check(ROOT_PLUS_2, &list).is_ok());
+        list.revoke_did(ROOT_PLUS_2);
+        list.issued = 2000;
+        assert!(history.check(ROOT_PLUS_2, &list).is_ok());
+        // The latest list can be seen again, but not an earlier one.
+        assert!(history.check(ROOT_PLUS_2, &list).is_ok());
+        let earlier = RevocationList {
+            issued: 1000,
+            ..Default::default()
+        };
+        assert!(matches!(
+            history.check(ROOT_PLUS_2, &earlier),
+            Err(RevocationError::RolledBack(_, 1000, 2000))
+        ));
+        // The lists of other DIDs, and the lists seen by another history, are separate.
+        assert!(history.check("did:ion:test:other", &earlier).is_ok());
+        assert!(RevocationHistory::new()
+            .check(ROOT_PLUS_2, &earlier)
+            .is_ok());
+    }
+}
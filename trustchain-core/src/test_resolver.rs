@@ -0,0 +1,67 @@
+//! Test resolver for crate.
+use crate::resolver::TrustchainResolver;
+use ssi::did::Document;
+use ssi::did_resolve::{
+    DIDResolver, DocumentMetadata, ResolutionInputMetadata, ResolutionMetadata,
+};
+use std::collections::HashMap;
+
+/// Resolver returning fixed Trustchain documents and metadata.
+pub(crate) struct FixtureResolver(pub(crate) HashMap<String, (Document, DocumentMetadata)>);
+
+impl FixtureResolver {
+    /// Makes a resolver of the given resolved documents, by their ids.
+    pub(crate) fn new(fixtures: Vec<(Document, DocumentMetadata)>) -> Self {
+        Self(
+            fixtures
+                .into_iter()
+                .map(|(doc, doc_meta)| (doc.id.to_owned(), (doc, doc_meta)))
+                .collect(),
+        )
+    }
+
+    /// Makes a resolver of the given JSON documents and metadata.
+    pub(crate) fn from_json(fixtures: &[(&str, &str)]) -> Self {
+        Self::new(
+            fixtures
+                .iter()
+                .map(|(doc, doc_meta)| {
+                    (
+                        Document::from_json(doc).unwrap(),
+                        serde_json::from_str(doc_meta).unwrap(),
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+#[async_trait::async_trait]
+impl DIDResolver for FixtureResolver {
+    async fn resolve(
+        &self,
+        did: &str,
+        _input_metadata: &ResolutionInputMetadata,
+    ) -> (
+        ResolutionMetadata,
+        Option<Document>,
+        Option<DocumentMetadata>,
+    ) {
+        let resolved = self.0.get(did).cloned();
+        (
+            ResolutionMetadata {
+                error: resolved.is_none().then(|| "notFound".to_string()),
+                content_type: None,
+                property_set: None,
+            },
+            resolved.as_ref().map(|(doc, _)| doc.clone()),
+            resolved.map(|(_, doc_meta)| doc_meta),
+        )
+    }
+}
+
+impl TrustchainResolver for FixtureResolver {
+    fn wrapped_resolver(&self) -> &dyn DIDResolver {
+        self
+    }
+}
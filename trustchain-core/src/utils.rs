@@ -2,11 +2,12 @@
 use crate::data::{ROOT_PLUS_1_SIGNING_KEY, ROOT_PLUS_2_SIGNING_KEYS};
 use crate::key_manager::KeyManager;
 use crate::key_manager::KeyType;
+use crate::verifier::Timestamp;
 use crate::TRUSTCHAIN_DATA;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use ssi::did::{Document, ServiceEndpoint, VerificationMethod, VerificationMethodMap};
-use ssi::jwk::JWK;
+use ssi::jwk::{Algorithm, JWK};
 use ssi::one_or_many::OneOrMany;
 use std::path::{Path, PathBuf};
 
@@ -114,6 +115,14 @@ pub fn get_did_suffix(did: &str) -> &str {
     did.split(':').last().unwrap()
 }
 
+/// Returns the method name of a DID (e.g. `ion` for `did:ion:test:...`).
+pub fn get_did_method(did: &str) -> Option<&str> {
+    match did.split(':').collect::<Vec<&str>>().as_slice() {
+        ["did", method, _, ..] if !method.is_empty() => Some(method),
+        _ => None,
+    }
+}
+
 /// Converts a short-form DID into a complete DID.
 pub fn get_did_from_suffix(did_suffix: &str, method_and_network: &str) -> String {
     format!("did:{method_and_network}:{did_suffix}")
@@ -163,6 +172,30 @@ pub fn decode(jwt: &str) -> Result<String, ssi::jws::Error> {
     ssi::jwt::decode_unverified(jwt)
 }
 
+/// Signs data as a detached JWS with unencoded payload ([RFC 7797](https://www.rfc-editor.org/rfc/rfc7797)).
+pub fn detached_sign(
+    algorithm: Algorithm,
+    data: &str,
+    key: &JWK,
+) -> Result<String, ssi::jws::Error> {
+    ssi::jws::detached_sign_unencoded_payload(algorithm, data.as_bytes(), key)
+}
+
+/// Verifies a detached JWS with unencoded payload over the given data.
+pub fn detached_verify(jws: &str, data: &str, key: &JWK) -> Result<(), ssi::jws::Error> {
+    ssi::jws::detached_verify(jws, data.as_bytes(), key).map(|_| ())
+}
+
+/// Returns whether a compact JWS has a detached payload (i.e. an empty payload part).
+pub fn is_detached(jws: &str) -> bool {
+    matches!(jws.split('.').collect::<Vec<_>>()[..], [_, "", _])
+}
+
+/// Returns the current Unix time in seconds.
+pub fn current_time() -> Timestamp {
+    chrono::Utc::now().timestamp() as Timestamp
+}
+
 /// Extracts keys (`JWK`) from a type.
 pub trait HasKeys {
     /// Gets keys.
@@ -340,6 +373,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_get_did_method() {
+        assert_eq!(
+            get_did_method("did:ion:test:EiCClfEdkTv_aM3UnBBhlOV89LlGhpQAbfeZLFdFxVFkEg"),
+            Some("ion")
+        );
+        assert_eq!(
+            get_did_method("did:sov:BF4pE6ugcqNWt5kpJUE1Ro"),
+            Some("sov")
+        );
+        assert_eq!(get_did_method("did:key"), None);
+        assert_eq!(get_did_method("ion:test:EiCClfEdkTv"), None);
+    }
+
     #[test]
     fn test_generate_key() {
         let result = generate_key();
@@ -373,6 +420,22 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_detached_sign_verify() -> Result<(), Box<dyn std::error::Error>> {
+        let key = generate_key();
+        let doc: Document = serde_json::from_str(TEST_ROOT_PLUS_1_DOCUMENT)?;
+        let doc_canon = canonicalize(&doc)?;
+        let jws = detached_sign(Algorithm::ES256K, &doc_canon, &key)?;
+        assert!(is_detached(&jws));
+        assert!(!is_detached(TEST_ROOT_PLUS_1_JWT));
+        assert!(detached_verify(&jws, &doc_canon, &key.to_public()).is_ok());
+
+        // The signature covers the canonicalized document itself.
+        assert!(detached_verify(&jws, &hash(&doc_canon), &key.to_public()).is_err());
+        assert!(detached_verify(&jws, &doc_canon, &generate_key().to_public()).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_json_contains() {
         // Test with a JSON map.
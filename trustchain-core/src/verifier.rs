@@ -1,11 +1,22 @@
 //! DID verifier API and default implementation.
+use std::collections::{BTreeSet, HashMap};
 use std::error::Error;
+use std::marker::PhantomData;
+use std::sync::Arc;
 
-use crate::chain::{Chain, ChainError, DIDChain};
+use crate::chain::{Chain, ChainError, DIDChain, DEFAULT_MAX_CHAIN_DEPTH};
 use crate::commitment::{CommitmentError, DIDCommitment, TimestampCommitment};
-use crate::resolver::{ResolverError, TrustchainResolver};
+use crate::multichain::DIDMultiChain;
+use crate::policy::{PolicyViolation, TrustPolicy};
+use crate::report::{ChainVerificationReport, TimestampReport};
+use crate::resolver::{DIDMethodResolver, ResolverError, TrustchainResolver};
+use crate::revocation::{RevocationHistory, RevocationList};
+use crate::utils::{current_time, get_did_method};
 use async_trait::async_trait;
-use ssi::did_resolve::DIDResolver;
+use ssi::did::Document;
+use ssi::did_resolve::{
+    DIDResolver, DocumentMetadata, ResolutionInputMetadata, ResolutionMetadata,
+};
 use thiserror::Error;
 
 /// An error relating to Trustchain verification.
@@ -23,6 +34,12 @@ pub enum VerifierError {
     /// Invalid root with error:
     #[error("Invalid root DID ({0}) with timestamp: {1}.")]
     InvalidRootTimestamp(String, Timestamp),
+    /// No timestamp verifier is configured for the DID method of the root.
+    #[error("No timestamp verifier configured for the DID method of root DID: {0}.")]
+    NoTimestampVerifier(String),
+    /// No trusted root timestamp given to verify a root DID against.
+    #[error("No trusted root timestamp given for root DID: {0}.")]
+    NoRootTimestamp(String),
     /// Failed to build DID chain.
     #[error("Failed to build chain: {0}.")]
     ChainBuildFailure(String),
@@ -32,6 +49,9 @@ pub enum VerifierError {
     /// Invalid PoW hash.
     #[error("Invalid PoW hash: {0}.")]
     InvalidProofOfWorkHash(String),
+    /// Invalid ledger state proof.
+    #[error("Invalid ledger state proof: {0}.")]
+    InvalidStateProof(String),
     /// Failed to get DID operation.
     #[error("Error getting {0} DID operation.")]
     FailureToGetDIDOperation(String),
@@ -129,6 +149,9 @@ pub enum VerifierError {
     /// Attempt to access verification material before it has been fetched.
     #[error("Verification material not yet fetched for DID: {0}.")]
     VerificationMaterialNotYetFetched(String),
+    /// The chain violates the trust policy of the verifier.
+    #[error("Chain violates trust policy: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(" "))]
+    PolicyViolations(Vec<PolicyViolation>),
     /// Wrapped commitment error.
     #[error("A commitment error during verification: {0}")]
     CommitmentFailure(CommitmentError),
@@ -200,17 +223,161 @@ pub trait Verifier<T: Sync + Send + DIDResolver> {
         &self,
         did: &str,
         root_timestamp: Timestamp,
+    ) -> Result<DIDChain, VerifierError> {
+        self.verify_at(did, root_timestamp, current_time()).await
+    }
+
+    /// Verifies a downstream DID by tracing its chain back to the root, checking the validity
+    /// of time-bounded attestations at the given verification time.
+    async fn verify_at(
+        &self,
+        did: &str,
+        root_timestamp: Timestamp,
+        time: Timestamp,
     ) -> Result<DIDChain, VerifierError> {
         // Build a chain from the given DID to the root.
         let resolver = self.resolver();
-        let chain = DIDChain::new(did, resolver).await?;
+        let chain = DIDChain::new_with_max_depth(did, resolver, self.max_chain_depth()).await?;
 
         // Verify the proofs in the chain.
-        chain.verify_proofs()?;
+        chain.verify_proofs_at(time)?;
+
+        // Check no revocation list is older than one seen before, if the verifier keeps a history.
+        if let Some(history) = self.revocation_history() {
+            check_revocation_history(history, &chain)?;
+        }
+
+        // Evaluate the trust policy, if any, against the chain.
+        if let Some(policy) = self.policy() {
+            let violations = policy.violations(&chain);
+            if !violations.is_empty() {
+                return Err(VerifierError::PolicyViolations(violations));
+            }
+        }
 
         // Verify the root timestamp.
-        let root = chain.root();
+        self.verify_root_timestamp(chain.root(), root_timestamp)
+            .await?;
+        Ok(chain)
+    }
 
+    /// Verifies a downstream DID attested by at least `threshold` distinct controllers, tracing
+    /// every path along valid attestations back to a root. The root of each path must have been
+    /// timestamped at one of the trusted root timestamps.
+    async fn verify_multichain(
+        &self,
+        did: &str,
+        threshold: usize,
+        root_timestamps: &[Timestamp],
+    ) -> Result<Vec<DIDChain>, VerifierError> {
+        self.verify_multichain_at(did, threshold, root_timestamps, current_time())
+            .await
+    }
+
+    /// Verifies a downstream DID attested by at least `threshold` distinct controllers, as in
+    /// [`Verifier::verify_multichain`], at the given verification time.
+    async fn verify_multichain_at(
+        &self,
+        did: &str,
+        threshold: usize,
+        root_timestamps: &[Timestamp],
+        time: Timestamp,
+    ) -> Result<Vec<DIDChain>, VerifierError> {
+        // Build the DIDs upstream of the given DID through all of their controllers.
+        let multichain = DIDMultiChain::new_with_max_depth(
+            did,
+            self.resolver(),
+            threshold,
+            self.max_chain_depth(),
+        )
+        .await?;
+
+        // Verify the attestations, giving the chains from each root to the DID.
+        let chains = multichain.verify_proofs_at(time)?;
+
+        // Check no revocation list is older than one seen before, if the verifier keeps a history.
+        if let Some(history) = self.revocation_history() {
+            for chain in &chains {
+                check_revocation_history(history, chain)?;
+            }
+        }
+
+        // Evaluate the trust policy, if any, against every chain.
+        if let Some(policy) = self.policy() {
+            let violations: Vec<PolicyViolation> = chains
+                .iter()
+                .flat_map(|chain| policy.violations(chain))
+                .collect();
+            if !violations.is_empty() {
+                return Err(VerifierError::PolicyViolations(violations));
+            }
+        }
+
+        // Verify the timestamp of every root against the trusted root timestamps.
+        let roots: BTreeSet<&str> = chains.iter().map(|chain| chain.root()).collect();
+        for root in roots {
+            let mut result = Err(VerifierError::NoRootTimestamp(root.to_string()));
+            for root_timestamp in root_timestamps {
+                result = self.verify_root_timestamp(root, *root_timestamp).await;
+                if result.is_ok() {
+                    break;
+                }
+            }
+            result?;
+        }
+        Ok(chains)
+    }
+
+    /// Verifies a downstream DID by tracing its chain back to the root, reporting the result at
+    /// every level of the chain, and any violations of the trust policy, instead of stopping at
+    /// the first failure.
+    async fn verify_report(&self, did: &str, root_timestamp: Timestamp) -> ChainVerificationReport {
+        self.verify_report_at(did, root_timestamp, current_time())
+            .await
+    }
+
+    /// Verifies a downstream DID, reporting the result at every level of the chain as in
+    /// [`Verifier::verify_report`], at the given verification time.
+    async fn verify_report_at(
+        &self,
+        did: &str,
+        root_timestamp: Timestamp,
+        time: Timestamp,
+    ) -> ChainVerificationReport {
+        let mut report = ChainVerificationReport::new_at(
+            did,
+            self.resolver(),
+            self.max_chain_depth(),
+            self.policy(),
+            time,
+        )
+        .await;
+
+        // Verify the root timestamp, if a root was reached.
+        if let Some(root) = report.root().map(str::to_string) {
+            let (actual, error) = match self.verify_root_timestamp(&root, root_timestamp).await {
+                Ok(()) => (Some(root_timestamp), None),
+                Err(err @ VerifierError::InvalidRootTimestamp(_, actual)) => {
+                    (Some(actual), Some(err.to_string()))
+                }
+                Err(err) => (None, Some(err.to_string())),
+            };
+            report.timestamp = Some(TimestampReport {
+                did: root,
+                expected: root_timestamp,
+                actual,
+                error,
+            });
+        }
+        report
+    }
+
+    /// Verifies the timestamp of a root DID against the expected root timestamp.
+    async fn verify_root_timestamp(
+        &self,
+        root: &str,
+        root_timestamp: Timestamp,
+    ) -> Result<(), VerifierError> {
         let verifiable_timestamp = self.verifiable_timestamp(root, root_timestamp).await?;
 
         // Verify that the root DID content (keys & endpoints) and the timestamp share a common
@@ -228,7 +395,7 @@ pub trait Verifier<T: Sync + Send + DIDResolver> {
                 verifiable_timestamp.timestamp(),
             ))
         } else {
-            Ok(chain)
+            Ok(())
         }
     }
 
@@ -248,7 +415,489 @@ pub trait Verifier<T: Sync + Send + DIDResolver> {
 
     /// Gets the resolver used for DID verification.
     fn resolver(&self) -> &dyn TrustchainResolver;
+
+    /// Gets the maximum number of DIDs in a verified chain.
+    fn max_chain_depth(&self) -> usize {
+        DEFAULT_MAX_CHAIN_DEPTH
+    }
+
+    /// Gets the trust policy evaluated against verified chains, if any.
+    fn policy(&self) -> Option<&TrustPolicy> {
+        None
+    }
+
+    /// Gets the history of the revocation lists seen in verified chains, if kept, so that an
+    /// outdated revocation list is rejected after a later one has been seen.
+    fn revocation_history(&self) -> Option<&RevocationHistory> {
+        None
+    }
+}
+
+/// Checks the revocation lists published by the DIDs of a verified chain against a revocation
+/// history, recording their issue times.
+fn check_revocation_history(
+    history: &RevocationHistory,
+    chain: &DIDChain,
+) -> Result<(), ChainError> {
+    for did in chain.level_vec() {
+        let (doc, _) = chain
+            .data(did)
+            .ok_or_else(|| ChainError::ResolutionFailure(did.to_owned()))?;
+        let result = match RevocationList::from_document(doc) {
+            Ok(Some(revocation_list)) => history.check(did, &revocation_list),
+            Ok(None) => Ok(()),
+            Err(err) => Err(err),
+        };
+        result.map_err(|err| ChainError::InvalidRevocationList(did.to_owned(), err.to_string()))?;
+    }
+    Ok(())
+}
+
+/// A verifier of root DID timestamps for a single DID method, with the resolver type erased
+/// so that verifiers for different methods can be registered together.
+#[async_trait]
+pub trait RootVerifier: Send + Sync {
+    /// Verifies the timestamp of a root DID against the expected root timestamp.
+    async fn verify_root_timestamp(
+        &self,
+        root: &str,
+        root_timestamp: Timestamp,
+    ) -> Result<(), VerifierError>;
+
+    /// Constructs a verifiable timestamp for the given DID.
+    async fn verifiable_timestamp(
+        &self,
+        did: &str,
+        expected_timestamp: Timestamp,
+    ) -> Result<Box<dyn VerifiableTimestamp>, VerifierError>;
+
+    /// Gets a DID Commitment for the given DID.
+    async fn did_commitment(&self, did: &str) -> Result<Box<dyn DIDCommitment>, VerifierError>;
+
+    /// Validates the PoW (or equivalent ledger attestation) for the given hash.
+    fn validate_pow_hash(&self, hash: &str) -> Result<(), VerifierError>;
+}
+
+/// Wraps a method-specific `Verifier` as a `RootVerifier`.
+struct MethodRootVerifier<T, V> {
+    verifier: Arc<V>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+#[async_trait]
+impl<T, V> RootVerifier for MethodRootVerifier<T, V>
+where
+    T: DIDResolver + Send + Sync,
+    V: Verifier<T> + Send + Sync,
+{
+    async fn verify_root_timestamp(
+        &self,
+        root: &str,
+        root_timestamp: Timestamp,
+    ) -> Result<(), VerifierError> {
+        self.verifier
+            .verify_root_timestamp(root, root_timestamp)
+            .await
+    }
+
+    async fn verifiable_timestamp(
+        &self,
+        did: &str,
+        expected_timestamp: Timestamp,
+    ) -> Result<Box<dyn VerifiableTimestamp>, VerifierError> {
+        self.verifier
+            .verifiable_timestamp(did, expected_timestamp)
+            .await
+    }
+
+    async fn did_commitment(&self, did: &str) -> Result<Box<dyn DIDCommitment>, VerifierError> {
+        self.verifier.did_commitment(did).await
+    }
+
+    fn validate_pow_hash(&self, hash: &str) -> Result<(), VerifierError> {
+        self.verifier.validate_pow_hash(hash)
+    }
+}
+
+/// Resolves DIDs with the resolver of a shared method-specific `Verifier`, so that the verifier
+/// can be registered with a [`DIDMethodResolver`] without constructing a second resolver.
+pub struct VerifierResolver<T, V> {
+    verifier: Arc<V>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T, V> VerifierResolver<T, V> {
+    /// Constructs a resolver delegating to the resolver of the given verifier.
+    pub fn new(verifier: Arc<V>) -> Self {
+        Self {
+            verifier,
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<T, V> DIDResolver for VerifierResolver<T, V>
+where
+    T: DIDResolver + Send + Sync,
+    V: Verifier<T> + Send + Sync,
+{
+    async fn resolve(
+        &self,
+        did: &str,
+        input_metadata: &ResolutionInputMetadata,
+    ) -> (
+        ResolutionMetadata,
+        Option<Document>,
+        Option<DocumentMetadata>,
+    ) {
+        self.verifier.resolver().resolve(did, input_metadata).await
+    }
+}
+
+#[async_trait]
+impl<T, V> TrustchainResolver for VerifierResolver<T, V>
+where
+    T: DIDResolver + Send + Sync,
+    V: Verifier<T> + Send + Sync,
+{
+    fn wrapped_resolver(&self) -> &dyn DIDResolver {
+        self.verifier.resolver().wrapped_resolver()
+    }
+
+    async fn trustchain_resolve(
+        &self,
+        did: &str,
+        input_metadata: &ResolutionInputMetadata,
+    ) -> (
+        ResolutionMetadata,
+        Option<Document>,
+        Option<DocumentMetadata>,
+    ) {
+        self.verifier
+            .resolver()
+            .trustchain_resolve(did, input_metadata)
+            .await
+    }
+}
+
+/// Verifier of chains spanning several DID methods.
+///
+/// Chains are built with a method-dispatching resolver and the proofs of every link are
+/// verified as usual, while the root timestamp is verified by the verifier registered for the
+/// DID method of the root.
+pub struct DIDMethodVerifier {
+    resolver: DIDMethodResolver,
+    verifiers: HashMap<String, Arc<dyn RootVerifier>>,
+    max_chain_depth: usize,
+    policy: Option<TrustPolicy>,
+    revocation_history: RevocationHistory,
+}
+
+impl DIDMethodVerifier {
+    /// Constructs a verifier resolving DIDs with the given resolver, with no registered root
+    /// timestamp verifier.
+    pub fn new(resolver: DIDMethodResolver) -> Self {
+        Self {
+            resolver,
+            verifiers: HashMap::new(),
+            max_chain_depth: DEFAULT_MAX_CHAIN_DEPTH,
+            policy: None,
+            revocation_history: RevocationHistory::new(),
+        }
+    }
+
+    /// Sets the maximum number of DIDs in a verified chain.
+    pub fn with_max_chain_depth(mut self, max_chain_depth: usize) -> Self {
+        self.max_chain_depth = max_chain_depth;
+        self
+    }
+
+    /// Sets the trust policy evaluated against verified chains.
+    pub fn with_policy(mut self, policy: TrustPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Registers the verifier for root DIDs of the given methods (e.g. `["sov", "indy"]`).
+    pub fn with_verifier<T, V>(self, methods: &[&str], verifier: V) -> Self
+    where
+        T: DIDResolver + Send + Sync + 'static,
+        V: Verifier<T> + Send + Sync + 'static,
+    {
+        self.with_shared_verifier(methods, Arc::new(verifier))
+    }
+
+    /// Registers a verifier shared with the caller for root DIDs of the given methods, so that
+    /// its state (e.g. ledger connections and cached bundles) is not duplicated.
+    pub fn with_shared_verifier<T, V>(mut self, methods: &[&str], verifier: Arc<V>) -> Self
+    where
+        T: DIDResolver + Send + Sync + 'static,
+        V: Verifier<T> + Send + Sync + 'static,
+    {
+        let verifier: Arc<dyn RootVerifier> = Arc::new(MethodRootVerifier {
+            verifier,
+            _marker: PhantomData,
+        });
+        for method in methods {
+            self.verifiers
+                .insert(method.to_string(), Arc::clone(&verifier));
+        }
+        self
+    }
+
+    /// Gets the DID methods with a registered root timestamp verifier.
+    pub fn methods(&self) -> Vec<&str> {
+        let mut methods: Vec<&str> = self.verifiers.keys().map(|m| m.as_str()).collect();
+        methods.sort();
+        methods
+    }
+
+    /// Gets the verifier registered for the method of a DID.
+    pub fn method_verifier(&self, did: &str) -> Result<&dyn RootVerifier, VerifierError> {
+        get_did_method(did)
+            .and_then(|method| self.verifiers.get(method))
+            .map(|verifier| verifier.as_ref())
+            .ok_or_else(|| VerifierError::NoTimestampVerifier(did.to_string()))
+    }
+}
+
+#[async_trait]
+impl Verifier<DIDMethodResolver> for DIDMethodVerifier {
+    async fn verify_root_timestamp(
+        &self,
+        root: &str,
+        root_timestamp: Timestamp,
+    ) -> Result<(), VerifierError> {
+        self.method_verifier(root)?
+            .verify_root_timestamp(root, root_timestamp)
+            .await
+    }
+
+    async fn verifiable_timestamp(
+        &self,
+        did: &str,
+        expected_timestamp: Timestamp,
+    ) -> Result<Box<dyn VerifiableTimestamp>, VerifierError> {
+        self.method_verifier(did)?
+            .verifiable_timestamp(did, expected_timestamp)
+            .await
+    }
+
+    async fn did_commitment(&self, did: &str) -> Result<Box<dyn DIDCommitment>, VerifierError> {
+        self.method_verifier(did)?.did_commitment(did).await
+    }
+
+    /// The hash is valid if any registered verifier validates it. Root verification does not
+    /// rely on this, as the verifier for the root method validates its own target hash.
+    fn validate_pow_hash(&self, hash: &str) -> Result<(), VerifierError> {
+        if self
+            .verifiers
+            .values()
+            .any(|verifier| verifier.validate_pow_hash(hash).is_ok())
+        {
+            Ok(())
+        } else {
+            Err(VerifierError::InvalidProofOfWorkHash(hash.to_string()))
+        }
+    }
+
+    fn resolver(&self) -> &dyn TrustchainResolver {
+        &self.resolver
+    }
+
+    fn max_chain_depth(&self) -> usize {
+        self.max_chain_depth
+    }
+
+    fn policy(&self) -> Option<&TrustPolicy> {
+        self.policy.as_ref()
+    }
+
+    fn revocation_history(&self) -> Option<&RevocationHistory> {
+        Some(&self.revocation_history)
+    }
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+    use crate::data::{
+        ROOT_PLUS_1_SIGNING_KEY, TEST_ROOT_DOCUMENT, TEST_ROOT_DOCUMENT_METADATA,
+        TEST_ROOT_PLUS_1_DOCUMENT,
+    };
+    use crate::revocation::RevocationError;
+    use ssi::jwk::JWK;
+
+    /// Resolver returning the test root document for any DID.
+    struct RootResolver;
+
+    #[async_trait]
+    impl DIDResolver for RootResolver {
+        async fn resolve(
+            &self,
+            _did: &str,
+            _input_metadata: &ResolutionInputMetadata,
+        ) -> (
+            ResolutionMetadata,
+            Option<Document>,
+            Option<DocumentMetadata>,
+        ) {
+            (
+                ResolutionMetadata {
+                    error: None,
+                    content_type: None,
+                    property_set: None,
+                },
+                Some(Document::from_json(TEST_ROOT_DOCUMENT).unwrap()),
+                Some(serde_json::from_str(TEST_ROOT_DOCUMENT_METADATA).unwrap()),
+            )
+        }
+    }
+
+    impl TrustchainResolver for RootResolver {
+        fn wrapped_resolver(&self) -> &dyn DIDResolver {
+            self
+        }
+    }
+
+    #[tokio::test]
+    async fn test_did_method_verifier_no_timestamp_verifier() {
+        let root = "did:ion:test:EiCClfEdkTv_aM3UnBBhlOV89LlGhpQAbfeZLFdFxVFkEg";
+        let verifier =
+            DIDMethodVerifier::new(DIDMethodResolver::new().with_resolver(&["ion"], RootResolver));
+        assert!(verifier.methods().is_empty());
+        match verifier.verify(root, 1666265405).await {
+            Err(VerifierError::NoTimestampVerifier(did)) => assert_eq!(did, root),
+            _ => panic!("Expected missing timestamp verifier error."),
+        }
+        assert!(matches!(
+            verifier.validate_pow_hash("hash"),
+            Err(VerifierError::InvalidProofOfWorkHash(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_did_method_verifier_max_chain_depth() {
+        let root = "did:ion:test:EiCClfEdkTv_aM3UnBBhlOV89LlGhpQAbfeZLFdFxVFkEg";
+        let verifier =
+            DIDMethodVerifier::new(DIDMethodResolver::new().with_resolver(&["ion"], RootResolver))
+                .with_max_chain_depth(0);
+        assert_eq!(verifier.max_chain_depth(), 0);
+        assert!(matches!(
+            verifier.verify(root, 1666265405).await,
+            Err(VerifierError::ChainFailure(ChainError::MaxDepthExceeded(0)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_did_method_verifier_policy() {
+        let root = "did:ion:test:EiCClfEdkTv_aM3UnBBhlOV89LlGhpQAbfeZLFdFxVFkEg";
+        let other_root = "did:ion:test:EiBVpjUxXeSRJpvj2TewlX9zNF3GKMCKWwGmKBZqF6pk_A";
+        let policy = TrustPolicy {
+            allowed_roots: Some(vec![other_root.to_string()]),
+            ..Default::default()
+        };
+        let verifier =
+            DIDMethodVerifier::new(DIDMethodResolver::new().with_resolver(&["ion"], RootResolver))
+                .with_policy(policy);
+        // The policy is evaluated before the root timestamp.
+        match verifier.verify(root, 1666265405).await {
+            Err(VerifierError::PolicyViolations(violations)) => assert_eq!(
+                violations,
+                vec![PolicyViolation::RootNotAllowed(root.to_string())]
+            ),
+            _ => panic!("Expected policy violation error."),
+        }
+        // The violations are also reported.
+        let report = verifier.verify_report(root, 1666265405).await;
+        assert_eq!(
+            report.policy_violations,
+            vec![PolicyViolation::RootNotAllowed(root.to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_did_method_verifier_multichain() {
+        let root = "did:ion:test:EiCClfEdkTv_aM3UnBBhlOV89LlGhpQAbfeZLFdFxVFkEg";
+        let verifier =
+            DIDMethodVerifier::new(DIDMethodResolver::new().with_resolver(&["ion"], RootResolver));
+        // The root of every path is verified against the trusted root timestamps.
+        match verifier.verify_multichain(root, 1, &[]).await {
+            Err(VerifierError::NoRootTimestamp(did)) => assert_eq!(did, root),
+            _ => panic!("Expected missing root timestamp error."),
+        }
+        match verifier.verify_multichain(root, 1, &[1666265405]).await {
+            Err(VerifierError::NoTimestampVerifier(did)) => assert_eq!(did, root),
+            _ => panic!("Expected missing timestamp verifier error."),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_did_method_verifier_report() {
+        let root = "did:ion:test:EiCClfEdkTv_aM3UnBBhlOV89LlGhpQAbfeZLFdFxVFkEg";
+        let verifier =
+            DIDMethodVerifier::new(DIDMethodResolver::new().with_resolver(&["ion"], RootResolver));
+        let report = verifier.verify_report(root, 1666265405).await;
+        assert_eq!(report.root(), Some(root));
+        assert_eq!(report.levels.len(), 1);
+        let timestamp = report.timestamp.as_ref().unwrap();
+        assert_eq!(timestamp.expected, 1666265405);
+        assert!(timestamp.actual.is_none());
+        assert_eq!(
+            timestamp.error,
+            Some(VerifierError::NoTimestampVerifier(root.to_string()).to_string())
+        );
+        assert!(report.policy_violations.is_empty());
+        assert!(!report.is_valid());
+    }
+
+    #[test]
+    fn test_check_revocation_history() {
+        // A chain of a DID publishing the revocation list issued at the given time.
+        let key: JWK = serde_json::from_str(ROOT_PLUS_1_SIGNING_KEY).unwrap();
+        let chain_with_list = |issued: Timestamp| {
+            let mut doc = Document::from_json(TEST_ROOT_PLUS_1_DOCUMENT).unwrap();
+            doc.controller = None;
+            let list = RevocationList {
+                issued,
+                ..Default::default()
+            };
+            doc.service = Some(vec![list.to_service(&doc.id, &key).unwrap()]);
+            DIDChain::from_levels(vec![(doc, DocumentMetadata::default())])
+        };
+
+        let history = RevocationHistory::new();
+        assert!(check_revocation_history(&history, &chain_with_list(2000)).is_ok());
+        let err = check_revocation_history(&history, &chain_with_list(1000)).unwrap_err();
+        let did = chain_with_list(1000).root().to_string();
+        assert_eq!(
+            err,
+            ChainError::InvalidRevocationList(
+                did.to_owned(),
+                RevocationError::RolledBack(did, 1000, 2000).to_string()
+            )
+        );
+        // A verifier with its own history has not seen the later list.
+        assert!(
+            check_revocation_history(&RevocationHistory::new(), &chain_with_list(1000)).is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verifier_resolver() {
+        let did = "did:ion:test:EiCClfEdkTv_aM3UnBBhlOV89LlGhpQAbfeZLFdFxVFkEg";
+        let shared = Arc::new(DIDMethodVerifier::new(
+            DIDMethodResolver::new().with_resolver(&["ion"], RootResolver),
+        ));
+        let resolver = DIDMethodResolver::new()
+            .with_resolver(&["ion"], VerifierResolver::new(Arc::clone(&shared)));
+        let verifier = DIDMethodVerifier::new(resolver).with_shared_verifier(&["ion"], shared);
+        assert_eq!(verifier.methods(), vec!["ion"]);
+        let (_, doc, _) = verifier.resolver().resolve_as_result(did).await.unwrap();
+        assert_eq!(
+            doc.unwrap(),
+            Document::from_json(TEST_ROOT_DOCUMENT).unwrap()
+        );
+    }
+}
@@ -108,12 +108,15 @@ pub fn did_verify(did: String, opts: String) -> Result<String> {
             trustchain_resolver_light_client(&endpoint_opts.trustchain_endpoint().to_address()),
             endpoint_opts.trustchain_endpoint().to_address(),
         );
-        Ok(TrustchainAPI::verify(&did, root_event_time, &verifier)
-            .await
-            .map_err(FFIMobileError::FailedToVerifyDID)
-            .and_then(|did_chain| {
-                serde_json::to_string_pretty(&did_chain).map_err(FFIMobileError::FailedToSerialize)
-            })?)
+        Ok(
+            TrustchainAPI::verify(&did, root_event_time, None, &verifier)
+                .await
+                .map_err(FFIMobileError::FailedToVerifyDID)
+                .and_then(|did_chain| {
+                    serde_json::to_string_pretty(&did_chain)
+                        .map_err(FFIMobileError::FailedToSerialize)
+                })?,
+        )
     })
 }
 
@@ -144,6 +147,7 @@ pub fn vc_verify_credential(credential: String, opts: String) -> Result<String>
             &credential,
             ldp_opts,
             root_event_time,
+            None,
             &verifier,
             &mut ContextLoader::default(),
         )
@@ -244,6 +248,7 @@ pub fn vp_verify_presentation(presentation: String, opts: String) -> Result<()>
             &presentation,
             None,
             root_event_time,
+            None,
             &verifier,
             &mut ContextLoader::default(),
         )
@@ -6,8 +6,13 @@ use std::{
     str::FromStr,
 };
 use toml;
+use trustchain_core::chain::DEFAULT_MAX_CHAIN_DEPTH;
+use trustchain_core::policy::{PolicyError, TrustPolicy};
 use trustchain_core::verifier::Timestamp;
 use trustchain_core::TRUSTCHAIN_CONFIG;
+use trustchain_sovrin::network::SovrinNetwork;
+use trustchain_sovrin::resolver::{RequestConfig, SovrinResolver};
+use trustchain_sovrin::TrustchainSovrinError;
 
 const DEFAULT_HOST: &str = "127.0.0.1";
 const DEFAULT_PORT: u16 = 8081;
@@ -39,6 +44,22 @@ pub struct HTTPConfig {
     pub verifiable_endpoints: Option<bool>,
     /// Root event time for verifier.
     pub root_event_time: Option<Timestamp>,
+    /// Path to the genesis transactions of Sovrin MainNet. Sovrin DIDs are only resolved
+    /// if the genesis transactions of at least one Sovrin network are configured.
+    pub sovrin_genesis_transactions: Option<String>,
+    /// Path to the genesis transactions of Sovrin StagingNet.
+    pub sovrin_staging_genesis_transactions: Option<String>,
+    /// Path to the genesis transactions of Sovrin BuilderNet.
+    pub sovrin_builder_genesis_transactions: Option<String>,
+    /// Timeout and retry settings for Sovrin ledger requests.
+    #[serde(default)]
+    pub sovrin_request: RequestConfig,
+    /// Maximum number of DIDs in a verified chain, bounding the resolutions made for a request
+    /// (`None` by default and unwrapped as `DEFAULT_MAX_CHAIN_DEPTH`)
+    pub max_chain_depth: Option<usize>,
+    /// Path to a trust policy (TOML, or JSON with a `.json` extension) evaluated against verified
+    /// chains.
+    pub trust_policy: Option<String>,
 }
 
 impl std::fmt::Display for HTTPConfig {
@@ -60,6 +81,12 @@ impl Default for HTTPConfig {
             https_path: None,
             verifiable_endpoints: None,
             root_event_time: None,
+            sovrin_genesis_transactions: None,
+            sovrin_staging_genesis_transactions: None,
+            sovrin_builder_genesis_transactions: None,
+            sovrin_request: RequestConfig::default(),
+            max_chain_depth: None,
+            trust_policy: None,
         }
     }
 }
@@ -83,6 +110,42 @@ impl HTTPConfig {
             "http"
         }
     }
+    /// Gets the maximum number of DIDs in a verified chain.
+    pub fn max_chain_depth(&self) -> usize {
+        self.max_chain_depth.unwrap_or(DEFAULT_MAX_CHAIN_DEPTH)
+    }
+    /// Loads the trust policy, or `None` if no trust policy is configured.
+    pub fn trust_policy(&self) -> Option<Result<TrustPolicy, PolicyError>> {
+        self.trust_policy.as_ref().map(TrustPolicy::from_file)
+    }
+    /// Constructs a Sovrin resolver for each network with configured genesis transactions,
+    /// or `None` if no Sovrin network is configured.
+    pub fn sovrin_resolver(&self) -> Option<Result<SovrinResolver, TrustchainSovrinError>> {
+        let networks = [
+            (SovrinNetwork::MainNet, &self.sovrin_genesis_transactions),
+            (
+                SovrinNetwork::StagingNet,
+                &self.sovrin_staging_genesis_transactions,
+            ),
+            (
+                SovrinNetwork::BuilderNet,
+                &self.sovrin_builder_genesis_transactions,
+            ),
+        ];
+        if networks.iter().all(|(_, path)| path.is_none()) {
+            return None;
+        }
+        let mut resolver = SovrinResolver::new(self.sovrin_request.clone());
+        for (network, path) in networks {
+            if let Some(path) = path {
+                resolver = match resolver.with_network_file(network, path) {
+                    Ok(resolver) => resolver,
+                    Err(err) => return Some(Err(err)),
+                };
+            }
+        }
+        Some(Ok(resolver))
+    }
 }
 
 lazy_static! {
@@ -133,6 +196,9 @@ mod tests {
 
         let config: HTTPConfig = parse_toml(config_string);
         assert!(config.verifiable_endpoints.is_none());
+        assert!(config.sovrin_resolver().is_none());
+        assert_eq!(config.max_chain_depth(), DEFAULT_MAX_CHAIN_DEPTH);
+        assert!(config.trust_policy().is_none());
         assert_eq!(
             config,
             HTTPConfig {
@@ -3,10 +3,12 @@ use hyper::StatusCode;
 use serde_json::json;
 use thiserror::Error;
 use trustchain_core::{
-    commitment::CommitmentError, issuer::IssuerError, key_manager::KeyManagerError,
-    resolver::ResolverError, vc::CredentialError, verifier::VerifierError, vp::PresentationError,
+    chain::ChainError, commitment::CommitmentError, issuer::IssuerError,
+    key_manager::KeyManagerError, resolver::ResolverError, vc::CredentialError,
+    verifier::VerifierError, vp::PresentationError,
 };
 use trustchain_ion::root::TrustchainRootError;
+use trustchain_sovrin::TrustchainSovrinError;
 
 // TODO: refine and add doc comments for error variants
 #[derive(Error, Debug)]
@@ -43,6 +45,10 @@ pub enum TrustchainHTTPError {
     FailedToDeserialize(serde_json::Error),
     #[error("Root event time not configured for verification.")]
     RootEventTimeNotSet,
+    #[error("Sovrin resolution not configured.")]
+    SovrinNotConfigured,
+    #[error("Trustchain Sovrin error: {0}")]
+    SovrinError(TrustchainSovrinError),
 }
 
 impl From<ResolverError> for TrustchainHTTPError {
@@ -87,6 +93,12 @@ impl From<KeyManagerError> for TrustchainHTTPError {
     }
 }
 
+impl From<TrustchainSovrinError> for TrustchainHTTPError {
+    fn from(err: TrustchainSovrinError) -> Self {
+        TrustchainHTTPError::SovrinError(err)
+    }
+}
+
 // See axum IntoRespone example:
 // https://github.com/tokio-rs/axum/blob/main/examples/jwt/src/main.rs#L147-L160
 
@@ -98,9 +110,11 @@ impl IntoResponse for TrustchainHTTPError {
                 (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
             }
             err @ TrustchainHTTPError::VerifierError(VerifierError::InvalidRoot(_))
-            | err @ TrustchainHTTPError::VerifierError(VerifierError::CommitmentFailure(_)) => {
-                (StatusCode::OK, err.to_string())
-            }
+            | err @ TrustchainHTTPError::VerifierError(VerifierError::CommitmentFailure(_))
+            | err @ TrustchainHTTPError::VerifierError(VerifierError::PolicyViolations(_))
+            | err @ TrustchainHTTPError::VerifierError(VerifierError::ChainFailure(
+                ChainError::ConstraintViolation(_),
+            )) => (StatusCode::OK, err.to_string()),
             err @ TrustchainHTTPError::VerifierError(_) => {
                 (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
             }
@@ -162,6 +176,15 @@ impl IntoResponse for TrustchainHTTPError {
             err @ TrustchainHTTPError::RootEventTimeNotSet => {
                 (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
             }
+            err @ TrustchainHTTPError::SovrinNotConfigured => {
+                (StatusCode::NOT_IMPLEMENTED, err.to_string())
+            }
+            err @ TrustchainHTTPError::SovrinError(TrustchainSovrinError::NetworkNotConfigured(
+                _,
+            )) => (StatusCode::NOT_IMPLEMENTED, err.to_string()),
+            err @ TrustchainHTTPError::SovrinError(_) => {
+                (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+            }
         };
         let body = Json(json!({ "error": err_message }));
         (status, body).into_response()
@@ -3,7 +3,7 @@ use crate::state::AppState;
 use async_trait::async_trait;
 use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
-use axum::response::IntoResponse;
+use axum::response::{IntoResponse, Response};
 use axum::Json;
 use log::debug;
 use serde::{Deserialize, Serialize};
@@ -14,9 +14,14 @@ use ssi::{
 };
 use std::sync::Arc;
 use trustchain_core::chain::{Chain, DIDChain};
+use trustchain_core::report::ChainVerificationReport;
 use trustchain_core::resolver::TrustchainResolver;
-use trustchain_core::verifier::{Timestamp, Verifier, VerifierError};
+use trustchain_core::verifier::{DIDMethodVerifier, Timestamp, Verifier, VerifierError};
 use trustchain_ion::verifier::{TrustchainVerifier, VerificationBundle};
+use trustchain_sovrin::network::is_sovrin_did;
+use trustchain_sovrin::verifier::{
+    TrustchainVerifier as SovrinVerifier, VerificationBundle as SovrinVerificationBundle,
+};
 
 /// A HTTP API for resolving DID documents, chains, and verification bundles.
 #[async_trait]
@@ -28,17 +33,40 @@ pub trait TrustchainHTTP {
     ) -> Result<ResolutionResult, TrustchainHTTPError>;
 
     /// Resolves a DID chain.
-    async fn resolve_chain<T: DIDResolver + Send + Sync>(
+    async fn resolve_chain(
         did: &str,
-        verifier: &TrustchainVerifier<T>,
+        verifier: &DIDMethodVerifier,
         root_event_time: Timestamp,
     ) -> Result<DIDChainResolutionResult, TrustchainHTTPError>;
 
+    /// Resolves the DID chains from each root to a DID attested by at least `threshold` of its
+    /// controllers.
+    async fn resolve_multichain(
+        did: &str,
+        verifier: &DIDMethodVerifier,
+        threshold: usize,
+        root_event_time: Timestamp,
+    ) -> Result<Vec<DIDChainResolutionResult>, TrustchainHTTPError>;
+
+    /// Resolves a DID chain with a report of the verification of every level.
+    async fn resolve_chain_report(
+        did: &str,
+        verifier: &DIDMethodVerifier,
+        root_event_time: Timestamp,
+    ) -> ChainVerificationReport;
+
     /// Resolves a DID verification bundle.
     async fn resolve_bundle<T: DIDResolver + Send + Sync>(
         did: &str,
         verifier: &TrustchainVerifier<T>,
     ) -> Result<VerificationBundle, TrustchainHTTPError>;
+
+    /// Resolves a Sovrin DID verification bundle, including the raw ledger replies and their
+    /// state proofs.
+    async fn resolve_sovrin_bundle(
+        did: &str,
+        verifier: &SovrinVerifier,
+    ) -> Result<SovrinVerificationBundle, TrustchainHTTPError>;
 }
 
 /// Type for implementing handlers for resolution of DID documents, chains, and bundles.
@@ -61,9 +89,9 @@ impl TrustchainHTTP for TrustchainHTTPHandler {
         }
     }
 
-    async fn resolve_chain<T: DIDResolver + Send + Sync>(
+    async fn resolve_chain(
         did: &str,
-        verifier: &TrustchainVerifier<T>,
+        verifier: &DIDMethodVerifier,
         root_event_time: Timestamp,
     ) -> Result<DIDChainResolutionResult, TrustchainHTTPError> {
         debug!("Verifying...");
@@ -79,6 +107,33 @@ impl TrustchainHTTP for TrustchainHTTPHandler {
         Ok(DIDChainResolutionResult::new(&chain))
     }
 
+    async fn resolve_multichain(
+        did: &str,
+        verifier: &DIDMethodVerifier,
+        threshold: usize,
+        root_event_time: Timestamp,
+    ) -> Result<Vec<DIDChainResolutionResult>, TrustchainHTTPError> {
+        debug!("Verifying with threshold {}...", threshold);
+        let chains = verifier
+            .verify_multichain(did, threshold, &[root_event_time])
+            .await
+            // Any commitment error implies invalid root
+            .map_err(|err| match err {
+                err @ VerifierError::CommitmentFailure(_) => VerifierError::InvalidRoot(err.into()),
+                err => err,
+            })?;
+        Ok(chains.iter().map(DIDChainResolutionResult::new).collect())
+    }
+
+    async fn resolve_chain_report(
+        did: &str,
+        verifier: &DIDMethodVerifier,
+        root_event_time: Timestamp,
+    ) -> ChainVerificationReport {
+        debug!("Verifying with report...");
+        verifier.verify_report(did, root_event_time).await
+    }
+
     async fn resolve_bundle<T: DIDResolver + Send + Sync>(
         did: &str,
         verifier: &TrustchainVerifier<T>,
@@ -86,12 +141,25 @@ impl TrustchainHTTP for TrustchainHTTPHandler {
         let bundle = verifier.verification_bundle(did).await?;
         Ok((*bundle).clone())
     }
+
+    async fn resolve_sovrin_bundle(
+        did: &str,
+        verifier: &SovrinVerifier,
+    ) -> Result<SovrinVerificationBundle, TrustchainHTTPError> {
+        let bundle = verifier.verification_bundle(did).await?;
+        Ok((*bundle).clone())
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug)]
-/// Struct for deserializing `root_event_time` from handler's query param.
+/// Struct for deserializing `root_event_time`, the optional `report` flag and the optional
+/// `threshold` of controllers attesting the DID from handler's query params.
 pub struct RootEventTime {
     pub root_event_time: Timestamp,
+    #[serde(default)]
+    pub report: bool,
+    #[serde(default)]
+    pub threshold: Option<usize>,
 }
 
 impl TrustchainHTTPHandler {
@@ -101,7 +169,11 @@ impl TrustchainHTTPHandler {
         State(app_state): State<Arc<AppState>>,
     ) -> impl IntoResponse {
         debug!("Received DID to resolve: {}", did.as_str());
-        TrustchainHTTPHandler::resolve_did(did.as_str(), app_state.verifier.resolver())
+        let resolver = match app_state.resolver(did.as_str()) {
+            Ok(resolver) => resolver,
+            Err(err) => return Err(err),
+        };
+        TrustchainHTTPHandler::resolve_did(did.as_str(), resolver)
             .await
             .map(|resolved_json| (StatusCode::OK, Json(resolved_json)))
     }
@@ -111,26 +183,53 @@ impl TrustchainHTTPHandler {
         Path(did): Path<String>,
         Query(root_event_time): Query<RootEventTime>,
         State(app_state): State<Arc<AppState>>,
-    ) -> impl IntoResponse {
+    ) -> Result<Response, TrustchainHTTPError> {
         debug!("Received DID to get trustchain: {}", did.as_str());
         // let mut verifier = .write().await;
+        if let Some(threshold) = root_event_time.threshold {
+            return TrustchainHTTPHandler::resolve_multichain(
+                &did,
+                &app_state.chain_verifier,
+                threshold,
+                root_event_time.root_event_time,
+            )
+            .await
+            .map(|chains| (StatusCode::OK, Json(chains)).into_response());
+        }
+        if root_event_time.report {
+            let report = TrustchainHTTPHandler::resolve_chain_report(
+                &did,
+                &app_state.chain_verifier,
+                root_event_time.root_event_time,
+            )
+            .await;
+            return Ok((StatusCode::OK, Json(report)).into_response());
+        }
         TrustchainHTTPHandler::resolve_chain(
             &did,
-            &app_state.verifier,
+            &app_state.chain_verifier,
             root_event_time.root_event_time,
         )
         .await
-        .map(|chain| (StatusCode::OK, Json(chain)))
+        .map(|chain| (StatusCode::OK, Json(chain)).into_response())
     }
     /// Handles get request for DID verification bundle resolution
     pub async fn get_verification_bundle(
         Path(did): Path<String>,
         State(app_state): State<Arc<AppState>>,
-    ) -> impl IntoResponse {
+    ) -> Result<Response, TrustchainHTTPError> {
         debug!("Received DID to get verification bundle: {}", did.as_str());
+        if is_sovrin_did(&did) {
+            return TrustchainHTTPHandler::resolve_sovrin_bundle(
+                &did,
+                app_state.sovrin_verifier()?,
+            )
+            .await
+            .map(|bundle| (StatusCode::OK, Json(bundle)).into_response());
+        }
         TrustchainHTTPHandler::resolve_bundle(&did, &app_state.verifier)
             .await
-            .map(|bundle| (StatusCode::OK, Json(bundle)))
+            .map(|bundle| (StatusCode::OK, Json(bundle)).into_response())
     }
     /// Converts a DID document and metadata to a `ResolutionResult` type.
     pub fn to_resolution_result(doc: Document, doc_meta: DocumentMetadata) -> ResolutionResult {
@@ -245,6 +344,31 @@ mod tests {
             .starts_with(r#"{"error":"Trustchain Verifier error: Invalid root DID error:"#),)
     }
 
+    #[tokio::test]
+    #[ignore = "requires ION, MongoDB, IPFS and Bitcoin RPC"]
+    async fn test_resolve_chain_report() {
+        let app = TrustchainRouter::from(HTTPConfig::default()).into_router();
+        let client = TestClient::new(app);
+        let root_event_time = 1666265405;
+        let uri = format!("/did/chain/did:ion:test:EiAtHHKFJWAk5AsM3tgCut3OiBY4ekHTf66AAjoysXL65Q?root_event_time={root_event_time}&report=true");
+        let response = client.get(&uri).send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let report: ChainVerificationReport = serde_json::from_str(&response.text().await).unwrap();
+        assert_eq!(report.levels.len(), 3);
+        assert!(report.is_valid());
+
+        // An incorrect root_event_time is reported against the root timestamp only.
+        let incorrect_root_event_time = 1234500;
+        let uri = format!("/did/chain/did:ion:test:EiAtHHKFJWAk5AsM3tgCut3OiBY4ekHTf66AAjoysXL65Q?root_event_time={incorrect_root_event_time}&report=true");
+        let response = client.get(&uri).send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let report: ChainVerificationReport = serde_json::from_str(&response.text().await).unwrap();
+        assert!(report.levels.iter().all(|level| level.is_valid()));
+        let timestamp = report.timestamp.unwrap();
+        assert_eq!(timestamp.expected, incorrect_root_event_time);
+        assert!(!timestamp.is_valid());
+    }
+
     #[tokio::test]
     #[ignore = "requires ION, MongoDB, IPFS and Bitcoin RPC"]
     // Test of the bundle endpoint by using the verifier `fetch_bundle()` method to get from the endpoint
@@ -271,6 +395,24 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_get_sovrin_bundle_not_configured() {
+        let app_state = crate::state::AppState::new_with_cache(
+            HTTPConfig::default(),
+            std::collections::HashMap::new(),
+            std::collections::HashMap::new(),
+        );
+        let app = TrustchainRouter::from(Arc::new(app_state)).into_router();
+        let uri = "/did/bundle/did:sov:BF4pE6ugcqNWt5kpJUE1Ro".to_string();
+        let client = TestClient::new(app);
+        let response = client.get(&uri).send().await;
+        assert_eq!(response.status(), StatusCode::NOT_IMPLEMENTED);
+        assert_eq!(
+            response.text().await,
+            r#"{"error":"Sovrin resolution not configured."}"#.to_string()
+        );
+    }
+
     #[tokio::test]
     #[ignore = "requires ION, MongoDB, IPFS and Bitcoin RPC"]
     // Test of the bundle endpoint by using the verifier `fetch_bundle()` method to get from the endpoint
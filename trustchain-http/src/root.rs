@@ -2,7 +2,7 @@ use crate::state::AppState;
 use async_trait::async_trait;
 use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
-use axum::response::IntoResponse;
+use axum::response::{IntoResponse, Response};
 use axum::Json;
 use chrono::NaiveDate;
 use log::debug;
@@ -12,6 +12,9 @@ use std::sync::{Arc, RwLock};
 use trustchain_core::verifier::Timestamp;
 use trustchain_ion::root::{root_did_candidates, RootCandidate, TrustchainRootError};
 use trustchain_ion::utils::time_at_block_height;
+use trustchain_sovrin::network::SovrinNetwork;
+use trustchain_sovrin::resolver::SovrinResolver;
+use trustchain_sovrin::root::{self as sovrin_root, SovrinRootCandidate};
 
 use crate::errors::TrustchainHTTPError;
 
@@ -23,6 +26,15 @@ pub trait TrustchainRootHTTP {
         date: NaiveDate,
         root_candidates: &RwLock<HashMap<NaiveDate, RootCandidatesResult>>,
     ) -> Result<RootCandidatesResult, TrustchainHTTPError>;
+    /// Gets a vector of root DID candidates written to the ledger of a Sovrin network on a
+    /// given date. The candidates are read from GET_TXN replies without verifying their ledger
+    /// audit paths or multi-signatures, so they are unverified.
+    async fn sovrin_root_candidates(
+        date: NaiveDate,
+        network: SovrinNetwork,
+        resolver: Option<&SovrinResolver>,
+        root_candidates: &RwLock<HashMap<(SovrinNetwork, NaiveDate), SovrinRootCandidatesResult>>,
+    ) -> Result<SovrinRootCandidatesResult, TrustchainHTTPError>;
     /// Gets a unix timestamp for a given Bitcoin transaction ID.
     async fn block_timestamp(height: u64) -> Result<TimestampResult, TrustchainHTTPError>;
 }
@@ -55,6 +67,36 @@ impl TrustchainRootHTTP for TrustchainRootHTTPHandler {
         Ok(result)
     }
 
+    async fn sovrin_root_candidates(
+        date: NaiveDate,
+        network: SovrinNetwork,
+        resolver: Option<&SovrinResolver>,
+        root_candidates: &RwLock<HashMap<(SovrinNetwork, NaiveDate), SovrinRootCandidatesResult>>,
+    ) -> Result<SovrinRootCandidatesResult, TrustchainHTTPError> {
+        debug!("Getting {0} root candidates for {1}", network, date);
+        {
+            let read_guard = root_candidates.read().unwrap();
+            // Return the cached vector of root DID candidates, if available.
+            if let Some(result) = read_guard.get(&(network, date)) {
+                return Ok(result.clone());
+            }
+        }
+        let resolver = resolver.ok_or(TrustchainHTTPError::SovrinNotConfigured)?;
+        let result = SovrinRootCandidatesResult::new(
+            date,
+            network,
+            sovrin_root::root_did_candidates(&resolver.wrapped_resolver, network, date).await?,
+        );
+        debug!("Got root candidates: {:?}", &result);
+
+        // Add the result to the cache.
+        root_candidates
+            .write()
+            .unwrap()
+            .insert((network, date), result.clone());
+        Ok(result)
+    }
+
     async fn block_timestamp(height: u64) -> Result<TimestampResult, TrustchainHTTPError> {
         debug!("Getting unix timestamp for block height: {0}", height);
 
@@ -83,14 +125,27 @@ pub struct RootEventDay {
     day: u32,
 }
 
+#[derive(Deserialize, Debug)]
+/// Struct for deserializing the optional Sovrin `network` of the root event from handler's
+/// query param. Without a network, root DID candidates are searched for on ION.
+pub struct RootEventNetwork {
+    network: Option<SovrinNetwork>,
+}
+
 impl TrustchainRootHTTPHandler {
     /// Handles a GET request for root DID candidates.
+    ///
+    /// With a Sovrin `network` query param, e.g. `/root?year=2020&month=3&day=30&network=MainNet`,
+    /// the candidates in the response are NYM transactions read from the ledger without
+    /// verifying them against the validators of the network, as served by a single node. A
+    /// candidate must be verified as a root DID, with its timestamp, before it is trusted.
     pub async fn get_root_candidates(
         Query(year): Query<RootEventYear>,
         Query(month): Query<RootEventMonth>,
         Query(day): Query<RootEventDay>,
+        Query(network): Query<RootEventNetwork>,
         State(app_state): State<Arc<AppState>>,
-    ) -> impl IntoResponse {
+    ) -> Result<Response, TrustchainHTTPError> {
         debug!(
             "Received date for root DID candidates: {:?}-{:?}-{:?}",
             year, month, day
@@ -102,9 +157,22 @@ impl TrustchainRootHTTPHandler {
                 TrustchainRootError::InvalidDate(year.year, month.month, day.day),
             ));
         }
+        if let Some(network) = network.network {
+            return TrustchainRootHTTPHandler::sovrin_root_candidates(
+                date.unwrap(),
+                network,
+                app_state
+                    .sovrin_verifier
+                    .as_ref()
+                    .map(|verifier| verifier.sovrin_resolver()),
+                &app_state.sovrin_root_candidates,
+            )
+            .await
+            .map(|vec| (StatusCode::OK, Json(vec)).into_response());
+        }
         TrustchainRootHTTPHandler::root_candidates(date.unwrap(), &app_state.root_candidates)
             .await
-            .map(|vec| (StatusCode::OK, Json(vec)))
+            .map(|vec| (StatusCode::OK, Json(vec)).into_response())
     }
 
     /// Handles a GET request for a transaction timestamp.
@@ -141,6 +209,30 @@ impl RootCandidatesResult {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+/// Serializable type representing the result of a request for root DID candidates on a given date
+/// and Sovrin network. The candidates are not verified against the validators of the network.
+pub struct SovrinRootCandidatesResult {
+    date: NaiveDate,
+    network: SovrinNetwork,
+    root_candidates: Vec<SovrinRootCandidate>,
+}
+
+impl SovrinRootCandidatesResult {
+    pub fn new(
+        date: NaiveDate,
+        network: SovrinNetwork,
+        root_candidates: Vec<SovrinRootCandidate>,
+    ) -> Self {
+        Self {
+            date,
+            network,
+            root_candidates,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 /// Serializable type representing the result of a request for root DID candidates on a given date.
@@ -188,6 +280,22 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_sovrin_root_candidates_not_configured() {
+        let app_state =
+            AppState::new_with_cache(HTTPConfig::default(), HashMap::new(), HashMap::new());
+        let app = TrustchainRouter::from(Arc::new(app_state)).into_router();
+        let client = TestClient::new(app);
+
+        let uri = "/root?year=2020&month=3&day=30&network=MainNet".to_string();
+        let response = client.get(&uri).send().await;
+        assert_eq!(response.status(), StatusCode::NOT_IMPLEMENTED);
+        assert_eq!(
+            response.text().await,
+            r#"{"error":"Sovrin resolution not configured."}"#.to_string()
+        );
+    }
+
     #[tokio::test]
     #[ignore = "requires MongoDB and Bitcoin RPC"]
     async fn test_block_timestamp() {
@@ -1,15 +1,22 @@
-use crate::root::RootCandidatesResult;
+use crate::errors::TrustchainHTTPError;
+use crate::root::{RootCandidatesResult, SovrinRootCandidatesResult};
 use crate::store::CredentialStoreItem;
 use crate::{config::HTTPConfig, verifier::PresentationRequest};
 use chrono::NaiveDate;
 use did_ion::sidetree::HTTPSidetreeDIDResolver;
 use ssi::did_resolve::DIDResolver;
 use std::collections::HashMap;
-use std::sync::RwLock;
+use std::sync::{Arc, RwLock};
+use trustchain_core::policy::TrustPolicy;
+use trustchain_core::resolver::{DIDMethodResolver, TrustchainResolver};
+use trustchain_core::verifier::{DIDMethodVerifier, Verifier, VerifierResolver};
 use trustchain_core::TRUSTCHAIN_DATA;
 use trustchain_ion::ion::IONTest as ION;
 use trustchain_ion::trustchain_resolver;
 use trustchain_ion::verifier::TrustchainVerifier;
+use trustchain_ion::ION_METHOD;
+use trustchain_sovrin::network::{is_sovrin_did, SovrinNetwork, SOVRIN_METHODS};
+use trustchain_sovrin::verifier::TrustchainVerifier as SovrinVerifier;
 
 const DEFAULT_VERIFIER_ENDPOINT: &str = "http://localhost:3000/";
 
@@ -20,14 +27,22 @@ where
 {
     pub config: HTTPConfig,
     pub verifier: TrustchainVerifier<T>,
+    /// Verifier for Sovrin DIDs, if any Sovrin network is configured.
+    pub sovrin_verifier: Option<Arc<SovrinVerifier>>,
+    /// Verifier of DID chains, dispatching resolution and root verification by DID method.
+    pub chain_verifier: DIDMethodVerifier,
     pub credentials: HashMap<String, CredentialStoreItem>,
     pub root_candidates: RwLock<HashMap<NaiveDate, RootCandidatesResult>>,
+    pub sovrin_root_candidates:
+        RwLock<HashMap<(SovrinNetwork, NaiveDate), SovrinRootCandidatesResult>>,
     pub presentation_requests: HashMap<String, PresentationRequest>,
 }
 
 impl AppState {
     pub fn new(config: HTTPConfig) -> Self {
-        let verifier = TrustchainVerifier::new(trustchain_resolver(DEFAULT_VERIFIER_ENDPOINT));
+        let verifier = ion_verifier(&config);
+        let sovrin_verifier = sovrin_verifier(&config);
+        let chain_verifier = chain_verifier(&config, sovrin_verifier.as_ref());
         let path = std::env::var(TRUSTCHAIN_DATA).expect("TRUSTCHAIN_DATA env not set.");
         let credentials: HashMap<String, CredentialStoreItem> = serde_json::from_reader(
             // let credentials: HashMap<String, Credential> = serde_json::from_reader(
@@ -38,6 +53,7 @@ impl AppState {
         )
         .expect("Credential cache could not be deserialized.");
         let root_candidates = RwLock::new(HashMap::new());
+        let sovrin_root_candidates = RwLock::new(HashMap::new());
         let presentation_requests: HashMap<String, PresentationRequest> = serde_json::from_reader(
             std::fs::read(std::path::Path::new(&path).join("presentations/requests/cache.json"))
                 // If no cache, default to empty
@@ -48,8 +64,11 @@ impl AppState {
         Self {
             config,
             verifier,
+            sovrin_verifier,
+            chain_verifier,
             credentials,
             root_candidates,
+            sovrin_root_candidates,
             presentation_requests,
         }
     }
@@ -58,18 +77,107 @@ impl AppState {
         credentials: HashMap<String, CredentialStoreItem>,
         presentation_requests: HashMap<String, PresentationRequest>,
     ) -> Self {
-        let verifier = TrustchainVerifier::new(trustchain_resolver(DEFAULT_VERIFIER_ENDPOINT));
+        let verifier = ion_verifier(&config);
+        let sovrin_verifier = sovrin_verifier(&config);
+        let chain_verifier = chain_verifier(&config, sovrin_verifier.as_ref());
         let root_candidates = RwLock::new(HashMap::new());
+        let sovrin_root_candidates = RwLock::new(HashMap::new());
         Self {
             config,
             verifier,
+            sovrin_verifier,
+            chain_verifier,
             credentials,
             root_candidates,
+            sovrin_root_candidates,
             presentation_requests,
         }
     }
 }
 
+impl<T> AppState<T>
+where
+    T: DIDResolver + Send + Sync,
+{
+    /// Gets the resolver for a DID: the Sovrin resolver for Sovrin DIDs and the verifier's
+    /// ION resolver otherwise.
+    pub fn resolver(&self, did: &str) -> Result<&dyn TrustchainResolver, TrustchainHTTPError> {
+        if is_sovrin_did(did) {
+            Ok(self.sovrin_verifier()?.resolver())
+        } else {
+            Ok(self.verifier.resolver())
+        }
+    }
+
+    /// Gets the Sovrin verifier, if any Sovrin network is configured.
+    pub fn sovrin_verifier(&self) -> Result<&SovrinVerifier, TrustchainHTTPError> {
+        self.sovrin_verifier
+            .as_deref()
+            .ok_or(TrustchainHTTPError::SovrinNotConfigured)
+    }
+}
+
+/// Constructs the Sovrin verifier from the config, if any Sovrin network is configured.
+fn sovrin_verifier(config: &HTTPConfig) -> Option<Arc<SovrinVerifier>> {
+    config.sovrin_resolver().map(|resolver| {
+        let verifier =
+            SovrinVerifier::new(resolver.expect("Sovrin resolver could not be constructed."))
+                .with_max_chain_depth(config.max_chain_depth());
+        Arc::new(match trust_policy(config) {
+            Some(policy) => verifier.with_policy(policy),
+            None => verifier,
+        })
+    })
+}
+
+/// Constructs the verifier of DID chains, sharing the Sovrin verifier (and its ledger pools),
+/// if any, so that chains may mix ION and Sovrin DIDs.
+fn chain_verifier(
+    config: &HTTPConfig,
+    sovrin_verifier: Option<&Arc<SovrinVerifier>>,
+) -> DIDMethodVerifier {
+    let mut resolver = DIDMethodResolver::new().with_resolver(
+        &[ION_METHOD],
+        trustchain_resolver(DEFAULT_VERIFIER_ENDPOINT),
+    );
+    if let Some(sovrin_verifier) = sovrin_verifier {
+        resolver = resolver.with_resolver(
+            &SOVRIN_METHODS,
+            VerifierResolver::new(Arc::clone(sovrin_verifier)),
+        );
+    }
+    let mut verifier = DIDMethodVerifier::new(resolver)
+        .with_max_chain_depth(config.max_chain_depth())
+        .with_verifier(
+            &[ION_METHOD],
+            TrustchainVerifier::new(trustchain_resolver(DEFAULT_VERIFIER_ENDPOINT)),
+        );
+    if let Some(sovrin_verifier) = sovrin_verifier {
+        verifier = verifier.with_shared_verifier(&SOVRIN_METHODS, Arc::clone(sovrin_verifier));
+    }
+    match trust_policy(config) {
+        Some(policy) => verifier.with_policy(policy),
+        None => verifier,
+    }
+}
+
+/// Constructs the ION verifier from the config.
+fn ion_verifier(config: &HTTPConfig) -> TrustchainVerifier<HTTPSidetreeDIDResolver<ION>> {
+    let verifier = TrustchainVerifier::new(trustchain_resolver(DEFAULT_VERIFIER_ENDPOINT))
+        .with_max_chain_depth(config.max_chain_depth());
+    match trust_policy(config) {
+        Some(policy) => verifier.with_policy(policy),
+        None => verifier,
+    }
+}
+
+/// Loads the trust policy applied by the verifiers, if one is configured.
+fn trust_policy(config: &HTTPConfig) -> Option<TrustPolicy> {
+    config
+        .trust_policy()
+        .map(|policy| policy.expect("Trust policy could not be loaded."))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -81,4 +189,19 @@ mod tests {
         AppState::new(HTTPConfig::default());
         AppState::new_with_cache(HTTPConfig::default(), HashMap::new(), HashMap::new());
     }
+
+    #[test]
+    fn test_resolver_sovrin_not_configured() {
+        let app_state =
+            AppState::new_with_cache(HTTPConfig::default(), HashMap::new(), HashMap::new());
+        assert!(app_state.sovrin_verifier.is_none());
+        assert_eq!(app_state.chain_verifier.methods(), vec![ION_METHOD]);
+        assert!(matches!(
+            app_state.resolver("did:sov:BF4pE6ugcqNWt5kpJUE1Ro"),
+            Err(TrustchainHTTPError::SovrinNotConfigured)
+        ));
+        assert!(app_state
+            .resolver("did:ion:test:EiAtHHKFJWAk5AsM3tgCut3OiBY4ekHTf66AAjoysXL65Q")
+            .is_ok());
+    }
 }
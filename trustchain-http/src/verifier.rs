@@ -17,7 +17,7 @@ use ssi::vc::{Credential, Presentation};
 use std::sync::Arc;
 use trustchain_api::api::TrustchainVPAPI;
 use trustchain_api::TrustchainAPI;
-use trustchain_core::verifier::{Timestamp, Verifier};
+use trustchain_core::verifier::{Timestamp, Verifier, VerifierError};
 use trustchain_ion::verifier::TrustchainVerifier;
 
 /// A type for presentation requests. See [VP request spec](https://w3c-ccg.github.io/vp-request-spec/)
@@ -38,6 +38,7 @@ pub trait TrustchainVerifierHTTP {
             presentation,
             None,
             root_event_time,
+            None,
             verifier,
             // TODO [#128]: move into API upon context loader added to app_state
             &mut ContextLoader::default(),
@@ -60,10 +61,14 @@ pub trait TrustchainVerifierHTTP {
         if !verify_credential_result.errors.is_empty() {
             return Err(TrustchainHTTPError::InvalidSignature);
         }
-        match credential.get_issuer() {
-            Some(issuer) => Ok(verifier.verify(issuer, root_event_time).await.map(|_| ())?),
-            _ => Err(TrustchainHTTPError::NoCredentialIssuer),
-        }
+        let issuer = credential
+            .get_issuer()
+            .ok_or(TrustchainHTTPError::NoCredentialIssuer)?;
+        let chain = verifier.verify(issuer, root_event_time).await?;
+        let types: Vec<String> = credential.type_.clone().into_iter().collect();
+        Ok(chain
+            .verify_credential_types(&types)
+            .map_err(VerifierError::from)?)
     }
 }
 
@@ -4,22 +4,25 @@ use did_ion::sidetree::DIDStatePatch;
 use did_ion::sidetree::PublicKeyJwk;
 use did_ion::sidetree::{DIDSuffix, Operation, Sidetree};
 use serde_json::to_string_pretty as to_json;
+use ssi::did_resolve::{DIDResolver, ResolutionInputMetadata};
 use std::convert::TryFrom;
+use trustchain_core::attestor::AttestationClaims;
 use trustchain_core::controller::Controller;
 use trustchain_core::key_manager::{ControllerKeyManager, KeyType};
-use trustchain_core::resolver::TrustchainResolver;
+use trustchain_core::resolver::{proof_service_id, ResolverError, TrustchainResolver};
 use trustchain_core::subject::Subject;
 use trustchain_core::utils::get_operations_path;
-use trustchain_core::TRUSTCHAIN_PROOF_SERVICE_ID_VALUE;
 
 use crate::controller::IONController;
 use crate::trustchain_resolver;
 
-// Function to resolve a controlled DID, attest to its contents and perform an update
-// operation on the controlled DID to add the attestation proof within a service endpoint.
+// Function to resolve a controlled DID, attest to its contents with the given claims (validity
+// period and delegation constraints) and perform an update operation on the controlled DID to
+// add the attestation proof within a service endpoint.
 pub async fn attest_operation(
     did: &str,
     controlled_did: &str,
+    claims: &AttestationClaims,
     verbose: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // 1.1. Load controller from passed controlled_did to be signed and controller DID
@@ -59,22 +62,38 @@ pub async fn attest_operation(
         }
     }
 
+    // 1.4 Get the ID of the proof service of the controller from the document as stored by
+    // ION, as the Trustchain resolver removes the proof services of all controllers
+    let sidetree_doc = match resolver
+        .wrapped_resolver()
+        .resolve(controlled_did, &ResolutionInputMetadata::default())
+        .await
+    {
+        (_, Some(doc), _) => doc,
+        (res_meta, ..) => return Err(Box::new(ResolverError::FailureWithMetadata(res_meta))),
+    };
+    let (service_id, service_exists) = proof_service_id(&sidetree_doc, controller.did());
+
     // 2: Make required patches
     let mut patches: Vec<DIDStatePatch> = Vec::<DIDStatePatch>::new();
 
-    // 2.1: Add RemoveService patch if Trustchain proof already present
-    if controller.is_proof_in_doc_meta(&doc_meta) {
+    // 2.1: Add RemoveService patch if the controller's own Trustchain proof is already present,
+    // leaving the proofs of any other controllers
+    if service_exists {
         patches.push(DIDStatePatch::RemoveServices {
-            ids: vec![TRUSTCHAIN_PROOF_SERVICE_ID_VALUE.to_string()],
+            ids: vec![service_id.to_owned()],
         });
     }
 
-    // 2.2. Controller performs attestation to Document to generate proof data
+    // 2.2. Controller performs attestation to Document to generate proof data, embedding the
+    // claims if any
     // Sign the document from the controller using the "Attestor" trait method
-    let proof = controller.to_attestor().attest(&doc, None)?;
+    let proof = controller
+        .to_attestor()
+        .attest_with_claims(&doc, None, claims)?;
 
     // 2.3. Proof service is constructed from the proof data and make an AddService patch
-    patches.push(controller.add_proof_service(controller.did(), &proof));
+    patches.push(controller.add_proof_service(&service_id, controller.did(), &proof));
 
     // 2.4  Generate new update key
     controller.generate_next_update_key()?;
@@ -12,10 +12,12 @@ use trustchain_core::holder::{Holder, HolderError};
 use trustchain_core::issuer::{Issuer, IssuerError};
 use trustchain_core::key_manager::KeyType;
 use trustchain_core::resolver::TrustchainResolver;
+use trustchain_core::revocation::remove_revocation_service;
 use trustchain_core::{
-    attestor::{Attestor, AttestorError},
+    attestor::{AttestationClaims, AttestationPayload, Attestor, AttestorError, ProofFormat},
     key_manager::{AttestorKeyManager, KeyManager, KeyManagerError},
     subject::Subject,
+    utils::detached_sign,
 };
 
 /// Struct for IONAttestor.
@@ -107,7 +109,13 @@ impl Subject for IONAttestor {
 }
 
 impl Attestor for IONAttestor {
-    fn attest(&self, doc: &Document, key_id: Option<&str>) -> Result<String, AttestorError> {
+    fn attest_with_options(
+        &self,
+        doc: &Document,
+        key_id: Option<&str>,
+        format: ProofFormat,
+        claims: &AttestationClaims,
+    ) -> Result<String, AttestorError> {
         let algorithm = ION::SIGNATURE_ALGORITHM;
 
         // Add controller to document
@@ -116,6 +124,9 @@ impl Attestor for IONAttestor {
         // Use full short-form DID as controller
         doc.controller = Some(OneOrMany::One(self.did().to_string()));
 
+        // Exclude the revocation list, which is signed separately
+        remove_revocation_service(&mut doc);
+
         // Canonicalize document
         let doc_canon = ION::json_canonicalization_scheme(&doc)
             .map_err(|_| AttestorError::InvalidDocumentParameters(doc.id.clone()))?;
@@ -138,10 +149,18 @@ impl Attestor for IONAttestor {
             }
         };
         // Encode and sign
-        match ssi::jwt::encode_sign(algorithm, &doc_canon_hash, &signing_key) {
-            Ok(str) => Ok(str),
-            Err(e) => Err(AttestorError::SigningError(doc.id, e.to_string())),
-        }
+        let result = match format {
+            ProofFormat::Embedded => ssi::jwt::encode_sign(
+                algorithm,
+                &AttestationPayload::new(doc_canon_hash, claims),
+                &signing_key,
+            ),
+            ProofFormat::Detached if claims.is_empty() => {
+                detached_sign(algorithm, &doc_canon, &signing_key)
+            }
+            ProofFormat::Detached => return Err(AttestorError::DetachedClaims(doc.id)),
+        };
+        result.map_err(|e| AttestorError::SigningError(doc.id, e.to_string()))
     }
 }
 
@@ -230,8 +249,10 @@ mod tests {
     use crate::trustchain_resolver;
     use ssi::did::Document;
     use ssi::vc::CredentialOrJWT;
+    use trustchain_core::attestor::Validity;
+    use trustchain_core::constraints::Constraints;
     use trustchain_core::data::{TEST_CREDENTIAL, TEST_SIGNING_KEYS, TEST_TRUSTCHAIN_DOCUMENT};
-    use trustchain_core::utils::init;
+    use trustchain_core::utils::{detached_verify, init};
 
     #[test]
     fn test_try_from() -> Result<(), Box<dyn std::error::Error>> {
@@ -288,13 +309,34 @@ mod tests {
         let valid_decoded = valid_decoded.unwrap();
 
         // Reconstruct doc
-        let mut doc_with_controller = doc;
+        let mut doc_with_controller = doc.clone();
         doc_with_controller.controller = Some(OneOrMany::One(target.did().to_string()));
         let doc_canon = ION::json_canonicalization_scheme(&doc_with_controller)?;
         let doc_canon_hash = ION::hash(doc_canon.as_bytes());
 
         assert_eq!(valid_decoded, doc_canon_hash);
 
+        // Check detached proof is signed over the canonicalized document
+        let detached_proof = target.attest_with_format(&doc, None, ProofFormat::Detached)?;
+        assert!(detached_verify(&detached_proof, &doc_canon, valid_key).is_ok());
+        assert!(detached_verify(&detached_proof, &doc_canon, invalid_key).is_err());
+
+        // Check a time-bounded and constrained proof embeds its claims in the signed payload
+        let claims = AttestationClaims::new(
+            Validity::new(Some(1000), Some(2000)),
+            Constraints {
+                max_depth: Some(0),
+                ..Default::default()
+            },
+        );
+        let bounded_proof = target.attest_with_claims(&doc, None, &claims)?;
+        let payload: AttestationPayload = ssi::jwt::decode_verify(&bounded_proof, valid_key)?;
+        assert_eq!(payload, AttestationPayload::new(doc_canon_hash, &claims));
+        assert!(matches!(
+            target.attest_with_options(&doc, None, ProofFormat::Detached, &claims),
+            Err(AttestorError::DetachedClaims(_))
+        ));
+
         Ok(())
     }
 
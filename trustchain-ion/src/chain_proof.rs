@@ -0,0 +1,373 @@
+//! Portable chain proofs for offline verification of DID chains with an ION root.
+use crate::commitment::{BlockTimestampCommitment, IONCommitment};
+use crate::verifier::{
+    construct_commitment, validate_min_pow_zeros, IONTimestamp, VerificationBundle,
+};
+use serde::{Deserialize, Serialize};
+use ssi::did::Document;
+use ssi::did_resolve::DocumentMetadata;
+use std::str::FromStr;
+use std::sync::Arc;
+use thiserror::Error;
+use trustchain_core::chain::{Chain, ChainError, DIDChain};
+use trustchain_core::commitment::CommitmentChain;
+use trustchain_core::policy::TrustPolicy;
+use trustchain_core::utils::{canonicalize, current_time};
+use trustchain_core::verifier::{Timestamp, VerifiableTimestamp, VerifierError};
+
+/// Version of the chain proof format produced by this library.
+pub const CHAIN_PROOF_VERSION: u32 = 1;
+
+/// An error relating to a chain proof.
+#[derive(Error, Debug)]
+pub enum ChainProofError {
+    /// Chain proof in a version of the format that is not supported.
+    #[error("Unsupported chain proof version: {0}, expected: {CHAIN_PROOF_VERSION}.")]
+    UnsupportedVersion(u32),
+    /// Verification bundle not for the root of the chain.
+    #[error("Verification bundle is for DID: {0}, which is not the root of the chain: {1}.")]
+    RootBundleMismatch(String, String),
+    /// Unrecognised chain proof serialization format.
+    #[error("Unrecognised chain proof format: {0}.")]
+    UnrecognisedFormat(String),
+    /// Failed to (de)serialize a chain proof as JSON.
+    #[error("Failed to (de)serialize chain proof as JSON: {0}")]
+    JsonFailure(serde_json::Error),
+    /// Failed to (de)serialize a chain proof as CBOR.
+    #[error("Failed to (de)serialize chain proof as CBOR: {0}")]
+    CborFailure(String),
+    /// Wrapped chain error.
+    #[error("A chain error during chain proof verification: {0}")]
+    ChainFailure(ChainError),
+    /// Wrapped verifier error.
+    #[error("A verifier error during chain proof verification: {0}")]
+    VerifierFailure(VerifierError),
+}
+
+impl From<serde_json::Error> for ChainProofError {
+    fn from(err: serde_json::Error) -> Self {
+        ChainProofError::JsonFailure(err)
+    }
+}
+
+impl From<ChainError> for ChainProofError {
+    fn from(err: ChainError) -> Self {
+        ChainProofError::ChainFailure(err)
+    }
+}
+
+impl From<VerifierError> for ChainProofError {
+    fn from(err: VerifierError) -> Self {
+        ChainProofError::VerifierFailure(err)
+    }
+}
+
+/// Serialization format of a chain proof.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChainProofFormat {
+    /// JSON.
+    #[default]
+    Json,
+    /// CBOR ([RFC 8949](https://www.rfc-editor.org/rfc/rfc8949)).
+    Cbor,
+}
+
+impl FromStr for ChainProofFormat {
+    type Err = ChainProofError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(ChainProofFormat::Json),
+            "cbor" => Ok(ChainProofFormat::Cbor),
+            _ => Err(ChainProofError::UnrecognisedFormat(s.to_string())),
+        }
+    }
+}
+
+/// A DID in a chain proof, with the document metadata holding its controller proof(s).
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainProofLevel {
+    /// DID Document.
+    pub did_document: Document,
+    /// DID Document Metadata.
+    pub did_document_metadata: DocumentMetadata,
+}
+
+/// A self-contained proof of a DID chain, including everything needed to verify it offline
+/// against a trusted root event time.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainProof {
+    /// Version of the chain proof format.
+    version: u32,
+    /// DIDs in the chain, starting at the root.
+    levels: Vec<ChainProofLevel>,
+    /// Verification bundle for the timestamp of the root DID.
+    root_bundle: VerificationBundle,
+}
+
+impl ChainProof {
+    /// Constructs a chain proof from a DID chain and the verification bundle of its root.
+    pub fn new(chain: &DIDChain, root_bundle: VerificationBundle) -> Result<Self, ChainProofError> {
+        if root_bundle.did_doc().id != chain.root() {
+            return Err(ChainProofError::RootBundleMismatch(
+                root_bundle.did_doc().id.to_owned(),
+                chain.root().to_string(),
+            ));
+        }
+        Ok(Self {
+            version: CHAIN_PROOF_VERSION,
+            levels: chain
+                .to_vec()
+                .into_iter()
+                .map(|(did_document, did_document_metadata)| ChainProofLevel {
+                    did_document,
+                    did_document_metadata,
+                })
+                .collect(),
+            root_bundle,
+        })
+    }
+
+    /// Gets the version of the chain proof format.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Gets the DIDs in the chain, starting at the root.
+    pub fn levels(&self) -> &[ChainProofLevel] {
+        &self.levels
+    }
+
+    /// Gets the verification bundle of the root DID.
+    pub fn root_bundle(&self) -> &VerificationBundle {
+        &self.root_bundle
+    }
+
+    /// Serializes the chain proof in the given format.
+    pub fn to_bytes(&self, format: ChainProofFormat) -> Result<Vec<u8>, ChainProofError> {
+        match format {
+            ChainProofFormat::Json => Ok(serde_json::to_vec(self)?),
+            ChainProofFormat::Cbor => {
+                let mut bytes = Vec::new();
+                ciborium::ser::into_writer(self, &mut bytes)
+                    .map_err(|err| ChainProofError::CborFailure(err.to_string()))?;
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// Deserializes a chain proof in the given format, checking its version is supported.
+    pub fn from_bytes(bytes: &[u8], format: ChainProofFormat) -> Result<Self, ChainProofError> {
+        let proof: Self = match format {
+            ChainProofFormat::Json => serde_json::from_slice(bytes)?,
+            ChainProofFormat::Cbor => ciborium::de::from_reader(bytes)
+                .map_err(|err| ChainProofError::CborFailure(err.to_string()))?,
+        };
+        if proof.version != CHAIN_PROOF_VERSION {
+            return Err(ChainProofError::UnsupportedVersion(proof.version));
+        }
+        Ok(proof)
+    }
+
+    /// Verifies the chain proof offline against the expected root timestamp and the trust
+    /// policy, if any, returning the verified chain.
+    pub fn verify(
+        &self,
+        root_timestamp: Timestamp,
+        policy: Option<&TrustPolicy>,
+    ) -> Result<DIDChain, ChainProofError> {
+        self.verify_at(root_timestamp, policy, current_time())
+    }
+
+    /// Verifies the chain proof offline as in [`ChainProof::verify`], checking the validity of
+    /// time-bounded attestations at the given verification time.
+    pub fn verify_at(
+        &self,
+        root_timestamp: Timestamp,
+        policy: Option<&TrustPolicy>,
+        time: Timestamp,
+    ) -> Result<DIDChain, ChainProofError> {
+        if self.version != CHAIN_PROOF_VERSION {
+            return Err(ChainProofError::UnsupportedVersion(self.version));
+        }
+
+        // Rebuild the chain and verify the proofs of every link.
+        let chain = DIDChain::try_from_levels(
+            self.levels
+                .iter()
+                .cloned()
+                .map(|level| (level.did_document, level.did_document_metadata))
+                .collect(),
+        )?;
+        chain.verify_proofs_at(time)?;
+
+        // Evaluate the trust policy, if any, against the chain.
+        if let Some(policy) = policy {
+            let violations = policy.violations(&chain);
+            if !violations.is_empty() {
+                return Err(VerifierError::PolicyViolations(violations).into());
+            }
+        }
+
+        // The bundle must commit to the root document of the chain.
+        let (root_doc, _) = chain.data(chain.root()).unwrap();
+        if canonicalize(root_doc)? != canonicalize(self.root_bundle.did_doc())? {
+            return Err(ChainProofError::RootBundleMismatch(
+                self.root_bundle.did_doc().id.to_owned(),
+                chain.root().to_string(),
+            ));
+        }
+
+        // Verify the root timestamp as a light client would, from the bundle alone.
+        let verifiable_timestamp = self.verifiable_timestamp(root_timestamp)?;
+        let hash = verifiable_timestamp
+            .timestamp_commitment()
+            .hash()
+            .map_err(VerifierError::from)?;
+        verifiable_timestamp
+            .verify(&hash)
+            .map_err(VerifierError::from)?;
+        validate_min_pow_zeros(&hash)?;
+        if !verifiable_timestamp.timestamp().eq(&root_timestamp) {
+            return Err(VerifierError::InvalidRootTimestamp(
+                chain.root().to_string(),
+                verifiable_timestamp.timestamp(),
+            )
+            .into());
+        }
+        Ok(chain)
+    }
+
+    /// Constructs the verifiable timestamp of the root DID from the verification bundle.
+    fn verifiable_timestamp(
+        &self,
+        expected_timestamp: Timestamp,
+    ) -> Result<IONTimestamp, VerifierError> {
+        let ion_commitment: IONCommitment =
+            construct_commitment(Arc::new(self.root_bundle.clone()))?;
+        let timestamp_commitment = BlockTimestampCommitment::new(
+            ion_commitment
+                .chained_commitment()
+                .commitments()
+                .last()
+                .expect("Unexpected empty commitment chain.")
+                .candidate_data()
+                .to_owned(),
+            expected_timestamp,
+        )?;
+        Ok(IONTimestamp::new(
+            Box::new(ion_commitment),
+            Box::new(timestamp_commitment),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{
+        TEST_BLOCK_HEADER_HEX, TEST_CHUNK_FILE_HEX, TEST_CORE_INDEX_FILE_HEX,
+        TEST_MERKLE_BLOCK_HEX, TEST_PROVISIONAL_INDEX_FILE_HEX, TEST_TRANSACTION_HEX,
+    };
+    use trustchain_core::data::TEST_DID_CHAIN;
+    use trustchain_core::policy::PolicyViolation;
+
+    const ROOT_EVENT_TIME: Timestamp = 1666265405;
+
+    fn test_chain_proof() -> ChainProof {
+        let chain: DIDChain = serde_json::from_str(TEST_DID_CHAIN).unwrap();
+        let (root_doc, root_doc_meta) = chain.data(chain.root()).unwrap().clone();
+        let root_bundle = VerificationBundle::new(
+            root_doc,
+            root_doc_meta,
+            hex::decode(TEST_CHUNK_FILE_HEX).unwrap(),
+            hex::decode(TEST_PROVISIONAL_INDEX_FILE_HEX).unwrap(),
+            hex::decode(TEST_CORE_INDEX_FILE_HEX).unwrap(),
+            hex::decode(TEST_TRANSACTION_HEX).unwrap(),
+            hex::decode(TEST_MERKLE_BLOCK_HEX).unwrap(),
+            hex::decode(TEST_BLOCK_HEADER_HEX).unwrap(),
+        );
+        ChainProof::new(&chain, root_bundle).unwrap()
+    }
+
+    #[test]
+    fn test_verify() {
+        let chain_proof = test_chain_proof();
+        assert_eq!(chain_proof.version(), CHAIN_PROOF_VERSION);
+        let chain = chain_proof.verify(ROOT_EVENT_TIME, None).unwrap();
+        assert_eq!(chain.len(), 3);
+
+        assert!(matches!(
+            chain_proof.verify(ROOT_EVENT_TIME + 1, None),
+            Err(ChainProofError::VerifierFailure(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_policy() {
+        let chain_proof = test_chain_proof();
+        let policy = TrustPolicy {
+            max_chain_length: Some(3),
+            ..Default::default()
+        };
+        assert!(chain_proof.verify(ROOT_EVENT_TIME, Some(&policy)).is_ok());
+
+        let policy = TrustPolicy {
+            max_chain_length: Some(2),
+            ..Default::default()
+        };
+        match chain_proof.verify(ROOT_EVENT_TIME, Some(&policy)) {
+            Err(ChainProofError::VerifierFailure(VerifierError::PolicyViolations(violations))) => {
+                assert_eq!(violations, vec![PolicyViolation::ChainTooLong(3, 2)])
+            }
+            _ => panic!("Expected policy violation error."),
+        }
+    }
+
+    #[test]
+    fn test_verify_tampered() {
+        // Swap the metadata (and so the proof) of the two downstream DIDs.
+        let mut chain_proof = test_chain_proof();
+        let meta_1 = chain_proof.levels[1].did_document_metadata.clone();
+        chain_proof.levels[1].did_document_metadata =
+            chain_proof.levels[2].did_document_metadata.clone();
+        chain_proof.levels[2].did_document_metadata = meta_1;
+        assert!(matches!(
+            chain_proof.verify(ROOT_EVENT_TIME, None),
+            Err(ChainProofError::ChainFailure(_))
+        ));
+
+        // Drop the root DID.
+        let mut chain_proof = test_chain_proof();
+        chain_proof.levels.remove(0);
+        assert!(matches!(
+            chain_proof.verify(ROOT_EVENT_TIME, None),
+            Err(ChainProofError::ChainFailure(ChainError::BrokenLink(_)))
+        ));
+    }
+
+    #[test]
+    fn test_to_from_bytes() {
+        let chain_proof = test_chain_proof();
+        for format in [ChainProofFormat::Json, ChainProofFormat::Cbor] {
+            let bytes = chain_proof.to_bytes(format).unwrap();
+            let deserialized = ChainProof::from_bytes(&bytes, format).unwrap();
+            assert!(deserialized.verify(ROOT_EVENT_TIME, None).is_ok());
+        }
+
+        // Unsupported versions are rejected.
+        let mut chain_proof = chain_proof;
+        chain_proof.version = CHAIN_PROOF_VERSION + 1;
+        let bytes = chain_proof.to_bytes(ChainProofFormat::Json).unwrap();
+        assert!(matches!(
+            ChainProof::from_bytes(&bytes, ChainProofFormat::Json),
+            Err(ChainProofError::UnsupportedVersion(v)) if v == CHAIN_PROOF_VERSION + 1
+        ));
+        assert_eq!(
+            "CBOR".parse::<ChainProofFormat>().unwrap(),
+            ChainProofFormat::Cbor
+        );
+    }
+}
@@ -13,7 +13,7 @@ use trustchain_core::controller::Controller;
 use trustchain_core::key_manager::{ControllerKeyManager, KeyManager, KeyManagerError, KeyType};
 use trustchain_core::subject::Subject;
 use trustchain_core::utils::generate_key;
-use trustchain_core::{TRUSTCHAIN_PROOF_SERVICE_ID_VALUE, TRUSTCHAIN_PROOF_SERVICE_TYPE_VALUE};
+use trustchain_core::TRUSTCHAIN_PROOF_SERVICE_TYPE_VALUE;
 impl KeyManager for IONController {}
 impl ControllerKeyManager for IONController {}
 
@@ -134,15 +134,16 @@ impl IONController {
         }
     }
 
-    /// Returns a patch for adding a proof service.
-    pub fn add_proof_service(&self, did: &str, proof: &str) -> DIDStatePatch {
+    /// Returns a patch for adding a proof service with the given ID, which is distinct for each
+    /// controller of a DID (see `proof_service_id`).
+    pub fn add_proof_service(&self, id: &str, did: &str, proof: &str) -> DIDStatePatch {
         let mut obj: Map<String, Value> = Map::new();
         obj.insert("controller".to_string(), Value::from(did));
         obj.insert("proofValue".to_string(), Value::from(proof.to_owned()));
 
         DIDStatePatch::AddServices {
             services: vec![ServiceEndpointEntry {
-                id: TRUSTCHAIN_PROOF_SERVICE_ID_VALUE.to_string(),
+                id: id.to_string(),
                 r#type: TRUSTCHAIN_PROOF_SERVICE_TYPE_VALUE.to_string(),
                 service_endpoint: ServiceEndpoint::Map(serde_json::Value::Object(obj.clone())),
             }],
@@ -362,7 +363,14 @@ mod tests {
         let controlled_did = "did:example:controlled_add_proof_service";
         let controller = test_controller(did, controlled_did)?;
         let proof = "test_proof_information";
-        let _ = controller.add_proof_service(controlled_did, proof);
+        let patch = controller.add_proof_service("trustchain-controller-proof-2", did, proof);
+        match patch {
+            DIDStatePatch::AddServices { services } => {
+                assert_eq!(services.len(), 1);
+                assert_eq!(services[0].id, "trustchain-controller-proof-2");
+            }
+            _ => panic!("Expected an AddServices patch."),
+        }
         Ok(())
     }
 }
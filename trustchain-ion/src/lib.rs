@@ -1,6 +1,7 @@
 //! Trustchain library for ION DID method.
 pub mod attest;
 pub mod attestor;
+pub mod chain_proof;
 pub mod commitment;
 pub mod config;
 pub mod controller;
@@ -9,6 +10,7 @@ pub mod data;
 pub mod ion;
 pub mod mnemonic;
 pub mod resolver;
+pub mod revoke;
 pub mod root;
 pub mod sidetree;
 pub mod utils;
@@ -0,0 +1,130 @@
+//! ION operation for revoking attestations.
+use crate::attestor::IONAttestor;
+use crate::controller::IONController;
+use crate::ion::IONTest as ION;
+use crate::trustchain_resolver;
+use did_ion::sidetree::Sidetree;
+use did_ion::sidetree::{DIDStatePatch, DIDSuffix, Operation, PublicKeyJwk, ServiceEndpointEntry};
+use serde_json::to_string_pretty as to_json;
+use std::convert::TryFrom;
+use trustchain_core::controller::Controller;
+use trustchain_core::key_manager::{ControllerKeyManager, KeyType};
+use trustchain_core::resolver::TrustchainResolver;
+use trustchain_core::revocation::{has_revocation_service, RevocationList};
+use trustchain_core::utils::{current_time, get_operations_path};
+use trustchain_core::{
+    TRUSTCHAIN_REVOCATION_SERVICE_ID_VALUE, TRUSTCHAIN_REVOCATION_SERVICE_TYPE_VALUE,
+};
+
+/// An attestation to revoke, either every attestation of a downstream DID or a single proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Revocation {
+    /// Every attestation of the downstream DID.
+    ControlledDID(String),
+    /// The proof with the given hash.
+    ProofHash(String),
+}
+
+// Function to resolve a DID, add a revocation to its signed revocation list and perform an
+// update operation on the DID to replace the revocation list service.
+pub async fn revoke_operation(
+    did: &str,
+    revocation: &Revocation,
+    verbose: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // 1.1. Load controller of the DID's own document
+    let controller = IONController::new(did, did)?;
+
+    // 1.2. Resolve DID document with Trustchain resolver
+    let resolver = trustchain_resolver("http://localhost:3000/");
+    let (doc, doc_meta) = match resolver.resolve_as_result(did).await {
+        Ok((_, Some(doc), Some(doc_meta))) => (doc, doc_meta),
+        Err(e) => {
+            return Err(Box::new(e));
+        }
+        _ => panic!("Unhandled resolution error encountered for DID: {}", did),
+    };
+
+    // 1.3 Check whether a present `next_update_key` matches the update commitment
+    if let Ok(Some(key)) = controller.next_update_key() {
+        if controller.is_commitment_key(&doc_meta, &key, KeyType::NextUpdateKey) {
+            controller.apply_next_update_key(controller.controlled_did_suffix(), &key)?;
+        } else {
+            panic!("'update_commitment' value is not compatible with 'next_update_key'.");
+        }
+    }
+
+    // 2: Make required patches
+    let mut patches: Vec<DIDStatePatch> = Vec::<DIDStatePatch>::new();
+
+    // 2.1: Add RemoveService patch if a revocation list is already present. A published list
+    // that no longer verifies (e.g. signed with a rotated key) is overwritten, carrying over any
+    // revocations that can still be read from it.
+    if has_revocation_service(&doc) {
+        patches.push(DIDStatePatch::RemoveServices {
+            ids: vec![TRUSTCHAIN_REVOCATION_SERVICE_ID_VALUE.to_string()],
+        });
+    }
+    let mut revocation_list = match RevocationList::from_document(&doc) {
+        Ok(revocation_list) => revocation_list.unwrap_or_default(),
+        Err(err) => {
+            if verbose {
+                println!("Overwriting published revocation list: {}", err);
+            }
+            RevocationList::from_document_unverified(&doc).unwrap_or_default()
+        }
+    };
+
+    // 2.2. Add the revocation to the list
+    let added = match revocation {
+        Revocation::ControlledDID(controlled_did) => revocation_list.revoke_did(controlled_did),
+        Revocation::ProofHash(proof_hash) => revocation_list.revoke_proof_hash(proof_hash),
+    };
+    if verbose {
+        println!("DID: {}", did);
+        match added {
+            true => println!("Revoked: {:?}", revocation),
+            false => println!("Already revoked: {:?}", revocation),
+        }
+    }
+
+    // 2.3. Issue the revocation list after the published one, sign it and make an AddService
+    // patch
+    revocation_list.issued = current_time().max(revocation_list.issued + 1);
+    let signing_key = IONAttestor::new(did).signing_key(None)?;
+    patches.push(DIDStatePatch::AddServices {
+        services: vec![ServiceEndpointEntry {
+            id: TRUSTCHAIN_REVOCATION_SERVICE_ID_VALUE.to_string(),
+            r#type: TRUSTCHAIN_REVOCATION_SERVICE_TYPE_VALUE.to_string(),
+            service_endpoint: revocation_list.to_service_endpoint(&signing_key)?,
+        }],
+    });
+
+    // 2.4  Generate new update key
+    controller.generate_next_update_key()?;
+
+    // 2.5 Get private update_key and public next_update_key
+    let update_key = controller.update_key();
+    let next_update_pk = match controller.next_update_key() {
+        Ok(Some(key)) => key.to_public(),
+        _ => panic!("'next_update_key' could not be loaded."),
+    };
+
+    // 2.6 Create update operation including all patches constructed
+    let update_operation = ION::update(
+        DIDSuffix(controller.controlled_did_suffix().to_string()),
+        &update_key?,
+        &PublicKeyJwk::try_from(next_update_pk).unwrap(),
+        patches,
+    )?;
+
+    // 3. Save operation to file in operations path
+    let operation = Operation::Update(update_operation);
+    let path = get_operations_path()?.join(format!(
+        "revoke_operation_{}.json",
+        controller.controlled_did_suffix()
+    ));
+    std::fs::write(path, to_json(&operation)?)?;
+
+    Ok(())
+}
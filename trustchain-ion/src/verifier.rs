@@ -24,10 +24,13 @@ use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
+use trustchain_core::chain::DEFAULT_MAX_CHAIN_DEPTH;
 use trustchain_core::commitment::{
     CommitmentChain, CommitmentError, DIDCommitment, TimestampCommitment,
 };
+use trustchain_core::policy::TrustPolicy;
 use trustchain_core::resolver::{ResolverError, TrustchainResolver};
+use trustchain_core::revocation::RevocationHistory;
 use trustchain_core::verifier::{Timestamp, VerifiableTimestamp, Verifier, VerifierError};
 
 /// Data bundle for DID timestamp verification.
@@ -73,6 +76,11 @@ impl VerificationBundle {
             block_header,
         }
     }
+
+    /// Gets the DID Document.
+    pub fn did_doc(&self) -> &Document {
+        &self.did_doc
+    }
 }
 
 /// Trustchain Verifier implementation via the ION DID method.
@@ -86,6 +94,9 @@ where
     ipfs_client: Option<IpfsClient>,
     bundles: Mutex<HashMap<String, Arc<VerificationBundle>>>,
     endpoint: Option<URL>,
+    max_chain_depth: usize,
+    policy: Option<TrustPolicy>,
+    revocation_history: RevocationHistory,
     _marker: PhantomData<U>,
 }
 
@@ -120,6 +131,9 @@ where
             ipfs_client: Some(ipfs_client),
             bundles,
             endpoint: None,
+            max_chain_depth: DEFAULT_MAX_CHAIN_DEPTH,
+            policy: None,
+            revocation_history: RevocationHistory::new(),
             _marker: PhantomData,
         }
     }
@@ -317,6 +331,9 @@ where
             ipfs_client: None,
             bundles: Mutex::new(HashMap::new()),
             endpoint: Some(endpoint),
+            max_chain_depth: DEFAULT_MAX_CHAIN_DEPTH,
+            policy: None,
+            revocation_history: RevocationHistory::new(),
             _marker: PhantomData,
         }
     }
@@ -376,6 +393,18 @@ impl<T, U> TrustchainVerifier<T, U>
 where
     T: Send + Sync + DIDResolver,
 {
+    /// Sets the maximum number of DIDs in a verified chain.
+    pub fn with_max_chain_depth(mut self, max_chain_depth: usize) -> Self {
+        self.max_chain_depth = max_chain_depth;
+        self
+    }
+
+    /// Sets the trust policy evaluated against verified chains.
+    pub fn with_policy(mut self, policy: TrustPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
     /// Extracts the IPFS content identifier from the ION OP_RETURN data inside a Bitcoin transaction.
     fn op_return_cid(&self, tx: &Transaction) -> Result<String, VerifierError> {
         tx_to_op_return_cid(tx)
@@ -397,6 +426,26 @@ pub fn construct_commitment(
     )
 }
 
+/// Validates a PoW hash without a Bitcoin node, as on a light client.
+pub(crate) fn validate_min_pow_zeros(hash: &str) -> Result<(), VerifierError> {
+    // Check the PoW difficulty of the hash against the configured minimum threshold.
+    // TODO: update Cargo.toml to use version 0.30.0+ of the bitcoin Rust library
+    // and specify a minimum work/target in the Trustchain client config, see:
+    // https://docs.rs/bitcoin/0.30.0/src/bitcoin/pow.rs.html#72-78
+    // In the meantime, just check for a minimum number of leading zeros in the hash.
+    if hash.chars().take_while(|&c| c == '0').count() < crate::MIN_POW_ZEROS {
+        return Err(VerifierError::InvalidProofOfWorkHash(format!(
+            "{}, only has {} zeros but MIN_POW_ZEROS is {}",
+            hash,
+            hash.chars().take_while(|&c| c == '0').count(),
+            crate::MIN_POW_ZEROS
+        )));
+    }
+
+    // If the PoW difficulty is satisfied, accept the timestamp in the DID commitment.
+    Ok(())
+}
+
 /// Converts DID content from a chunk file into a vector of Delta objects.
 pub fn content_deltas(chunk_file_json: &Value) -> Result<Vec<Delta>, VerifierError> {
     let chunk_file: ChunkFile =
@@ -432,6 +481,18 @@ where
         &self.resolver
     }
 
+    fn max_chain_depth(&self) -> usize {
+        self.max_chain_depth
+    }
+
+    fn policy(&self) -> Option<&TrustPolicy> {
+        self.policy.as_ref()
+    }
+
+    fn revocation_history(&self) -> Option<&RevocationHistory> {
+        Some(&self.revocation_history)
+    }
+
     async fn verifiable_timestamp(
         &self,
         did: &str,
@@ -466,22 +527,7 @@ where
     T: Sync + Send + DIDResolver,
 {
     fn validate_pow_hash(&self, hash: &str) -> Result<(), VerifierError> {
-        // Check the PoW difficulty of the hash against the configured minimum threshold.
-        // TODO: update Cargo.toml to use version 0.30.0+ of the bitcoin Rust library
-        // and specify a minimum work/target in the Trustchain client config, see:
-        // https://docs.rs/bitcoin/0.30.0/src/bitcoin/pow.rs.html#72-78
-        // In the meantime, just check for a minimum number of leading zeros in the hash.
-        if hash.chars().take_while(|&c| c == '0').count() < crate::MIN_POW_ZEROS {
-            return Err(VerifierError::InvalidProofOfWorkHash(format!(
-                "{}, only has {} zeros but MIN_POW_ZEROS is {}",
-                hash,
-                hash.chars().take_while(|&c| c == '0').count(),
-                crate::MIN_POW_ZEROS
-            )));
-        }
-
-        // If the PoW difficulty is satisfied, accept the timestamp in the DID commitment.
-        Ok(())
+        validate_min_pow_zeros(hash)
     }
 
     async fn did_commitment(&self, did: &str) -> Result<Box<dyn DIDCommitment>, VerifierError> {
@@ -493,6 +539,18 @@ where
         &self.resolver
     }
 
+    fn max_chain_depth(&self) -> usize {
+        self.max_chain_depth
+    }
+
+    fn policy(&self) -> Option<&TrustPolicy> {
+        self.policy.as_ref()
+    }
+
+    fn revocation_history(&self) -> Option<&RevocationHistory> {
+        Some(&self.revocation_history)
+    }
+
     async fn verifiable_timestamp(
         &self,
         did: &str,
@@ -528,7 +586,7 @@ pub struct IONTimestamp {
 }
 
 impl IONTimestamp {
-    fn new(
+    pub(crate) fn new(
         did_commitment: Box<dyn DIDCommitment>,
         timestamp_commitment: Box<dyn TimestampCommitment>,
     ) -> Self {
@@ -0,0 +1,542 @@
+//! Verification of AnonCreds presentations against ledger objects from Sovrin networks.
+//!
+//! AnonCreds credentials issued on Indy ledgers reference their schema and credential
+//! definition by legacy identifiers (`<DID>:2:<name>:<version>` and
+//! `<DID>:3:CL:<schema seqNo>:<tag>`). Both are fetched from the ledger, with verified state
+//! proofs, to verify a presentation. The DIDs of the issuers are returned so that they can be
+//! verified by a Trustchain chain.
+use ::anoncreds::data_types::cred_def::{CredentialDefinition, CredentialDefinitionId};
+use ::anoncreds::data_types::schema::{Schema, SchemaId};
+use ::anoncreds::verifier::verify_presentation as verify_anoncreds_presentation;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use thiserror::Error;
+use trustchain_core::verifier::VerifierError;
+
+pub use ::anoncreds::data_types::pres_request::PresentationRequest;
+pub use ::anoncreds::data_types::presentation::Presentation;
+
+use crate::network::SovrinNetwork;
+use crate::utils::reply_result;
+use crate::{
+    TrustchainSovrinError, ATTR_NAMES_KEY, DATA_KEY, DEST_KEY, NAME_KEY, ORIGIN_KEY, REF_KEY,
+    SEQ_NO_KEY, SIGNATURE_TYPE_KEY, TAG_KEY, VERSION_KEY,
+};
+
+/// An error relating to the verification of AnonCreds presentations.
+#[derive(Error, Debug)]
+pub enum AnonCredsError {
+    /// The presentation does not verify against its schemas and credential definitions.
+    #[error("Invalid AnonCreds presentation: {0}")]
+    InvalidPresentation(String),
+    /// Wrapped error for Sovrin error.
+    #[error("A wrapped Sovrin error: {0}")]
+    SovrinError(TrustchainSovrinError),
+    /// Wrapped error for Verifier error, from the Trustchain verification of an issuer.
+    #[error("A wrapped Verifier error: {0}")]
+    VerifierError(VerifierError),
+}
+
+impl From<TrustchainSovrinError> for AnonCredsError {
+    fn from(err: TrustchainSovrinError) -> Self {
+        AnonCredsError::SovrinError(err)
+    }
+}
+
+impl From<VerifierError> for AnonCredsError {
+    fn from(err: VerifierError) -> Self {
+        AnonCredsError::VerifierError(err)
+    }
+}
+
+/// A source of ledger replies for AnonCreds schemas and credential definitions.
+#[async_trait]
+pub trait AnonCredsObjectSource {
+    /// Gets the raw GET_SCHEMA reply for the schema with the given legacy identifier on a
+    /// network, after verifying its state proof.
+    async fn schema_reply(
+        &self,
+        network: SovrinNetwork,
+        schema_id: &str,
+    ) -> Result<String, TrustchainSovrinError>;
+
+    /// Gets the raw GET_CLAIM_DEF reply for the credential definition with the given legacy
+    /// identifier on a network, after verifying its state proof.
+    async fn cred_def_reply(
+        &self,
+        network: SovrinNetwork,
+        cred_def_id: &str,
+    ) -> Result<String, TrustchainSovrinError>;
+}
+
+/// Converts a raw GET_SCHEMA reply into the AnonCreds schema with the given legacy identifier.
+///
+/// Returns the schema together with the sequence number of its ledger transaction, which
+/// credential definitions on the schema refer to.
+pub fn schema_from_reply(
+    reply: &[u8],
+    schema_id: &str,
+) -> Result<(Schema, u64), TrustchainSovrinError> {
+    let result = reply_result(reply)?;
+    let data = result
+        .get(DATA_KEY)
+        .and_then(Value::as_object)
+        .ok_or(TrustchainSovrinError::InvalidLedgerAnswer)?;
+    let (dest, name, version) = match (
+        result.get(DEST_KEY),
+        data.get(NAME_KEY),
+        data.get(VERSION_KEY),
+    ) {
+        (Some(Value::String(dest)), Some(Value::String(name)), Some(Value::String(version))) => {
+            (dest, name, version)
+        }
+        _ => return Err(TrustchainSovrinError::InvalidLedgerAnswer),
+    };
+    if format!("{}:2:{}:{}", dest, name, version) != schema_id {
+        return Err(TrustchainSovrinError::InvalidSchema(format!(
+            "Ledger reply is not for schema: {}",
+            schema_id
+        )));
+    }
+    let (attr_names, seq_no) = match (data.get(ATTR_NAMES_KEY), result.get(SEQ_NO_KEY)) {
+        (Some(attr_names @ Value::Array(_)), Some(Value::Number(seq_no))) => {
+            (attr_names, seq_no.as_u64())
+        }
+        _ => {
+            return Err(TrustchainSovrinError::InvalidSchema(format!(
+                "Schema not found on ledger: {}",
+                schema_id
+            )))
+        }
+    };
+    let seq_no = seq_no.ok_or(TrustchainSovrinError::InvalidLedgerAnswer)?;
+    let schema = serde_json::from_value(json!({
+        "name": name,
+        "version": version,
+        "attrNames": attr_names,
+        "issuerId": dest,
+    }))
+    .map_err(|e| TrustchainSovrinError::InvalidSchema(e.to_string()))?;
+    Ok((schema, seq_no))
+}
+
+/// Converts a raw GET_CLAIM_DEF reply into the AnonCreds credential definition with the given
+/// legacy identifier, on the schema with the given identifier.
+///
+/// Returns the credential definition together with the sequence number of the schema
+/// transaction it refers to.
+pub fn cred_def_from_reply(
+    reply: &[u8],
+    cred_def_id: &str,
+    schema_id: &str,
+) -> Result<(CredentialDefinition, u64), TrustchainSovrinError> {
+    let result = reply_result(reply)?;
+    let (origin, signature_type, schema_ref, tag) = match (
+        result.get(ORIGIN_KEY),
+        result.get(SIGNATURE_TYPE_KEY),
+        result.get(REF_KEY).and_then(Value::as_u64),
+        result.get(TAG_KEY),
+    ) {
+        (
+            Some(Value::String(origin)),
+            Some(Value::String(signature_type)),
+            Some(schema_ref),
+            Some(Value::String(tag)),
+        ) => (origin, signature_type, schema_ref, tag),
+        _ => return Err(TrustchainSovrinError::InvalidLedgerAnswer),
+    };
+    if format!("{}:3:{}:{}:{}", origin, signature_type, schema_ref, tag) != cred_def_id {
+        return Err(TrustchainSovrinError::InvalidCredentialDefinition(format!(
+            "Ledger reply is not for credential definition: {}",
+            cred_def_id
+        )));
+    }
+    let value = match result.get(DATA_KEY) {
+        Some(value @ Value::Object(_)) => value,
+        _ => {
+            return Err(TrustchainSovrinError::InvalidCredentialDefinition(format!(
+                "Credential definition not found on ledger: {}",
+                cred_def_id
+            )))
+        }
+    };
+    let cred_def = serde_json::from_value(json!({
+        "schemaId": schema_id,
+        "type": signature_type,
+        "tag": tag,
+        "value": value,
+        "issuerId": origin,
+    }))
+    .map_err(|e| TrustchainSovrinError::InvalidCredentialDefinition(e.to_string()))?;
+    Ok((cred_def, schema_ref))
+}
+
+/// Gets the issuer DID of a legacy AnonCreds identifier, qualified on the given network.
+pub fn legacy_issuer(id: &str, network: SovrinNetwork) -> Result<String, TrustchainSovrinError> {
+    match id.split_once(':') {
+        Some((issuer, _)) if !issuer.is_empty() => Ok(network.did_indy(issuer)),
+        _ => Err(TrustchainSovrinError::InvalidDID(id.to_string())),
+    }
+}
+
+/// Verifies an AnonCreds presentation against the schemas and credential definitions of its
+/// credentials, fetched from the ledger of the given network by the source.
+///
+/// Revocation registries are not fetched, so presentations requesting proofs of
+/// non-revocation are rejected. Returns the DIDs of the credential issuers, in the order of
+/// the credentials in the presentation, each with the types of the credentials it issued. The
+/// type of an AnonCreds credential is the name of its schema.
+pub async fn verify_presentation<S: AnonCredsObjectSource + Sync>(
+    source: &S,
+    network: SovrinNetwork,
+    presentation: &Presentation,
+    presentation_request: &PresentationRequest,
+) -> Result<Vec<(String, Vec<String>)>, AnonCredsError> {
+    if presentation.identifiers.is_empty() {
+        return Err(AnonCredsError::InvalidPresentation(
+            "No credentials in presentation.".to_string(),
+        ));
+    }
+    let mut schemas: HashMap<SchemaId, Schema> = HashMap::new();
+    let mut cred_defs: HashMap<CredentialDefinitionId, CredentialDefinition> = HashMap::new();
+    let mut issuers: Vec<(String, Vec<String>)> = Vec::new();
+    for identifier in presentation.identifiers.iter() {
+        if cred_defs.contains_key(&identifier.cred_def_id) {
+            continue;
+        }
+        let schema_id = identifier.schema_id.to_string();
+        let cred_def_id = identifier.cred_def_id.to_string();
+        let reply = source.schema_reply(network, &schema_id).await?;
+        let (schema, schema_seq_no) = schema_from_reply(reply.as_bytes(), &schema_id)?;
+        let reply = source.cred_def_reply(network, &cred_def_id).await?;
+        let (cred_def, schema_ref) =
+            cred_def_from_reply(reply.as_bytes(), &cred_def_id, &schema_id)?;
+        // Legacy credential definitions refer to their schema by its sequence number.
+        if schema_ref != schema_seq_no {
+            return Err(TrustchainSovrinError::InvalidCredentialDefinition(format!(
+                "Credential definition {} is not on schema {}",
+                cred_def_id, schema_id
+            ))
+            .into());
+        }
+        let issuer = legacy_issuer(&cred_def_id, network)?;
+        let index = match issuers.iter().position(|(did, _)| did == &issuer) {
+            Some(index) => index,
+            None => {
+                issuers.push((issuer, Vec::new()));
+                issuers.len() - 1
+            }
+        };
+        let types = &mut issuers[index].1;
+        if !types.contains(&schema.name) {
+            types.push(schema.name.to_owned());
+        }
+        schemas.insert(identifier.schema_id.to_owned(), schema);
+        cred_defs.insert(identifier.cred_def_id.to_owned(), cred_def);
+    }
+    let valid = verify_anoncreds_presentation(
+        presentation,
+        presentation_request,
+        &schemas,
+        &cred_defs,
+        None,
+        None,
+        None,
+    )
+    .map_err(|e| AnonCredsError::InvalidPresentation(e.to_string()))?;
+    if !valid {
+        return Err(AnonCredsError::InvalidPresentation(
+            "Presentation proof does not verify.".to_string(),
+        ));
+    }
+    Ok(issuers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{
+        TEST_CRED_DEF_ID, TEST_GET_CRED_DEF_REPLY, TEST_GET_SCHEMA_REPLY, TEST_SCHEMA_ID,
+    };
+    use ::anoncreds::data_types::issuer_id::IssuerId;
+    use ::anoncreds::types::{
+        CredentialDefinitionConfig, MakeCredentialValues, PresentCredentials, SignatureType,
+    };
+    use ::anoncreds::{issuer, prover};
+
+    /// Ledger replies for a single schema and credential definition.
+    struct FixtureSource {
+        schema_reply: String,
+        cred_def_reply: String,
+    }
+
+    #[async_trait]
+    impl AnonCredsObjectSource for FixtureSource {
+        async fn schema_reply(
+            &self,
+            _network: SovrinNetwork,
+            _schema_id: &str,
+        ) -> Result<String, TrustchainSovrinError> {
+            Ok(self.schema_reply.to_owned())
+        }
+
+        async fn cred_def_reply(
+            &self,
+            _network: SovrinNetwork,
+            _cred_def_id: &str,
+        ) -> Result<String, TrustchainSovrinError> {
+            Ok(self.cred_def_reply.to_owned())
+        }
+    }
+
+    fn test_presentation_request() -> PresentationRequest {
+        serde_json::from_value(json!({
+            "name": "employee",
+            "version": "1.0",
+            "nonce": "1234567890",
+            "requested_attributes": { "role": { "name": "role" } },
+            "requested_predicates": {},
+        }))
+        .unwrap()
+    }
+
+    /// Issues a credential on the schema in TEST_GET_SCHEMA_REPLY, under a new credential
+    /// definition with identifier TEST_CRED_DEF_ID, and presents its "role" attribute.
+    ///
+    /// The placeholder key values in TEST_GET_CRED_DEF_REPLY cannot verify a presentation, so
+    /// the returned source answers with that reply carrying the new credential definition.
+    fn test_presentation() -> (FixtureSource, Presentation) {
+        let (schema, _) =
+            schema_from_reply(TEST_GET_SCHEMA_REPLY.as_bytes(), TEST_SCHEMA_ID).unwrap();
+        let schema_id = SchemaId::new(TEST_SCHEMA_ID).unwrap();
+        let cred_def_id = CredentialDefinitionId::new(TEST_CRED_DEF_ID).unwrap();
+        let (cred_def, cred_def_private, key_correctness_proof) =
+            issuer::create_credential_definition(
+                schema_id.to_owned(),
+                &schema,
+                IssuerId::new("BF4pE6ugcqNWt5kpJUE1Ro").unwrap(),
+                "default",
+                SignatureType::CL,
+                CredentialDefinitionConfig {
+                    support_revocation: false,
+                },
+            )
+            .unwrap();
+        let cred_offer = issuer::create_credential_offer(
+            schema_id.to_owned(),
+            cred_def_id.to_owned(),
+            &key_correctness_proof,
+        )
+        .unwrap();
+        let link_secret = prover::create_link_secret().unwrap();
+        let (cred_request, cred_request_metadata) = prover::create_credential_request(
+            Some("entropy"),
+            None,
+            &cred_def,
+            &link_secret,
+            "default",
+            &cred_offer,
+        )
+        .unwrap();
+        let mut cred_values = MakeCredentialValues::default();
+        cred_values.add_raw("name", "Alice").unwrap();
+        cred_values.add_raw("role", "engineer").unwrap();
+        let mut credential = issuer::create_credential(
+            &cred_def,
+            &cred_def_private,
+            &cred_offer,
+            &cred_request,
+            cred_values.into(),
+            None,
+        )
+        .unwrap();
+        prover::process_credential(
+            &mut credential,
+            &cred_request_metadata,
+            &link_secret,
+            &cred_def,
+            None,
+        )
+        .unwrap();
+
+        let mut cred_def_reply: Value = serde_json::from_str(TEST_GET_CRED_DEF_REPLY).unwrap();
+        cred_def_reply["result"]["data"] =
+            serde_json::to_value(&cred_def).unwrap()["value"].clone();
+        let source = FixtureSource {
+            schema_reply: TEST_GET_SCHEMA_REPLY.to_string(),
+            cred_def_reply: cred_def_reply.to_string(),
+        };
+
+        let mut present = PresentCredentials::default();
+        present
+            .add_credential(&credential, None, None)
+            .add_requested_attribute("role", true);
+        let schemas = HashMap::from([(schema_id, schema)]);
+        let cred_defs = HashMap::from([(cred_def_id, cred_def)]);
+        let presentation = prover::create_presentation(
+            &test_presentation_request(),
+            present,
+            None,
+            &link_secret,
+            &schemas,
+            &cred_defs,
+        )
+        .unwrap();
+        (source, presentation)
+    }
+
+    #[test]
+    fn test_schema_from_reply() {
+        let (schema, seq_no) =
+            schema_from_reply(TEST_GET_SCHEMA_REPLY.as_bytes(), TEST_SCHEMA_ID).unwrap();
+        assert_eq!(schema.name, "trustchain-employee");
+        assert_eq!(schema.version, "1.0");
+        assert_eq!(seq_no, 31050);
+
+        // The reply must be for the requested schema.
+        assert!(matches!(
+            schema_from_reply(
+                TEST_GET_SCHEMA_REPLY.as_bytes(),
+                "BF4pE6ugcqNWt5kpJUE1Ro:2:trustchain-employee:2.0"
+            ),
+            Err(TrustchainSovrinError::InvalidSchema(_))
+        ));
+
+        // A missing schema is answered with its name and version only.
+        let mut reply: Value = serde_json::from_str(TEST_GET_SCHEMA_REPLY).unwrap();
+        reply["result"]["data"]
+            .as_object_mut()
+            .unwrap()
+            .remove(ATTR_NAMES_KEY);
+        assert!(matches!(
+            schema_from_reply(reply.to_string().as_bytes(), TEST_SCHEMA_ID),
+            Err(TrustchainSovrinError::InvalidSchema(_))
+        ));
+    }
+
+    #[test]
+    fn test_cred_def_from_reply() {
+        let (cred_def, schema_ref) = cred_def_from_reply(
+            TEST_GET_CRED_DEF_REPLY.as_bytes(),
+            TEST_CRED_DEF_ID,
+            TEST_SCHEMA_ID,
+        )
+        .unwrap();
+        assert_eq!(cred_def.tag, "default");
+        assert_eq!(cred_def.schema_id.to_string(), TEST_SCHEMA_ID);
+        assert_eq!(schema_ref, 31050);
+
+        // The reply must be for the requested credential definition.
+        assert!(matches!(
+            cred_def_from_reply(
+                TEST_GET_CRED_DEF_REPLY.as_bytes(),
+                "BF4pE6ugcqNWt5kpJUE1Ro:3:CL:31050:other",
+                TEST_SCHEMA_ID,
+            ),
+            Err(TrustchainSovrinError::InvalidCredentialDefinition(_))
+        ));
+
+        let mut reply: Value = serde_json::from_str(TEST_GET_CRED_DEF_REPLY).unwrap();
+        reply["result"]["data"] = Value::Null;
+        assert!(matches!(
+            cred_def_from_reply(
+                reply.to_string().as_bytes(),
+                TEST_CRED_DEF_ID,
+                TEST_SCHEMA_ID
+            ),
+            Err(TrustchainSovrinError::InvalidCredentialDefinition(_))
+        ));
+    }
+
+    #[test]
+    fn test_legacy_issuer() {
+        assert_eq!(
+            legacy_issuer(TEST_CRED_DEF_ID, SovrinNetwork::MainNet).unwrap(),
+            "did:indy:sovrin:BF4pE6ugcqNWt5kpJUE1Ro"
+        );
+        assert!(legacy_issuer(":3:CL:31050:default", SovrinNetwork::MainNet).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_verify_presentation_no_credentials() {
+        let presentation: Presentation = serde_json::from_value(json!({
+            "proof": { "proofs": [], "aggregated_proof": { "c_hash": "1", "c_list": [] } },
+            "requested_proof": {},
+            "identifiers": [],
+        }))
+        .unwrap();
+        let presentation_request: PresentationRequest = serde_json::from_value(json!({
+            "name": "employee",
+            "version": "1.0",
+            "nonce": "1234",
+            "requested_attributes": {},
+            "requested_predicates": {},
+        }))
+        .unwrap();
+        assert!(matches!(
+            verify_presentation(
+                &FixtureSource {
+                    schema_reply: TEST_GET_SCHEMA_REPLY.to_string(),
+                    cred_def_reply: TEST_GET_CRED_DEF_REPLY.to_string(),
+                },
+                SovrinNetwork::MainNet,
+                &presentation,
+                &presentation_request,
+            )
+            .await,
+            Err(AnonCredsError::InvalidPresentation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_verify_presentation() {
+        let (source, presentation) = test_presentation();
+        let issuers = verify_presentation(
+            &source,
+            SovrinNetwork::MainNet,
+            &presentation,
+            &test_presentation_request(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            issuers,
+            vec![(
+                "did:indy:sovrin:BF4pE6ugcqNWt5kpJUE1Ro".to_string(),
+                vec!["trustchain-employee".to_string()]
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verify_presentation_tampered() {
+        let (source, presentation) = test_presentation();
+        let mut tampered = serde_json::to_value(&presentation).unwrap();
+        tampered["requested_proof"]["revealed_attrs"]["role"]["raw"] = json!("director");
+        let tampered: Presentation = serde_json::from_value(tampered).unwrap();
+        assert!(matches!(
+            verify_presentation(
+                &source,
+                SovrinNetwork::MainNet,
+                &tampered,
+                &test_presentation_request(),
+            )
+            .await,
+            Err(AnonCredsError::InvalidPresentation(_))
+        ));
+
+        // The presentation must be for the credential definition on the ledger.
+        let (other_source, _) = test_presentation();
+        assert!(matches!(
+            verify_presentation(
+                &other_source,
+                SovrinNetwork::MainNet,
+                &presentation,
+                &test_presentation_request(),
+            )
+            .await,
+            Err(AnonCredsError::InvalidPresentation(_))
+        ));
+    }
+}
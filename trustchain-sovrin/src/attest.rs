@@ -0,0 +1,256 @@
+//! Sovrin operation for DID attestation.
+use indy_vdr::ledger::RequestBuilder;
+use indy_vdr::pool::PreparedRequest;
+use indy_vdr::utils::did::DidValue;
+use serde_json::to_string_pretty as to_json;
+use ssi::did::{Document, VerificationMethod};
+use ssi::jwk::{Algorithm, JWK};
+use trustchain_core::attestor::AttestationClaims;
+use trustchain_core::controller::Controller;
+use trustchain_core::subject::Subject;
+use trustchain_core::utils::get_operations_path;
+
+use crate::attestor::SovrinAttestor;
+use crate::attrib::ControllerProof;
+use crate::controller::SovrinController;
+use crate::network::{normalise_did, SovrinNetwork};
+use crate::resolver::SovrinResolver;
+use crate::utils::ed25519_verkey;
+use crate::TrustchainSovrinError;
+
+/// Signs a ledger request with an Ed25519 key.
+pub fn sign_request(request: &mut PreparedRequest, key: &JWK) -> Result<(), TrustchainSovrinError> {
+    // Indy requests are only signed with Ed25519 keys.
+    ed25519_verkey(key)?;
+    let input = request
+        .get_signature_input()
+        .map_err(|e| TrustchainSovrinError::SigningError(e.to_string()))?;
+    let signature = ssi::jws::sign_bytes(Algorithm::EdDSA, input.as_bytes(), key)
+        .map_err(|e| TrustchainSovrinError::SigningError(e.to_string()))?;
+    request
+        .set_signature(&signature)
+        .map_err(|e| TrustchainSovrinError::SigningError(e.to_string()))
+}
+
+/// Builds an ATTRIB write request storing a controller proof for the controlled DID, signed
+/// with the Ed25519 ledger key of the controlled DID.
+pub fn build_attrib_request(
+    request_builder: &RequestBuilder,
+    controlled_did: &str,
+    proof: &ControllerProof,
+    ledger_key: &JWK,
+) -> Result<PreparedRequest, TrustchainSovrinError> {
+    let (_, id) = SovrinNetwork::from_did(controlled_did)?;
+    let did_value = DidValue::new(id, None);
+    let mut request = request_builder
+        .build_attrib_request(&did_value, &did_value, None, Some(&proof.to_raw()?), None)
+        .map_err(|_| TrustchainSovrinError::FailedToBuildRequest(controlled_did.to_string()))?;
+    sign_request(&mut request, ledger_key)?;
+    Ok(request)
+}
+
+/// Checks that a key is the ledger verification key of a resolved Sovrin DID document.
+fn check_ledger_key(doc: &Document, key: &JWK) -> Result<(), TrustchainSovrinError> {
+    let verkey = ed25519_verkey(key)?;
+    let is_ledger_key = doc.verification_method.iter().flatten().any(|vm| match vm {
+        VerificationMethod::Map(vm_map) => vm_map.public_key_base58.as_deref() == Some(&verkey),
+        _ => false,
+    });
+    match is_ledger_key {
+        true => Ok(()),
+        false => Err(TrustchainSovrinError::KeyMismatch(doc.id.to_string())),
+    }
+}
+
+/// Attests to a DID document as the given controller DID, returning the controller proof to
+/// store in an ATTRIB of the controlled DID. A Sovrin controller DID is normalised to its
+/// `did:indy` form, as the id of its resolved document, which the attested document must name
+/// as its controller to verify.
+pub fn controller_proof(
+    did: &str,
+    doc: &Document,
+    key_id: Option<&str>,
+    claims: &AttestationClaims,
+) -> Result<ControllerProof, Box<dyn std::error::Error>> {
+    let did = normalise_did(did)?;
+    let proof_value = SovrinAttestor::new(&did).attest_with_claims(doc, key_id, claims)?;
+    Ok(ControllerProof::new(did, proof_value))
+}
+
+// Function to resolve a controlled Sovrin DID, attest to its contents with the given claims and
+// build an ATTRIB transaction on the controlled DID storing the attestation proof. The signed
+// transaction is either submitted to the pool of the DID's network or saved to the operations
+// path.
+pub async fn attest_operation(
+    did: &str,
+    controlled_did: &str,
+    key_id: Option<&str>,
+    claims: &AttestationClaims,
+    resolver: &SovrinResolver,
+    submit: bool,
+    verbose: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // 1.1. Load controller from passed controlled_did to be signed and controller DID, with a
+    // Sovrin controller DID in the form of the id of its resolved document
+    let did = &normalise_did(did)?;
+    let controller = SovrinController::new(did, controlled_did);
+
+    if verbose {
+        println!("DID: {}", controller.did());
+        println!("Controlled DID: {}", controller.controlled_did());
+    }
+
+    // 1.2. Resolve the controlled_did document as on the ledger (without any existing
+    // controller proof, which is replaced by the new ATTRIB transaction).
+    let (doc, _) = resolver.wrapped_resolver.fetch_did(controlled_did).await?;
+
+    // 1.3. Check the ledger key of the controlled DID is available to sign the transaction
+    let ledger_key = controller.update_key()?;
+    check_ledger_key(&doc, &ledger_key)?;
+
+    // 2. Controller performs attestation to Document to generate proof data
+    let proof = controller_proof(did, &doc, key_id, claims)?;
+
+    // 3. Construct the signed ATTRIB transaction
+    let request_builder = resolver.wrapped_resolver.request_builder(controlled_did)?;
+    let request = build_attrib_request(&request_builder, controlled_did, &proof, &ledger_key)?;
+
+    // 4. Submit the transaction or save it to file in operations path
+    if submit {
+        let reply = resolver
+            .wrapped_resolver
+            .submit_write(controlled_did, &request)
+            .await?;
+        if verbose {
+            println!("Ledger reply: {}", reply);
+        }
+    } else {
+        let path = get_operations_path()?.join(format!(
+            "attest_operation_{}.json",
+            controller.controlled_did_suffix()
+        ));
+        std::fs::write(path, to_json(&request.req_json)?)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attrib::add_controller_proof;
+    use crate::attrib::TRUSTCHAIN_ATTRIB_NAME;
+    use crate::data::{TEST_CONTROLLER_DID, TEST_GET_NYM_REPLY};
+    use crate::resolver::{add_verkey_jwks, resolve_nym_reply};
+    use crate::utils::did_from_verkey;
+    use serde_json::json;
+    use ssi::did_resolve::DocumentMetadata;
+    use trustchain_core::chain::{Chain, DIDChain};
+    use trustchain_core::data::TEST_ROOT_PLUS_2_DOCUMENT;
+    use trustchain_core::key_manager::{AttestorKeyManager, KeyType};
+    use trustchain_core::utils::init;
+
+    #[test]
+    fn test_build_attrib_request() {
+        let key = JWK::generate_ed25519().unwrap();
+        let proof = ControllerProof::new(TEST_CONTROLLER_DID.to_string(), "proof".to_string());
+        let request = build_attrib_request(
+            &RequestBuilder::default(),
+            "did:sov:BF4pE6ugcqNWt5kpJUE1Ro",
+            &proof,
+            &key,
+        )
+        .unwrap();
+
+        let req_json = &request.req_json;
+        assert_eq!(req_json["identifier"], "BF4pE6ugcqNWt5kpJUE1Ro");
+        assert_eq!(req_json["operation"]["dest"], "BF4pE6ugcqNWt5kpJUE1Ro");
+        let raw: serde_json::Value =
+            serde_json::from_str(req_json["operation"]["raw"].as_str().unwrap()).unwrap();
+        assert_eq!(
+            raw[TRUSTCHAIN_ATTRIB_NAME]["controller"],
+            TEST_CONTROLLER_DID
+        );
+
+        // The request is signed by the ledger key.
+        let signature = bs58::decode(req_json["signature"].as_str().unwrap())
+            .into_vec()
+            .unwrap();
+        let input = request.get_signature_input().unwrap();
+        assert!(ssi::jws::verify_bytes(
+            Algorithm::EdDSA,
+            input.as_bytes(),
+            &key.to_public(),
+            &signature
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_sign_request_invalid_key() {
+        let mut request = RequestBuilder::default()
+            .build_get_nym_request(
+                None,
+                &DidValue::new("BF4pE6ugcqNWt5kpJUE1Ro", None),
+                None,
+                None,
+            )
+            .unwrap();
+        assert!(matches!(
+            sign_request(&mut request, &JWK::generate_secp256k1().unwrap()),
+            Err(TrustchainSovrinError::InvalidKey(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_ledger_key() {
+        let (doc, _) = resolve_nym_reply(TEST_GET_NYM_REPLY, SovrinNetwork::MainNet).unwrap();
+        assert!(matches!(
+            check_ledger_key(&doc, &JWK::generate_ed25519().unwrap()),
+            Err(TrustchainSovrinError::KeyMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_controller_proof_did_sov() -> Result<(), Box<dyn std::error::Error>> {
+        init();
+        // A Sovrin DID of a ledger key, resolved as did:indy.
+        let key = JWK::generate_ed25519()?;
+        let verkey = ed25519_verkey(&key)?;
+        let suffix = did_from_verkey(&verkey)?;
+        let udid = format!("did:indy:sovrin:{suffix}");
+        let mut udid_doc: Document = serde_json::from_value(json!({
+            "@context": ["https://www.w3.org/ns/did/v1"],
+            "id": udid,
+            "verificationMethod": [{
+                "id": format!("{udid}#verkey"),
+                "type": "Ed25519VerificationKey2018",
+                "controller": udid,
+                "publicKeyBase58": verkey
+            }]
+        }))?;
+        add_verkey_jwks(&mut udid_doc)?;
+        let attestor = SovrinAttestor::new(&udid);
+        attestor.save_key(attestor.did_suffix(), KeyType::SigningKey, &key, false)?;
+
+        // Attesting as the did:sov form of the controller names its did:indy form.
+        let mut ddoc = Document::from_json(TEST_ROOT_PLUS_2_DOCUMENT)?;
+        ddoc.controller = None;
+        let proof = controller_proof(
+            &format!("did:sov:{suffix}"),
+            &ddoc,
+            None,
+            &AttestationClaims::default(),
+        )?;
+        assert_eq!(proof.controller, udid);
+
+        // The proof, as lifted from the ATTRIB, verifies against the resolved chain.
+        let (ddoc, ddoc_meta) =
+            add_controller_proof(ddoc, DocumentMetadata::default(), Some(proof))?;
+        let chain = DIDChain::try_from_levels(vec![
+            (udid_doc, DocumentMetadata::default()),
+            (ddoc, ddoc_meta),
+        ])?;
+        assert!(chain.verify_proofs().is_ok());
+        Ok(())
+    }
+}
@@ -0,0 +1,191 @@
+//! Implementation of `Attestor` API for the Sovrin DID method.
+use ssi::did::Document;
+use ssi::{jwk::JWK, one_or_many::OneOrMany};
+use trustchain_core::revocation::remove_revocation_service;
+use trustchain_core::utils::{canonicalize, detached_sign, hash};
+use trustchain_core::{
+    attestor::{AttestationClaims, AttestationPayload, Attestor, AttestorError, ProofFormat},
+    key_manager::{AttestorKeyManager, KeyManager, KeyManagerError},
+    subject::Subject,
+};
+
+/// Struct for SovrinAttestor.
+///
+/// Signs with the algorithm of the loaded signing key, so the attestor may be a Sovrin DID
+/// (with Ed25519 keys) or a DID of another method managed under `TRUSTCHAIN_DATA`.
+pub struct SovrinAttestor {
+    did: String,
+}
+
+impl AttestorKeyManager for SovrinAttestor {}
+
+impl KeyManager for SovrinAttestor {}
+
+impl SovrinAttestor {
+    /// Construct a new SovrinAttestor instance.
+    pub fn new(did: &str) -> Self {
+        Self {
+            did: did.to_owned(),
+        }
+    }
+
+    /// Gets the signing keys of the attestor.
+    pub fn signing_keys(&self) -> Result<OneOrMany<JWK>, KeyManagerError> {
+        self.read_signing_keys(self.did_suffix())
+    }
+
+    /// Gets the signing key with ID `key_id` of the attestor.
+    pub fn signing_key(&self, key_id: Option<&str>) -> Result<JWK, KeyManagerError> {
+        let keys = self.signing_keys()?;
+        // If no key_id is given, return the first available key.
+        if let Some(key_id) = key_id {
+            for key in keys.into_iter() {
+                if key.key_id.as_deref() == Some(key_id) || key.thumbprint()? == key_id {
+                    return Ok(key);
+                }
+            }
+            Err(KeyManagerError::FailedToLoadKey)
+        } else {
+            match keys.first() {
+                Some(key) => Ok(key.to_owned()),
+                None => Err(KeyManagerError::FailedToLoadKey),
+            }
+        }
+    }
+}
+
+impl Subject for SovrinAttestor {
+    fn did(&self) -> &str {
+        &self.did
+    }
+}
+
+impl Attestor for SovrinAttestor {
+    fn attest_with_options(
+        &self,
+        doc: &Document,
+        key_id: Option<&str>,
+        format: ProofFormat,
+        claims: &AttestationClaims,
+    ) -> Result<String, AttestorError> {
+        // Add controller to document
+        let mut doc = doc.clone();
+        doc.controller = Some(OneOrMany::One(self.did().to_string()));
+        // Exclude the revocation list, which is signed separately
+        remove_revocation_service(&mut doc);
+
+        // Canonicalize document, as reconstructed when verifying the chain
+        let doc_canon = canonicalize(&doc)
+            .map_err(|_| AttestorError::InvalidDocumentParameters(doc.id.clone()))?;
+
+        // Get the signing key.
+        let signing_key = self.signing_key(key_id).map_err(|_| match key_id {
+            Some(key_id) => {
+                AttestorError::NoSigningKeyWithId(self.did().to_string(), key_id.to_string())
+            }
+            None => AttestorError::NoSigningKey(self.did().to_string()),
+        })?;
+        let algorithm = signing_key.get_algorithm().ok_or_else(|| {
+            AttestorError::SigningError(doc.id.clone(), "Unsupported key type.".to_string())
+        })?;
+
+        // Encode and sign
+        let result = match format {
+            ProofFormat::Embedded => ssi::jwt::encode_sign(
+                algorithm,
+                &AttestationPayload::new(hash(&doc_canon), claims),
+                &signing_key,
+            ),
+            ProofFormat::Detached if claims.is_empty() => {
+                detached_sign(algorithm, &doc_canon, &signing_key)
+            }
+            ProofFormat::Detached => return Err(AttestorError::DetachedClaims(doc.id)),
+        };
+        result.map_err(|e| AttestorError::SigningError(doc.id, e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolver::add_verkey_jwks;
+    use crate::utils::{did_from_verkey, ed25519_verkey};
+    use serde_json::json;
+    use ssi::did_resolve::DocumentMetadata;
+    use trustchain_core::chain::{Chain, DIDChain};
+    use trustchain_core::data::{TEST_ROOT_PLUS_2_DOCUMENT, TEST_TRUSTCHAIN_DOCUMENT};
+    use trustchain_core::key_manager::KeyType;
+    use trustchain_core::utils::{detached_verify, init};
+
+    #[test]
+    fn test_attest() -> Result<(), Box<dyn std::error::Error>> {
+        init();
+        let did = "did:sov:test_sovrin_attest";
+        let key = JWK::generate_ed25519()?;
+        let target = SovrinAttestor::new(did);
+        target.save_key(target.did_suffix(), KeyType::SigningKey, &key, false)?;
+
+        let doc = Document::from_json(TEST_TRUSTCHAIN_DOCUMENT).expect("Document failed to load.");
+        let proof = target.attest(&doc, None)?;
+
+        // Check signature and payload.
+        let decoded: String = ssi::jwt::decode_verify(&proof, &key.to_public())?;
+        let mut doc_with_controller = doc.clone();
+        doc_with_controller.controller = Some(OneOrMany::One(did.to_string()));
+        assert_eq!(decoded, hash(&canonicalize(&doc_with_controller)?));
+        assert!(ssi::jwt::decode_verify::<String>(&proof, &JWK::generate_ed25519()?).is_err());
+
+        // Detached JWS over the canonicalized document.
+        let proof = target.attest_with_format(&doc, None, ProofFormat::Detached)?;
+        assert!(detached_verify(
+            &proof,
+            &canonicalize(&doc_with_controller)?,
+            &key.to_public()
+        )
+        .is_ok());
+
+        // Unknown key ID.
+        assert!(matches!(
+            target.attest(&doc, Some("unknown")),
+            Err(AttestorError::NoSigningKeyWithId(_, _))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_attest_verify_proofs() -> Result<(), Box<dyn std::error::Error>> {
+        init();
+        // A Sovrin DID of a ledger key, resolved with its verkey mapped to a JWK.
+        let key = JWK::generate_ed25519()?;
+        let verkey = ed25519_verkey(&key)?;
+        let udid = format!("did:indy:sovrin:{}", did_from_verkey(&verkey)?);
+        let mut udid_doc: Document = serde_json::from_value(json!({
+            "@context": ["https://www.w3.org/ns/did/v1"],
+            "id": udid,
+            "verificationMethod": [{
+                "id": format!("{udid}#verkey"),
+                "type": "Ed25519VerificationKey2018",
+                "controller": udid,
+                "publicKeyBase58": verkey
+            }]
+        }))?;
+        add_verkey_jwks(&mut udid_doc)?;
+        let attestor = SovrinAttestor::new(&udid);
+        attestor.save_key(attestor.did_suffix(), KeyType::SigningKey, &key, false)?;
+
+        // The Sovrin DID attests a downstream DID.
+        let mut ddoc = Document::from_json(TEST_ROOT_PLUS_2_DOCUMENT)?;
+        ddoc.controller = Some(OneOrMany::One(udid.to_owned()));
+        let proof = attestor.attest(&ddoc, None)?;
+        let ddoc_meta: DocumentMetadata = serde_json::from_value(json!({
+            "proof": { "id": udid, "type": "JsonWebSignature2020", "proofValue": proof }
+        }))?;
+
+        let chain = DIDChain::try_from_levels(vec![
+            (udid_doc, DocumentMetadata::default()),
+            (ddoc, ddoc_meta),
+        ])?;
+        assert!(chain.verify_proofs().is_ok());
+        Ok(())
+    }
+}
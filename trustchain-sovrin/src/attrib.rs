@@ -0,0 +1,180 @@
+//! Trustchain controller proofs stored in Sovrin ATTRIB transactions.
+//!
+//! A NYM transaction only holds a DID's verification key, so a Sovrin DID document has no
+//! place for the Trustchain proof service. Instead, the upstream controller DID and its JWS
+//! proof are written as the raw attribute `trustchain` of the downstream DID:
+//!
+//! `{"trustchain": {"controller": "<uDID>", "proofValue": "<JWS>"}}`
+//!
+//! On resolution they are lifted into the `controller` of the DID document and the `proof`
+//! of the DID document metadata, in the same format as for the Trustchain proof service.
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use ssi::did::Document;
+use ssi::did_resolve::{DocumentMetadata, Metadata};
+use ssi::one_or_many::OneOrMany;
+use std::collections::HashMap;
+
+use crate::utils::reply_result;
+use crate::{TrustchainSovrinError, DATA_KEY};
+
+/// Name of the raw attribute holding the Trustchain controller proof.
+pub const TRUSTCHAIN_ATTRIB_NAME: &str = "trustchain";
+
+/// A controller proof: the upstream DID and its JWS over the downstream DID document.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ControllerProof {
+    /// The upstream (controller) DID.
+    pub controller: String,
+    /// JWS by the controller over the canonicalized downstream DID document.
+    pub proof_value: String,
+}
+
+impl ControllerProof {
+    pub fn new(controller: String, proof_value: String) -> Self {
+        Self {
+            controller,
+            proof_value,
+        }
+    }
+
+    /// Extracts the controller proof from a raw GET_ATTRIB reply for the `trustchain`
+    /// attribute. Returns `None` if the attribute is not set.
+    pub fn from_attrib_reply(reply: &[u8]) -> Result<Option<Self>, TrustchainSovrinError> {
+        let data = match reply_result(reply)?.get(DATA_KEY) {
+            None | Some(Value::Null) => return Ok(None),
+            Some(Value::String(data)) => data.to_owned(),
+            _ => return Err(TrustchainSovrinError::InvalidLedgerAnswer),
+        };
+        let mut raw: HashMap<String, Value> = serde_json::from_str(&data)
+            .map_err(|e| TrustchainSovrinError::InvalidControllerProof(e.to_string()))?;
+        match raw.remove(TRUSTCHAIN_ATTRIB_NAME) {
+            Some(proof) => serde_json::from_value(proof)
+                .map(Some)
+                .map_err(|e| TrustchainSovrinError::InvalidControllerProof(e.to_string())),
+            None => Err(TrustchainSovrinError::InvalidControllerProof(format!(
+                "Missing attribute: {}",
+                TRUSTCHAIN_ATTRIB_NAME
+            ))),
+        }
+    }
+
+    /// Gets the raw attribute value to write to the ledger in an ATTRIB transaction.
+    pub fn to_raw(&self) -> Result<Value, TrustchainSovrinError> {
+        let proof = serde_json::to_value(self)
+            .map_err(|e| TrustchainSovrinError::InvalidControllerProof(e.to_string()))?;
+        let mut raw = Map::new();
+        raw.insert(TRUSTCHAIN_ATTRIB_NAME.to_string(), proof);
+        Ok(Value::Object(raw))
+    }
+}
+
+/// Adds a controller proof (if any) to a DID document and its metadata: the document
+/// controller is set to the upstream DID and the proof is added to the metadata.
+pub fn add_controller_proof(
+    mut doc: Document,
+    mut doc_meta: DocumentMetadata,
+    proof: Option<ControllerProof>,
+) -> Result<(Document, DocumentMetadata), TrustchainSovrinError> {
+    let proof = match proof {
+        Some(proof) => proof,
+        None => return Ok((doc, doc_meta)),
+    };
+    if doc.controller.is_some() {
+        return Err(TrustchainSovrinError::InvalidControllerProof(
+            "Controller is already present in DID document.".to_string(),
+        ));
+    }
+    doc.controller = Some(OneOrMany::One(proof.controller.to_owned()));
+
+    let mut proof_hash_map: HashMap<String, Metadata> = HashMap::new();
+    proof_hash_map.insert(String::from("id"), Metadata::String(proof.controller));
+    proof_hash_map.insert(
+        String::from("type"),
+        Metadata::String("JsonWebSignature2020".to_string()),
+    );
+    proof_hash_map.insert(
+        String::from("proofValue"),
+        Metadata::String(proof.proof_value),
+    );
+    doc_meta
+        .property_set
+        .get_or_insert_with(HashMap::new)
+        .insert(String::from("proof"), Metadata::Map(proof_hash_map));
+    Ok((doc, doc_meta))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{TEST_CONTROLLER_DID, TEST_GET_ATTRIB_REPLY, TEST_GET_NYM_REPLY};
+    use crate::network::SovrinNetwork;
+    use crate::resolver::resolve_nym_reply;
+
+    #[test]
+    fn test_from_attrib_reply() {
+        let proof = ControllerProof::from_attrib_reply(TEST_GET_ATTRIB_REPLY.as_bytes())
+            .unwrap()
+            .unwrap();
+        assert_eq!(proof.controller, TEST_CONTROLLER_DID);
+        assert!(proof.proof_value.starts_with("eyJhbGciOiJFUzI1NksifQ."));
+
+        // The raw attribute round trips.
+        let reply = TEST_GET_ATTRIB_REPLY.replace(
+            &serde_json::to_string(&proof.to_raw().unwrap().to_string()).unwrap(),
+            "null",
+        );
+        assert_ne!(reply, TEST_GET_ATTRIB_REPLY);
+        assert!(ControllerProof::from_attrib_reply(reply.as_bytes())
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_from_attrib_reply_invalid() {
+        let reply = TEST_GET_ATTRIB_REPLY.replace("proofValue", "signature");
+        assert!(matches!(
+            ControllerProof::from_attrib_reply(reply.as_bytes()),
+            Err(TrustchainSovrinError::InvalidControllerProof(_))
+        ));
+    }
+
+    #[test]
+    fn test_add_controller_proof() {
+        let (doc, doc_meta) =
+            resolve_nym_reply(TEST_GET_NYM_REPLY, SovrinNetwork::MainNet).unwrap();
+        let proof = ControllerProof::from_attrib_reply(TEST_GET_ATTRIB_REPLY.as_bytes()).unwrap();
+        let (doc, doc_meta) = add_controller_proof(doc, doc_meta, proof.clone()).unwrap();
+        assert_eq!(
+            doc.controller,
+            Some(OneOrMany::One(TEST_CONTROLLER_DID.to_string()))
+        );
+        match doc_meta.property_set.as_ref().unwrap().get("proof") {
+            Some(Metadata::Map(proof_map)) => {
+                assert!(matches!(
+                    proof_map.get("proofValue"),
+                    Some(Metadata::String(proof_value))
+                        if proof_value == &proof.as_ref().unwrap().proof_value
+                ));
+                assert!(matches!(
+                    proof_map.get("id"),
+                    Some(Metadata::String(id)) if id == TEST_CONTROLLER_DID
+                ));
+            }
+            _ => panic!("Expected proof in document metadata."),
+        }
+
+        // A controller cannot be added twice.
+        assert!(add_controller_proof(doc, doc_meta, proof).is_err());
+    }
+
+    #[test]
+    fn test_add_controller_proof_none() {
+        let (doc, doc_meta) =
+            resolve_nym_reply(TEST_GET_NYM_REPLY, SovrinNetwork::MainNet).unwrap();
+        let (doc, doc_meta) = add_controller_proof(doc, doc_meta, None).unwrap();
+        assert!(doc.controller.is_none());
+        assert!(doc_meta.property_set.unwrap().get("proof").is_none());
+    }
+}
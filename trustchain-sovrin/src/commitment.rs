@@ -0,0 +1,215 @@
+//! Implementation of `Commitment` API for the Sovrin DID method.
+use serde_json::{json, Value};
+use ssi::did::{Document, VerificationMethod};
+use trustchain_core::commitment::{
+    Commitment, CommitmentError, CommitmentResult, DIDCommitment, TimestampCommitment,
+    TrivialCommitment,
+};
+use trustchain_core::verifier::Timestamp;
+
+use crate::state_proof::verify_nym_state_proof;
+use crate::utils::expanded_nym_data;
+use crate::{DEST_KEY, TXN_TIME_KEY, VERKEY_KEY};
+
+fn state_proof_hasher() -> fn(&[u8]) -> CommitmentResult<String> {
+    // Candidate data is a GET_NYM ledger reply, whose "hash" is the ledger state root under
+    // which the state proof in the reply proves the NYM data.
+    |x| verify_nym_state_proof(x).map_err(|e| CommitmentError::FailedToComputeHash(e.to_string()))
+}
+
+fn nym_data_decoder() -> fn(&[u8]) -> CommitmentResult<Value> {
+    |x| expanded_nym_data(x).map_err(|e| CommitmentError::DataDecodingError(e.to_string()))
+}
+
+/// A Commitment whose hash is the ledger state root proving a Sovrin NYM transaction.
+pub struct NymCommitment {
+    did_doc: Document,
+    candidate_data: Vec<u8>,
+    expected_data: Value,
+}
+
+impl NymCommitment {
+    pub fn new(did_doc: Document, ledger_reply: Vec<u8>) -> CommitmentResult<Self> {
+        // The NYM transaction must be for this DID and contain its verification keys.
+        let suffix = did_doc.id.rsplit(':').next().unwrap_or(&did_doc.id);
+        let mut expected_data = vec![json!({ DEST_KEY: suffix })];
+        if let Some(verification_methods) = &did_doc.verification_method {
+            expected_data.extend(verification_methods.iter().filter_map(|vm| {
+                match vm {
+                    VerificationMethod::Map(vm_map) => vm_map
+                        .public_key_base58
+                        .as_ref()
+                        .map(|key| json!({ VERKEY_KEY: key })),
+                    _ => None,
+                }
+            }));
+        }
+        Ok(Self {
+            did_doc,
+            candidate_data: ledger_reply,
+            expected_data: json!(expected_data),
+        })
+    }
+}
+
+impl TrivialCommitment for NymCommitment {
+    fn hasher(&self) -> fn(&[u8]) -> CommitmentResult<String> {
+        state_proof_hasher()
+    }
+
+    fn candidate_data(&self) -> &[u8] {
+        &self.candidate_data
+    }
+
+    /// Decodes the candidate data into the NYM transaction data.
+    fn decode_candidate_data(&self) -> fn(&[u8]) -> CommitmentResult<Value> {
+        nym_data_decoder()
+    }
+
+    fn to_commitment(self: Box<Self>, _: serde_json::Value) -> Box<dyn Commitment> {
+        self
+    }
+}
+
+impl Commitment for NymCommitment {
+    fn expected_data(&self) -> &serde_json::Value {
+        &self.expected_data
+    }
+}
+
+impl DIDCommitment for NymCommitment {
+    fn did(&self) -> &str {
+        &self.did_doc.id
+    }
+
+    fn did_document(&self) -> &Document {
+        &self.did_doc
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// A Commitment whose expected data is the Unix time at which a NYM transaction
+/// was written to the ledger.
+pub struct NymTimestampCommitment {
+    candidate_data: Vec<u8>,
+    expected_data: Timestamp,
+}
+
+impl NymTimestampCommitment {
+    pub fn new(candidate_data: Vec<u8>, expected_data: Timestamp) -> CommitmentResult<Self> {
+        Ok(Self {
+            candidate_data,
+            expected_data,
+        })
+    }
+}
+
+impl TrivialCommitment<Timestamp> for NymTimestampCommitment {
+    fn hasher(&self) -> fn(&[u8]) -> CommitmentResult<String> {
+        state_proof_hasher()
+    }
+
+    fn candidate_data(&self) -> &[u8] {
+        &self.candidate_data
+    }
+
+    /// Decodes the candidate data into the NYM transaction data.
+    fn decode_candidate_data(&self) -> fn(&[u8]) -> CommitmentResult<Value> {
+        nym_data_decoder()
+    }
+
+    /// Override the filter method to ensure only timestamp content is considered.
+    fn filter(&self) -> Option<Box<dyn Fn(&serde_json::Value) -> CommitmentResult<Value>>> {
+        Some(Box::new(move |value| {
+            if let Value::Object(map) = value {
+                match map.get(TXN_TIME_KEY) {
+                    Some(Value::Number(timestamp)) => Ok(Value::Number(timestamp.clone())),
+                    _ => Err(CommitmentError::DataDecodingFailure),
+                }
+            } else {
+                Err(CommitmentError::DataDecodingFailure)
+            }
+        }))
+    }
+
+    fn to_commitment(self: Box<Self>, _: Timestamp) -> Box<dyn Commitment<Timestamp>> {
+        self
+    }
+}
+
+impl Commitment<Timestamp> for NymTimestampCommitment {
+    fn expected_data(&self) -> &Timestamp {
+        &self.expected_data
+    }
+}
+
+impl TimestampCommitment for NymTimestampCommitment {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{
+        TEST_GET_NYM_REPLY, TEST_NYM_TXN_TIME, TEST_NYM_VERKEY, TEST_STATE_ROOT_HASH,
+    };
+
+    fn test_document(verkey: &str) -> Document {
+        serde_json::from_value(json!({
+            "@context": ["https://www.w3.org/ns/did/v1"],
+            "id": "did:sov:BF4pE6ugcqNWt5kpJUE1Ro",
+            "verificationMethod": [{
+                "id": "did:sov:BF4pE6ugcqNWt5kpJUE1Ro#verkey",
+                "type": "Ed25519VerificationKey2018",
+                "controller": "did:sov:BF4pE6ugcqNWt5kpJUE1Ro",
+                "publicKeyBase58": verkey
+            }]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_nym_commitment() {
+        let commitment = NymCommitment::new(
+            test_document(TEST_NYM_VERKEY),
+            TEST_GET_NYM_REPLY.as_bytes().to_vec(),
+        )
+        .unwrap();
+        assert_eq!(commitment.hash().unwrap(), TEST_STATE_ROOT_HASH);
+        assert!(commitment.verify(TEST_STATE_ROOT_HASH).is_ok());
+
+        // Verification fails against a different target.
+        let target = "6D2nTi4Yifzu9mygqoQThhYkec96JWf81ow6CGjxr5Nm";
+        assert!(commitment.verify(target).is_err());
+    }
+
+    #[test]
+    fn test_nym_commitment_wrong_key() {
+        let commitment = NymCommitment::new(
+            test_document("3ak9gLeW72a24Kbbv6LPL8YfKkNB47BeTo8LJF6SDYHh"),
+            TEST_GET_NYM_REPLY.as_bytes().to_vec(),
+        )
+        .unwrap();
+        assert!(matches!(
+            commitment.verify(TEST_STATE_ROOT_HASH),
+            Err(CommitmentError::FailedContentVerification(..))
+        ));
+    }
+
+    #[test]
+    fn test_nym_timestamp_commitment() {
+        let commitment =
+            NymTimestampCommitment::new(TEST_GET_NYM_REPLY.as_bytes().to_vec(), TEST_NYM_TXN_TIME)
+                .unwrap();
+        assert_eq!(commitment.timestamp(), TEST_NYM_TXN_TIME);
+        assert!(commitment.verify(TEST_STATE_ROOT_HASH).is_ok());
+
+        let commitment = NymTimestampCommitment::new(
+            TEST_GET_NYM_REPLY.as_bytes().to_vec(),
+            TEST_NYM_TXN_TIME + 1,
+        )
+        .unwrap();
+        assert!(commitment.verify(TEST_STATE_ROOT_HASH).is_err());
+    }
+}
@@ -0,0 +1,70 @@
+//! Implementation of `Controller` API for the Sovrin DID method.
+use ssi::jwk::JWK;
+use trustchain_core::attestor::Attestor;
+use trustchain_core::controller::Controller;
+use trustchain_core::key_manager::{ControllerKeyManager, KeyManager, KeyManagerError, KeyType};
+use trustchain_core::subject::Subject;
+
+use crate::attestor::SovrinAttestor;
+
+impl KeyManager for SovrinController {}
+impl ControllerKeyManager for SovrinController {}
+
+/// Struct for SovrinController.
+///
+/// The update key of a controlled Sovrin DID is its Ed25519 ledger key, which signs the
+/// write requests (such as ATTRIB transactions) for the DID.
+pub struct SovrinController {
+    did: String,
+    controlled_did: String,
+}
+
+impl SovrinController {
+    /// Constructs a new SovrinController instance from existing Subject and Controller DIDs.
+    pub fn new(did: &str, controlled_did: &str) -> Self {
+        Self {
+            did: did.to_owned(),
+            controlled_did: controlled_did.to_owned(),
+        }
+    }
+}
+
+impl Subject for SovrinController {
+    fn did(&self) -> &str {
+        &self.did
+    }
+}
+
+impl Controller for SovrinController {
+    fn controlled_did(&self) -> &str {
+        &self.controlled_did
+    }
+
+    fn update_key(&self) -> Result<JWK, KeyManagerError> {
+        self.read_update_key(self.controlled_did_suffix())
+    }
+
+    fn next_update_key(&self) -> Result<Option<JWK>, KeyManagerError> {
+        Ok(Some(
+            self.read_next_update_key(self.controlled_did_suffix())?,
+        ))
+    }
+
+    fn generate_next_update_key(&self) -> Result<(), KeyManagerError> {
+        let key = JWK::generate_ed25519().map_err(|_| KeyManagerError::FailedToSaveKey)?;
+        self.save_key(
+            self.controlled_did_suffix(),
+            KeyType::NextUpdateKey,
+            &key,
+            false,
+        )
+    }
+
+    fn recovery_key(&self) -> Result<JWK, KeyManagerError> {
+        self.read_recovery_key(self.controlled_did_suffix())
+    }
+
+    fn to_attestor(&self) -> Box<dyn Attestor> {
+        Box::new(SovrinAttestor::new(&self.did))
+    }
+}
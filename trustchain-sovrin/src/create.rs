@@ -0,0 +1,207 @@
+//! Sovrin operation for DID creation.
+use bip39::Mnemonic;
+use indy_vdr::ledger::RequestBuilder;
+use indy_vdr::pool::PreparedRequest;
+use indy_vdr::utils::did::DidValue;
+use serde_json::to_string_pretty as to_json;
+use ssi::jwk::JWK;
+use trustchain_core::controller::Controller;
+use trustchain_core::key_manager::{KeyManager, KeyType};
+use trustchain_core::utils::get_operations_path;
+
+use crate::controller::SovrinController;
+use crate::mnemonic::generate_ledger_key;
+use crate::network::SovrinNetwork;
+use crate::utils::{did_from_verkey, ed25519_verkey};
+use crate::TrustchainSovrinError;
+
+/// Builds the unsigned NYM request registering the DID of an Ed25519 ledger key on a network.
+///
+/// NYM transactions must be signed by an endorser, which is set as the request identifier.
+/// Without an endorser, the new DID is used as identifier (e.g. for self-serve networks).
+/// Returns the new DID together with the request.
+pub fn build_nym_request(
+    request_builder: &RequestBuilder,
+    network: SovrinNetwork,
+    ledger_key: &JWK,
+    endorser: Option<&str>,
+) -> Result<(String, PreparedRequest), TrustchainSovrinError> {
+    let verkey = ed25519_verkey(ledger_key)?;
+    let id = did_from_verkey(&verkey)?;
+    let identifier = match endorser {
+        Some(endorser) => match SovrinNetwork::from_did(endorser)? {
+            (endorser_network, endorser_id) if endorser_network == network => endorser_id,
+            _ => return Err(TrustchainSovrinError::InvalidDID(endorser.to_string())),
+        },
+        None => id.as_str(),
+    };
+    let did = network.did_indy(&id);
+    let request = request_builder
+        .build_nym_request(
+            &DidValue::new(identifier, None),
+            &DidValue::new(&id, None),
+            Some(verkey),
+            None,
+            None,
+            None,
+            None,
+        )
+        .map_err(|_| TrustchainSovrinError::FailedToBuildRequest(did.to_string()))?;
+    Ok((did, request))
+}
+
+/// Writes the ledger key of the new DID and its NYM request to file, returning the filename.
+///
+/// The ledger key both signs write requests for the DID (as its update key) and is the key
+/// of its DID document (as its signing key).
+fn write_create_operation(
+    did: &str,
+    ledger_key: &JWK,
+    request: &PreparedRequest,
+) -> Result<String, Box<dyn std::error::Error>> {
+    // DID is arbitrarily set to controlled_did in creation
+    let controller = SovrinController::new(did, did);
+    let did_suffix = controller.controlled_did_suffix();
+    controller.save_key(did_suffix, KeyType::UpdateKey, ledger_key, false)?;
+    controller.save_key(did_suffix, KeyType::SigningKey, ledger_key, false)?;
+
+    // Write NYM request to be signed and submitted by the endorser
+    let path = get_operations_path()?;
+    let filename = format!("create_operation_{}.json", did_suffix);
+    std::fs::write(path.join(&filename), to_json(&request.req_json)?)?;
+    Ok(filename)
+}
+
+/// Makes a new Sovrin DID with a generated ledger key, writing its NYM request to file.
+pub fn create_operation(
+    network: SovrinNetwork,
+    endorser: Option<&str>,
+    verbose: bool,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let ledger_key = JWK::generate_ed25519()?;
+    create_operation_from_key(network, endorser, ledger_key, verbose)
+}
+
+/// Makes a new Sovrin DID with a ledger key generated from a mnemonic, writing its NYM request
+/// to file.
+pub fn create_operation_mnemonic(
+    mnemonic: &str,
+    index: Option<u32>,
+    network: SovrinNetwork,
+    endorser: Option<&str>,
+    verbose: bool,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mnemonic = Mnemonic::parse(mnemonic)
+        .map_err(|e| TrustchainSovrinError::InvalidMnemonic(e.to_string()))?;
+    let ledger_key = generate_ledger_key(&mnemonic, index)?;
+    create_operation_from_key(network, endorser, ledger_key, verbose)
+}
+
+fn create_operation_from_key(
+    network: SovrinNetwork,
+    endorser: Option<&str>,
+    ledger_key: JWK,
+    verbose: bool,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let (did, request) =
+        build_nym_request(&RequestBuilder::default(), network, &ledger_key, endorser)?;
+
+    // Verbose output
+    if verbose {
+        println!("NYM request:");
+        println!("{}", to_json(&request.req_json)?);
+        println!("Controlled DID: {:?}", did);
+        println!("Verkey: {:?}", ed25519_verkey(&ledger_key)?);
+    }
+    write_create_operation(&did, &ledger_key, &request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trustchain_core::key_manager::ControllerKeyManager;
+    use trustchain_core::utils::init;
+
+    const TEST_MNEMONIC: &str =
+        "state draft moral repeat knife trend animal pretty delay collect fall adjust";
+
+    #[test]
+    fn test_build_nym_request() {
+        let key = JWK::generate_ed25519().unwrap();
+        let verkey = ed25519_verkey(&key).unwrap();
+        let (did, request) = build_nym_request(
+            &RequestBuilder::default(),
+            SovrinNetwork::MainNet,
+            &key,
+            None,
+        )
+        .unwrap();
+        let id = did_from_verkey(&verkey).unwrap();
+        assert_eq!(did, format!("did:indy:sovrin:{}", id));
+
+        let req_json = &request.req_json;
+        assert_eq!(req_json["operation"]["type"], "1");
+        assert_eq!(req_json["operation"]["dest"], id.as_str());
+        assert_eq!(req_json["operation"]["verkey"], verkey.as_str());
+        assert_eq!(req_json["identifier"], id.as_str());
+        // The request is left unsigned for the endorser.
+        assert!(req_json.get("signature").is_none());
+    }
+
+    #[test]
+    fn test_build_nym_request_endorser() {
+        let key = JWK::generate_ed25519().unwrap();
+        let (_, request) = build_nym_request(
+            &RequestBuilder::default(),
+            SovrinNetwork::StagingNet,
+            &key,
+            Some("did:sov:staging:BF4pE6ugcqNWt5kpJUE1Ro"),
+        )
+        .unwrap();
+        assert_eq!(request.req_json["identifier"], "BF4pE6ugcqNWt5kpJUE1Ro");
+
+        // The endorser must be on the same network.
+        assert!(matches!(
+            build_nym_request(
+                &RequestBuilder::default(),
+                SovrinNetwork::MainNet,
+                &key,
+                Some("did:sov:staging:BF4pE6ugcqNWt5kpJUE1Ro"),
+            ),
+            Err(TrustchainSovrinError::InvalidDID(_))
+        ));
+    }
+
+    #[test]
+    fn test_create_operation_mnemonic() -> Result<(), Box<dyn std::error::Error>> {
+        init();
+        let filename =
+            create_operation_mnemonic(TEST_MNEMONIC, None, SovrinNetwork::BuilderNet, None, false)?;
+        assert_eq!(filename, "create_operation_JZBDcz3cJyNtzMGCLVKAkJ.json");
+        assert!(get_operations_path()?.join(&filename).exists());
+
+        // The ledger key is saved as both update and signing key.
+        let controller = SovrinController::new(
+            "did:indy:sovrin:builder:JZBDcz3cJyNtzMGCLVKAkJ",
+            "did:indy:sovrin:builder:JZBDcz3cJyNtzMGCLVKAkJ",
+        );
+        let update_key = controller.read_update_key(controller.controlled_did_suffix())?;
+        assert_eq!(
+            ed25519_verkey(&update_key)?,
+            "AZwZEje9BYYr2dbU7WUgussLnagnGTQDoeqgYmPqWLTb"
+        );
+        let signing_keys = controller.read_signing_keys(controller.controlled_did_suffix())?;
+        assert_eq!(signing_keys.first(), Some(&update_key));
+
+        // Keys are not overwritten.
+        assert!(create_operation_mnemonic(
+            TEST_MNEMONIC,
+            None,
+            SovrinNetwork::BuilderNet,
+            None,
+            false
+        )
+        .is_err());
+        Ok(())
+    }
+}
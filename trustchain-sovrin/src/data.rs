@@ -0,0 +1,386 @@
+//! Test fixtures for crate.
+#![allow(dead_code)]
+
+#[cfg(test)]
+use crate::genesis::{default_generator, multi_signature_message};
+#[cfg(test)]
+use crate::state_proof::MultiSignatureValue;
+#[cfg(test)]
+use indy_blssignatures::{Bls, MultiSignature, SignKey, Signature, VerKey};
+#[cfg(test)]
+use serde_json::Value;
+
+// Note on test fixtures:
+//
+// TEST_GET_NYM_REPLY is a GET_NYM ledger reply, in the wire format returned by Indy pool nodes,
+// for the DID:
+// did:sov:BF4pE6ugcqNWt5kpJUE1Ro
+//
+// Its state proof contains a branch node and a leaf node of the ledger state trie, proving the
+// NYM data under the state root TEST_STATE_ROOT_HASH. The BLS multi-signature over the state
+// root is not produced by the keys of any real validator pool.
+//
+// TEST_GET_ATTRIB_REPLY is a GET_ATTRIB ledger reply for the `trustchain` raw attribute of the
+// same DID, holding a Trustchain controller proof. Its state proof is a single leaf node proving
+// the attribute hash under the state root TEST_ATTRIB_STATE_ROOT_HASH. The controller proof is
+// not a signature by the controller DID's keys, and the BLS multi-signature is again not by any
+// real validator pool.
+//
+// TEST_GET_ENDPOINT_REPLY is a GET_ATTRIB ledger reply for the `endpoint` raw attribute of the
+// same DID, with a service endpoint URL and a routing key. Its state proof is a single leaf node
+// proving the attribute hash under the state root TEST_ENDPOINT_STATE_ROOT_HASH.
+//
+// TEST_GET_SCHEMA_REPLY and TEST_GET_CRED_DEF_REPLY are GET_SCHEMA and GET_CLAIM_DEF ledger
+// replies for an AnonCreds schema written by the same DID and a CL credential definition on it.
+// Each state proof is a single leaf node proving the object under its own state root. The
+// credential definition key values are small placeholder numbers, not a usable CL public key, so
+// presentation tests issue a credential under a new credential definition and substitute its key
+// values into the reply.
+//
+// TEST_GENESIS_TRANSACTIONS contains NODE transactions for five validators NodeA, ..., NodeE,
+// followed by a transaction demoting NodeE. The BLS keys are those of Sovrin MainNet validators.
+//
+// The multi-signatures of the fixture replies are not by any of these validators. To verify a
+// reply with a valid multi-signature, tests re-sign it with a TestPool, whose BLS keys are
+// derived from fixed seeds, and verify it against the genesis transactions of that pool.
+//
+// TODO: none of these replies are recorded from a live pool, so they do not exercise the exact
+// serialisation of real nodes. Replace them with GET_NYM, GET_ATTRIB, GET_SCHEMA and
+// GET_CLAIM_DEF replies recorded from BuilderNet, together with its genesis transactions, so that
+// they verify against the real validator multi-signatures without re-signing.
+
+/// GET_NYM reply for did:sov:BF4pE6ugcqNWt5kpJUE1Ro.
+pub const TEST_GET_NYM_REPLY: &str = r##"
+{
+  "op": "REPLY",
+  "result": {
+    "type": "105",
+    "identifier": "LibindyDid111111111111",
+    "reqId": 1711620213263431000,
+    "dest": "BF4pE6ugcqNWt5kpJUE1Ro",
+    "data": "{\"dest\":\"BF4pE6ugcqNWt5kpJUE1Ro\",\"identifier\":\"V4SGRU86Z58d6TV7PBUe6f\",\"role\":\"101\",\"seqNo\":29314,\"txnTime\":1585581457,\"verkey\":\"~2861q8nPGS7nv53JP5TPYt\"}",
+    "seqNo": 29314,
+    "txnTime": 1585581457,
+    "state_proof": {
+      "root_hash": "6CtqSvxyu4hsjrqfrdy4CvzwMMvDJZameixdAJK76Hzq",
+      "proof_nodes": "+Pb4UaA+AZdF/tNdekGUuGmGlDPi/zqoDQO08y6OtS7MPWszT4CAgICAgICAgICAgKDJpUbqeN4w7Iq1hUpw3UxHjM/k4kPFDiIUvuftmhgraoCAgPihoD1SzdE/ntYxcvM4kk5FDNfDCl3ijdN9vnlDVMeULC/CuH74fLh6eyJpZGVudGlmaWVyIjoiVjRTR1JVODZaNThkNlRWN1BCVWU2ZiIsInJvbGUiOiIxMDEiLCJzZXFObyI6MjkzMTQsInR4blRpbWUiOjE1ODU1ODE0NTcsInZlcmtleSI6In4yODYxcThuUEdTN252NTNKUDVUUFl0In0=",
+      "multi_signature": {
+        "value": {
+          "ledger_id": 1,
+          "pool_state_root_hash": "6D2nTi4Yifzu9mygqoQThhYkec96JWf81ow6CGjxr5Nm",
+          "state_root_hash": "6CtqSvxyu4hsjrqfrdy4CvzwMMvDJZameixdAJK76Hzq",
+          "timestamp": 1585581460,
+          "txn_root_hash": "3ak9gLeW72a24Kbbv6LPL8YfKkNB47BeTo8LJF6SDYHh"
+        },
+        "signature": "HS6x5HtGu4wgHeCu7DmCaMGBNfHED5wNta1AKWZFFk4xBwG3Z6x6ZYk6Pu7jXn4fWDsiZdyfS7uTJWJiYKPqQAn71jwx9Dv2fGmgE2n1bYaoDvUowppDD9iPUm8msQDdxjSY1RCCfZ4wwc7jopLacrevteEF21JzGepmhkpVKRPwCa5",
+        "participants": [
+          "NodeA",
+          "NodeB",
+          "NodeC"
+        ]
+      }
+    }
+  }
+}
+"##;
+
+/// State root hash proven by the state proof in TEST_GET_NYM_REPLY.
+pub const TEST_STATE_ROOT_HASH: &str = "6CtqSvxyu4hsjrqfrdy4CvzwMMvDJZameixdAJK76Hzq";
+
+/// Ledger timestamp of the NYM transaction in TEST_GET_NYM_REPLY.
+pub const TEST_NYM_TXN_TIME: u64 = 1585581457;
+
+/// Full verification key of did:sov:BF4pE6ugcqNWt5kpJUE1Ro.
+pub const TEST_NYM_VERKEY: &str = "6anbDQNCcVh2f6okexjaX1VGj6tEnizJ1kV5UTBS8Zhi";
+
+/// GET_ATTRIB reply for the `trustchain` attribute of did:sov:BF4pE6ugcqNWt5kpJUE1Ro.
+pub const TEST_GET_ATTRIB_REPLY: &str = r##"
+{
+  "op": "REPLY",
+  "result": {
+    "type": "104",
+    "identifier": "LibindyDid111111111111",
+    "reqId": 1711620213263432000,
+    "dest": "BF4pE6ugcqNWt5kpJUE1Ro",
+    "raw": "trustchain",
+    "data": "{\"trustchain\":{\"controller\":\"did:ion:test:EiCClfEdkTv_aM3UnBBhlOV89LlGhpQAbfeZLFdFxVFkEg\",\"proofValue\":\"eyJhbGciOiJFUzI1NksifQ.RTUxVVM0SWJFLXZxRlBHd19oaFhjaUxrRmNLV2JqbzFFY1FaWUZVaklnSQ.G_naFgidoH4Nfx0R5DXWDxEwZOo3X-_kJkwcnX3ahM1rpMYQlomiv8jwVcAnbA7O5JCer9-k1orBz5uCDAS8LA\"}}",
+    "seqNo": 31045,
+    "txnTime": 1591357286,
+    "state_proof": {
+      "root_hash": "6HsZsrs6hF6ismtwNAFtR8eTVgoWYsAKUeGA1A2kFDHK",
+      "proof_nodes": "+Mv4ybhaIEJGNHBFNnVnY3FOV3Q1a3BKVUUxUm86MTo5OTkxZDY1MGJkNzAwYjg1ZjE1ZWMyNWUwZDAyNzVjZmE5ODhhNDQwMTM3OGI5ZTNiOTVjOGZlOGQxYTViNjFluGv4abhneyJsc24iOjMxMDQ1LCJsdXQiOjE1OTEzNTcyODYsInZhbCI6IjM5NGMwZDE3Mjc1ZWNkOWQ4MWUwMjhjNWI2ODUwZDE3ZDIxMWVlYjEzOTVlMzYwMTY1MzRmNjNmNzI5ODgyYTYifQ==",
+      "multi_signature": {
+        "value": {
+          "ledger_id": 1,
+          "pool_state_root_hash": "J8MLqHWUeWLCZZHpTsAMbDeJucqbQgyTVtTJ1FipospU",
+          "state_root_hash": "6HsZsrs6hF6ismtwNAFtR8eTVgoWYsAKUeGA1A2kFDHK",
+          "timestamp": 1591357289,
+          "txn_root_hash": "8dfAh2aUznmJXRbZnJCo3pBPfoVkCz7rKQQQdWvtER1y"
+        },
+        "signature": "HS6x5HtGu4wgHeCu7DmCaMGBNfHED5wNta1AKWZFFk4xBwG3Z6x6ZYk6Pu7jXn4fWDsiZdyfS7uTJWJiYKPqQAn71jwx9Dv2fGmgE2n1bYaoDvUowppDD9iPUm8msQDdxjSY1RCCfZ4wwc7jopLacrevteEF21JzGepmhkpVKRPwCa5",
+        "participants": [
+          "NodeA",
+          "NodeB",
+          "NodeC"
+        ]
+      }
+    }
+  }
+}
+"##;
+
+/// GET_TXN reply for the domain ledger transaction creating did:sov:BF4pE6ugcqNWt5kpJUE1Ro.
+pub const TEST_GET_TXN_REPLY: &str = r##"
+{
+  "op": "REPLY",
+  "result": {
+    "type": "3",
+    "identifier": "LibindyDid111111111111",
+    "reqId": 1711620213263433000,
+    "seqNo": 29314,
+    "data": {
+      "txn": {
+        "type": "1",
+        "data": {
+          "dest": "BF4pE6ugcqNWt5kpJUE1Ro",
+          "role": "101",
+          "verkey": "~2861q8nPGS7nv53JP5TPYt"
+        },
+        "metadata": {
+          "from": "V4SGRU86Z58d6TV7PBUe6f",
+          "reqId": 1585581456990434600
+        },
+        "protocolVersion": 2
+      },
+      "txnMetadata": {
+        "seqNo": 29314,
+        "txnTime": 1585581457
+      },
+      "ver": "1"
+    }
+  }
+}
+"##;
+
+/// State root hash proven by the state proof in TEST_GET_ATTRIB_REPLY.
+pub const TEST_ATTRIB_STATE_ROOT_HASH: &str = "6HsZsrs6hF6ismtwNAFtR8eTVgoWYsAKUeGA1A2kFDHK";
+
+/// GET_ATTRIB reply for the `endpoint` attribute of did:sov:BF4pE6ugcqNWt5kpJUE1Ro.
+pub const TEST_GET_ENDPOINT_REPLY: &str = r##"
+{
+  "op": "REPLY",
+  "result": {
+    "type": "104",
+    "identifier": "LibindyDid111111111111",
+    "reqId": 1711620213263433000,
+    "dest": "BF4pE6ugcqNWt5kpJUE1Ro",
+    "raw": "endpoint",
+    "data": "{\"endpoint\":{\"endpoint\":\"https://agent.example.com:8080/\",\"routingKeys\":[\"3zPGeUDQkfSuLVLuLqjPRGdnA9iBfPpjJbUoR9sEcSUs\"]}}",
+    "seqNo": 31052,
+    "txnTime": 1591358122,
+    "state_proof": {
+      "root_hash": "9eyFYNtvwKQi3KQ7VeGdCKW6YY8qzg5hEYp8zRH221S6",
+      "proof_nodes": "+Mv4ybhaIEJGNHBFNnVnY3FOV3Q1a3BKVUUxUm86MTpiNmJmN2JjOGQ5NmYzZWE5ZDEzMmM4M2IzZGE4ZTc3NjBlNDIwMTM4NDg1NjU3MzcyZGI0ZDZhOTgxZDNmZDlluGv4abhneyJsc24iOjMxMDUyLCJsdXQiOjE1OTEzNTgxMjIsInZhbCI6IjAxNGEyNTFmOTVjMGI5M2IwYWQzMmRhNjJmM2E2MzI2ODRjMTkyMTk1YTcwYTk4MjAxMmY2MDIyMzVhZThmYTEifQ==",
+      "multi_signature": {
+        "value": {
+          "ledger_id": 1,
+          "pool_state_root_hash": "J8MLqHWUeWLCZZHpTsAMbDeJucqbQgyTVtTJ1FipospU",
+          "state_root_hash": "9eyFYNtvwKQi3KQ7VeGdCKW6YY8qzg5hEYp8zRH221S6",
+          "timestamp": 1591358125,
+          "txn_root_hash": "5fPJQrtSzKPmfLJHm4fHZ7UWXtYAAn4dnNpgBFUvqxjL"
+        },
+        "signature": "HS6x5HtGu4wgHeCu7DmCaMGBNfHED5wNta1AKWZFFk4xBwG3Z6x6ZYk6Pu7jXn4fWDsiZdyfS7uTJWJiYKPqQAn71jwx9Dv2fGmgE2n1bYaoDvUowppDD9iPUm8msQDdxjSY1RCCfZ4wwc7jopLacrevteEF21JzGepmhkpVKRPwCa5",
+        "participants": [
+          "NodeA",
+          "NodeB",
+          "NodeC"
+        ]
+      }
+    }
+  }
+}
+"##;
+
+/// State root hash proven by the state proof in TEST_GET_ENDPOINT_REPLY.
+pub const TEST_ENDPOINT_STATE_ROOT_HASH: &str = "9eyFYNtvwKQi3KQ7VeGdCKW6YY8qzg5hEYp8zRH221S6";
+
+/// GET_SCHEMA reply for the schema TEST_SCHEMA_ID.
+pub const TEST_GET_SCHEMA_REPLY: &str = r##"
+{
+  "op": "REPLY",
+  "result": {
+    "type": "107",
+    "identifier": "LibindyDid111111111111",
+    "reqId": 1711620213263434000,
+    "dest": "BF4pE6ugcqNWt5kpJUE1Ro",
+    "data": {
+      "name": "trustchain-employee",
+      "version": "1.0",
+      "attr_names": ["name", "role"]
+    },
+    "seqNo": 31050,
+    "txnTime": 1591357400,
+    "state_proof": {
+      "root_hash": "CCLF23Dik5wJXhPcNq4ii3KcphjQQPWGMSGyWe27pWiV",
+      "proof_nodes": "+H34e7EgQkY0cEU2dWdjcU5XdDVrcEpVRTFSbzoyOnRydXN0Y2hhaW4tZW1wbG95ZWU6MS4wuEf4RbhDeyJsc24iOjMxMDUwLCJsdXQiOjE1OTEzNTc0MDAsInZhbCI6eyJhdHRyX25hbWVzIjpbIm5hbWUiLCJyb2xlIl19fQ==",
+      "multi_signature": {
+        "value": {
+          "ledger_id": 1,
+          "pool_state_root_hash": "J8MLqHWUeWLCZZHpTsAMbDeJucqbQgyTVtTJ1FipospU",
+          "state_root_hash": "CCLF23Dik5wJXhPcNq4ii3KcphjQQPWGMSGyWe27pWiV",
+          "timestamp": 1591357403,
+          "txn_root_hash": "8dfAh2aUznmJXRbZnJCo3pBPfoVkCz7rKQQQdWvtER1y"
+        },
+        "signature": "HS6x5HtGu4wgHeCu7DmCaMGBNfHED5wNta1AKWZFFk4xBwG3Z6x6ZYk6Pu7jXn4fWDsiZdyfS7uTJWJiYKPqQAn71jwx9Dv2fGmgE2n1bYaoDvUowppDD9iPUm8msQDdxjSY1RCCfZ4wwc7jopLacrevteEF21JzGepmhkpVKRPwCa5",
+        "participants": [
+          "NodeA",
+          "NodeB",
+          "NodeC"
+        ]
+      }
+    }
+  }
+}
+"##;
+
+/// Legacy identifier of the AnonCreds schema in TEST_GET_SCHEMA_REPLY.
+pub const TEST_SCHEMA_ID: &str = "BF4pE6ugcqNWt5kpJUE1Ro:2:trustchain-employee:1.0";
+
+/// GET_CLAIM_DEF reply for the credential definition TEST_CRED_DEF_ID.
+pub const TEST_GET_CRED_DEF_REPLY: &str = r##"
+{
+  "op": "REPLY",
+  "result": {
+    "type": "108",
+    "identifier": "LibindyDid111111111111",
+    "reqId": 1711620213263435000,
+    "origin": "BF4pE6ugcqNWt5kpJUE1Ro",
+    "ref": 31050,
+    "signature_type": "CL",
+    "tag": "default",
+    "data": {
+      "primary": {
+        "n": "779",
+        "s": "5",
+        "r": {"master_secret": "7", "name": "11", "role": "13"},
+        "rctxt": "17",
+        "z": "19"
+      }
+    },
+    "seqNo": 31051,
+    "txnTime": 1591357500,
+    "state_proof": {
+      "root_hash": "Buo1Ajh9ae6XngvMmFxuvm4GbR1SSQsZeXVua8qZpYuh",
+      "proof_nodes": "+L/4vaogQkY0cEU2dWdjcU5XdDVrcEpVRTFSbzozOkNMOjMxMDUwOmRlZmF1bHS4kPiOuIx7ImxzbiI6MzEwNTEsImx1dCI6MTU5MTM1NzUwMCwidmFsIjp7InByaW1hcnkiOnsibiI6Ijc3OSIsInMiOiI1IiwiciI6eyJtYXN0ZXJfc2VjcmV0IjoiNyIsIm5hbWUiOiIxMSIsInJvbGUiOiIxMyJ9LCJyY3R4dCI6IjE3IiwieiI6IjE5In19fQ==",
+      "multi_signature": {
+        "value": {
+          "ledger_id": 1,
+          "pool_state_root_hash": "J8MLqHWUeWLCZZHpTsAMbDeJucqbQgyTVtTJ1FipospU",
+          "state_root_hash": "Buo1Ajh9ae6XngvMmFxuvm4GbR1SSQsZeXVua8qZpYuh",
+          "timestamp": 1591357503,
+          "txn_root_hash": "8dfAh2aUznmJXRbZnJCo3pBPfoVkCz7rKQQQdWvtER1y"
+        },
+        "signature": "HS6x5HtGu4wgHeCu7DmCaMGBNfHED5wNta1AKWZFFk4xBwG3Z6x6ZYk6Pu7jXn4fWDsiZdyfS7uTJWJiYKPqQAn71jwx9Dv2fGmgE2n1bYaoDvUowppDD9iPUm8msQDdxjSY1RCCfZ4wwc7jopLacrevteEF21JzGepmhkpVKRPwCa5",
+        "participants": [
+          "NodeA",
+          "NodeB",
+          "NodeC"
+        ]
+      }
+    }
+  }
+}
+"##;
+
+/// Legacy identifier of the AnonCreds credential definition in TEST_GET_CRED_DEF_REPLY.
+pub const TEST_CRED_DEF_ID: &str = "BF4pE6ugcqNWt5kpJUE1Ro:3:CL:31050:default";
+
+/// Controller DID in the Trustchain controller proof of TEST_GET_ATTRIB_REPLY.
+pub const TEST_CONTROLLER_DID: &str = "did:ion:test:EiCClfEdkTv_aM3UnBBhlOV89LlGhpQAbfeZLFdFxVFkEg";
+
+/// Genesis transactions for a pool of five validators, NodeA to NodeE, followed by a transaction
+/// demoting NodeE, leaving four active validators.
+pub const TEST_GENESIS_TRANSACTIONS: &str = r##"
+{"reqSignature":{},"txn":{"data":{"data":{"alias":"NodeA","blskey":"4Yry2Z17vf1Hf24HvRrduj3Zi5LBJ1x7PbDqNWX19RxHcYJVjpy2f9qriZk7Fx812Xip6LEhsEXWRB1qKujBwdLNbCvMFYnJK2kS2B9HNgDgbVwDbpw16QNuJMtUnvQv1B6vCmci96gypYWMvZmJ6p9qsPXA7CZ2ZSjRuLg3RqvD7y4","client_ip":"127.0.0.1","client_port":"9702","node_ip":"127.0.0.1","node_port":"9701","services":["VALIDATOR"]},"dest":"Gw6pDLhcBcoQesN72qfotTgFa7cbuqZpkX3Xo6pLhPhv"},"metadata":{"from":"Th7MpTaRZVRYnPiabds81Y"},"type":"0"},"txnMetadata":{"seqNo":1,"txnId":"0000000000000000000000000000000000000000000000000000000000000001"},"ver":"1"}
+{"reqSignature":{},"txn":{"data":{"data":{"alias":"NodeB","blskey":"2AKAKFSqGqSSp3nMi4Lmcm7Y48x3LYQuQEsFfWw5NxS9KE9x2DPf6aPC7wiiRYQcsNz5T9vboEopCA2XhpubGQdknXLCC98cUHwnr9WEzu1spRWNnqXufMYRVoF7Bbb6GXKsKqeu5VJYYXUeE5hu94MK7TwLS1tGT1pKPip5FoVrVPw","client_ip":"127.0.0.1","client_port":"9704","node_ip":"127.0.0.1","node_port":"9703","services":["VALIDATOR"]},"dest":"8ECVSk179mjsjKRLWiQtssMLgp6EPhWXtaYyStWPSGAb"},"metadata":{"from":"Th7MpTaRZVRYnPiabds81Y"},"type":"0"},"txnMetadata":{"seqNo":2,"txnId":"0000000000000000000000000000000000000000000000000000000000000002"},"ver":"1"}
+{"reqSignature":{},"txn":{"data":{"data":{"alias":"NodeC","blskey":"3NU2sWYG7eeJY66W1FGpLgLaDS9fDQfKMNgPGMCk9iTcatMd4XdmAF5UqULkLUpGWABftNTrRsgm82DpfJ1cTu7CTB84KYoW4SYf7Rq4a2wi6rVbmU6k76ZYgmny6h8vqBbdRwozxVTjkPyzV6Z5MSA3vrDdf31iiiPEcXTTT3oFhKC","client_ip":"127.0.0.1","client_port":"9706","node_ip":"127.0.0.1","node_port":"9705","services":["VALIDATOR"]},"dest":"DKVxG2fXXTU8yT5N7hGEbXB3dfdAnYv1JczDUHpmDxya"},"metadata":{"from":"Th7MpTaRZVRYnPiabds81Y"},"type":"0"},"txnMetadata":{"seqNo":3,"txnId":"0000000000000000000000000000000000000000000000000000000000000003"},"ver":"1"}
+{"reqSignature":{},"txn":{"data":{"data":{"alias":"NodeD","blskey":"3YNLhpremzHvPeQd43C1B27JgmHt83A6emNh1iWxt3CJQBHDYx2MnZd7oTN4jrnep1hnNsCxh362ZKqJk48BJ1VtzZqDRw6ZHuYefWrkV5ujeW17SmDXMH28gYKWzRHA57EP3bvM8NGQCnHJ1k4eZyfF571CdBF3Sw6gNSntyNJbvNF","client_ip":"127.0.0.1","client_port":"9708","node_ip":"127.0.0.1","node_port":"9707","services":["VALIDATOR"]},"dest":"4PS3EDQ3dW1tci1Bp6543CfuuebjFrg36kLAUcskGfaA"},"metadata":{"from":"Th7MpTaRZVRYnPiabds81Y"},"type":"0"},"txnMetadata":{"seqNo":4,"txnId":"0000000000000000000000000000000000000000000000000000000000000004"},"ver":"1"}
+{"reqSignature":{},"txn":{"data":{"data":{"alias":"NodeE","blskey":"37d7DmcwGWM7yfnpwLGzwVy6zZwoc6cAgeeSJFBWbVh6jq5tP8dPf7s2XDxxtWafmr1JdyzycBcNztEsE8Uf9qX2jRoXzhCnjEEYJCAByEn5hWC2VQ9EqkuKzq28Vob7Piof7rEJeUPxuBZtrXL1khyTN2waQtix6CYtv9QejNPZVJ2","client_ip":"127.0.0.1","client_port":"9710","node_ip":"127.0.0.1","node_port":"9709","services":["VALIDATOR"]},"dest":"9AoGc8MQuLN3uQPd2JmFyuSxRYDfDsTxzoK8Hm2XLEqD"},"metadata":{"from":"Th7MpTaRZVRYnPiabds81Y"},"type":"0"},"txnMetadata":{"seqNo":5,"txnId":"0000000000000000000000000000000000000000000000000000000000000005"},"ver":"1"}
+{"reqSignature":{},"txn":{"data":{"data":{"alias":"NodeE","services":[]},"dest":"9AoGc8MQuLN3uQPd2JmFyuSxRYDfDsTxzoK8Hm2XLEqD"},"metadata":{"from":"Th7MpTaRZVRYnPiabds81Y"},"type":"0"},"txnMetadata":{"seqNo":6,"txnId":"0000000000000000000000000000000000000000000000000000000000000006"},"ver":"1"}
+"##;
+
+/// A validator pool with BLS keys derived from fixed seeds, to sign the multi-signatures of the
+/// fixture replies.
+#[cfg(test)]
+pub(crate) struct TestPool {
+    nodes: Vec<(String, SignKey)>,
+}
+
+#[cfg(test)]
+impl TestPool {
+    /// Constructs a pool of `n` validators, aliased Node1, ..., Noden.
+    pub(crate) fn new(n: usize) -> Self {
+        let nodes = (1..=n)
+            .map(|i| {
+                let seed = [i as u8; 32];
+                (format!("Node{}", i), SignKey::new(Some(&seed)).unwrap())
+            })
+            .collect();
+        Self { nodes }
+    }
+
+    /// Gets genesis transactions with a NODE transaction for each validator of the pool.
+    pub(crate) fn genesis(&self) -> String {
+        let generator = default_generator().unwrap();
+        self.nodes
+            .iter()
+            .enumerate()
+            .map(|(i, (alias, sign_key))| {
+                let ver_key = VerKey::new(&generator, sign_key).unwrap();
+                serde_json::json!({
+                    "reqSignature": {},
+                    "txn": {
+                        "data": {
+                            "data": {
+                                "alias": alias,
+                                "blskey": bs58::encode(ver_key.as_bytes()).into_string(),
+                                "services": ["VALIDATOR"]
+                            },
+                            "dest": bs58::encode([i as u8 + 1; 32]).into_string()
+                        },
+                        "type": "0"
+                    },
+                    "txnMetadata": { "seqNo": i + 1 },
+                    "ver": "1"
+                })
+                .to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Signs the state values of the multi-signature in a raw ledger reply by the first
+    /// `signers` validators of the pool, returning the re-signed reply.
+    pub(crate) fn sign_reply(&self, reply: &str, signers: usize) -> String {
+        let mut reply: Value = serde_json::from_str(reply).unwrap();
+        let multi_signature = &mut reply["result"]["state_proof"]["multi_signature"];
+        let value: MultiSignatureValue =
+            serde_json::from_value(multi_signature["value"].to_owned()).unwrap();
+        let message = multi_signature_message(&value).unwrap();
+        let signatures: Vec<Signature> = self.nodes[..signers]
+            .iter()
+            .map(|(_, sign_key)| Bls::sign(&message, sign_key).unwrap())
+            .collect();
+        let signature =
+            MultiSignature::new(&signatures.iter().collect::<Vec<&Signature>>()).unwrap();
+        multi_signature["signature"] =
+            Value::from(bs58::encode(signature.as_bytes()).into_string());
+        multi_signature["participants"] = Value::from(
+            self.nodes[..signers]
+                .iter()
+                .map(|(alias, _)| alias.to_owned())
+                .collect::<Vec<String>>(),
+        );
+        reply.to_string()
+    }
+}
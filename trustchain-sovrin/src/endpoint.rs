@@ -0,0 +1,234 @@
+//! Service endpoints stored in Sovrin `endpoint` ATTRIB transactions.
+//!
+//! Sovrin DIDs publish their service endpoint as the raw attribute `endpoint`:
+//!
+//! `{"endpoint": {"endpoint": "<URL>", "routingKeys": [...], "types": [...]}}`
+//!
+//! On resolution it is mapped into the `service` entries of the DID document, as specified
+//! for `did:indy`: one service per listed type, with the types `endpoint` and
+//! `did-communication` when none are listed.
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use ssi::did::{Document, Service, ServiceEndpoint};
+use ssi::one_or_many::OneOrMany;
+use std::collections::HashMap;
+
+use crate::utils::reply_result;
+use crate::{TrustchainSovrinError, DATA_KEY};
+
+/// Name of the raw attribute holding the service endpoint.
+pub const ENDPOINT_ATTRIB_NAME: &str = "endpoint";
+
+/// Service type of a plain endpoint.
+const ENDPOINT_TYPE: &str = "endpoint";
+/// Service type of a DIDComm v1 endpoint.
+const DID_COMMUNICATION_TYPE: &str = "did-communication";
+/// Service type of a DIDComm v2 endpoint.
+const DIDCOMM_TYPE: &str = "DIDComm";
+
+/// A Sovrin service endpoint, as written in an `endpoint` ATTRIB transaction.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SovrinEndpoint {
+    /// The endpoint URL. Legacy attributes with only a host address (`ha`) have none.
+    pub endpoint: Option<String>,
+    /// Routing keys for DIDComm services.
+    #[serde(default)]
+    pub routing_keys: Vec<String>,
+    /// Service types, defaulting to `endpoint` and `did-communication`.
+    pub types: Option<Vec<String>>,
+}
+
+impl SovrinEndpoint {
+    /// Extracts the service endpoint from a raw GET_ATTRIB reply for the `endpoint` attribute.
+    /// Returns `None` if the attribute is not set or has no endpoint URL.
+    pub fn from_attrib_reply(reply: &[u8]) -> Result<Option<Self>, TrustchainSovrinError> {
+        let data = match reply_result(reply)?.get(DATA_KEY) {
+            None | Some(Value::Null) => return Ok(None),
+            Some(Value::String(data)) => data.to_owned(),
+            _ => return Err(TrustchainSovrinError::InvalidLedgerAnswer),
+        };
+        let mut raw: HashMap<String, Value> = serde_json::from_str(&data)
+            .map_err(|e| TrustchainSovrinError::InvalidEndpoint(e.to_string()))?;
+        let endpoint: Self = match raw.remove(ENDPOINT_ATTRIB_NAME) {
+            Some(endpoint) => serde_json::from_value(endpoint)
+                .map_err(|e| TrustchainSovrinError::InvalidEndpoint(e.to_string()))?,
+            None => {
+                return Err(TrustchainSovrinError::InvalidEndpoint(format!(
+                    "Missing attribute: {}",
+                    ENDPOINT_ATTRIB_NAME
+                )))
+            }
+        };
+        Ok(endpoint.endpoint.is_some().then_some(endpoint))
+    }
+
+    /// Gets the DID document services of the endpoint for a DID whose verification key has
+    /// the given ID. Unknown service types are skipped.
+    pub fn to_services(&self, did: &str, verkey_id: &str) -> Vec<Service> {
+        let endpoint = match &self.endpoint {
+            Some(endpoint) => endpoint,
+            None => return Vec::new(),
+        };
+        let default_types = [ENDPOINT_TYPE, DID_COMMUNICATION_TYPE].map(str::to_string);
+        let types = self.types.as_deref().unwrap_or(&default_types);
+        types
+            .iter()
+            .filter_map(|service_type| {
+                let (fragment, property_set) = match service_type.as_str() {
+                    ENDPOINT_TYPE => ("endpoint", None),
+                    DID_COMMUNICATION_TYPE => (
+                        "did-communication",
+                        Some(json!({
+                            "recipientKeys": [verkey_id],
+                            "routingKeys": self.routing_keys,
+                            "priority": 0,
+                            "accept": ["didcomm/aip2;env=rfc19"],
+                        })),
+                    ),
+                    DIDCOMM_TYPE => (
+                        "didcomm-1",
+                        Some(json!({
+                            "routingKeys": self.routing_keys,
+                            "accept": ["didcomm/v2"],
+                        })),
+                    ),
+                    _ => return None,
+                };
+                Some(Service {
+                    id: format!("{}#{}", did, fragment),
+                    type_: OneOrMany::One(service_type.to_string()),
+                    service_endpoint: Some(OneOrMany::One(ServiceEndpoint::URI(
+                        endpoint.to_string(),
+                    ))),
+                    property_set: property_set.and_then(|value| match value {
+                        Value::Object(map) => Some(map),
+                        _ => None,
+                    }),
+                })
+            })
+            .collect()
+    }
+
+    /// Gets the raw attribute value to write to the ledger in an ATTRIB transaction.
+    pub fn to_raw(&self) -> Result<Value, TrustchainSovrinError> {
+        let endpoint = serde_json::to_value(self)
+            .map_err(|e| TrustchainSovrinError::InvalidEndpoint(e.to_string()))?;
+        let mut raw = Map::new();
+        raw.insert(ENDPOINT_ATTRIB_NAME.to_string(), endpoint);
+        Ok(Value::Object(raw))
+    }
+}
+
+/// Adds the services of a service endpoint (if any) to a DID document.
+pub fn add_endpoint_services(
+    mut doc: Document,
+    endpoint: Option<SovrinEndpoint>,
+) -> Result<Document, TrustchainSovrinError> {
+    let endpoint = match endpoint {
+        Some(endpoint) => endpoint,
+        None => return Ok(doc),
+    };
+    // DIDComm v1 services reference the ledger verification key of the DID.
+    let verkey_id = match doc.verification_method.as_ref().and_then(|vms| vms.first()) {
+        Some(vm) => vm.get_id(&doc.id),
+        None => {
+            return Err(TrustchainSovrinError::InvalidEndpoint(
+                "DID document has no verification method.".to_string(),
+            ))
+        }
+    };
+    let services = endpoint.to_services(&doc.id, &verkey_id);
+    if !services.is_empty() {
+        doc.service.get_or_insert_with(Vec::new).extend(services);
+    }
+    Ok(doc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{TEST_GET_ENDPOINT_REPLY, TEST_GET_NYM_REPLY};
+    use crate::network::SovrinNetwork;
+    use crate::resolver::resolve_nym_reply;
+    use trustchain_core::utils::HasEndpoints;
+
+    #[test]
+    fn test_from_attrib_reply() {
+        let endpoint = SovrinEndpoint::from_attrib_reply(TEST_GET_ENDPOINT_REPLY.as_bytes())
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            endpoint.endpoint.as_deref(),
+            Some("https://agent.example.com:8080/")
+        );
+        assert_eq!(endpoint.routing_keys.len(), 1);
+        assert!(endpoint.types.is_none());
+
+        // Legacy host address attributes have no endpoint URL.
+        let reply = TEST_GET_ENDPOINT_REPLY.replace(
+            r#"\"endpoint\":\"https://agent.example.com:8080/\""#,
+            r#"\"ha\":\"127.0.0.1:9702\""#,
+        );
+        assert_ne!(reply, TEST_GET_ENDPOINT_REPLY);
+        assert!(SovrinEndpoint::from_attrib_reply(reply.as_bytes())
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_to_services() {
+        let did = "did:indy:sovrin:BF4pE6ugcqNWt5kpJUE1Ro";
+        let verkey_id = format!("{}#verkey", did);
+        let mut endpoint = SovrinEndpoint {
+            endpoint: Some("https://agent.example.com:8080/".to_string()),
+            routing_keys: vec![],
+            types: None,
+        };
+        let services = endpoint.to_services(did, &verkey_id);
+        assert_eq!(services.len(), 2);
+        assert_eq!(services[0].id, format!("{}#endpoint", did));
+        assert_eq!(services[0].type_, OneOrMany::One("endpoint".to_string()));
+        assert_eq!(services[1].id, format!("{}#did-communication", did));
+        assert_eq!(
+            services[1].property_set.as_ref().unwrap()["recipientKeys"],
+            json!([verkey_id])
+        );
+
+        endpoint.types = Some(vec!["DIDComm".to_string(), "unknown".to_string()]);
+        let services = endpoint.to_services(did, &verkey_id);
+        assert_eq!(services.len(), 1);
+        assert_eq!(services[0].id, format!("{}#didcomm-1", did));
+    }
+
+    #[test]
+    fn test_add_endpoint_services() {
+        let (doc, _) = resolve_nym_reply(TEST_GET_NYM_REPLY, SovrinNetwork::MainNet).unwrap();
+        assert!(doc.get_endpoints().is_none());
+        let endpoint =
+            SovrinEndpoint::from_attrib_reply(TEST_GET_ENDPOINT_REPLY.as_bytes()).unwrap();
+        let doc = add_endpoint_services(doc, endpoint).unwrap();
+        assert_eq!(
+            doc.get_endpoints().unwrap(),
+            vec![ServiceEndpoint::URI("https://agent.example.com:8080/".to_string()); 2]
+        );
+
+        // Without an endpoint the document is unchanged.
+        let doc = add_endpoint_services(doc.clone(), None).unwrap();
+        assert_eq!(doc.service.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_to_raw() {
+        let endpoint = SovrinEndpoint {
+            endpoint: Some("https://agent.example.com:8080/".to_string()),
+            routing_keys: vec![],
+            types: Some(vec!["endpoint".to_string()]),
+        };
+        let raw = endpoint.to_raw().unwrap();
+        assert_eq!(
+            raw[ENDPOINT_ATTRIB_NAME]["endpoint"],
+            "https://agent.example.com:8080/"
+        );
+    }
+}
@@ -0,0 +1,297 @@
+//! Validator pool data from Indy genesis transactions.
+use indy_blssignatures::{Bls, Generator, MultiSignature as BlsMultiSignature, VerKey};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+
+use crate::state_proof::{MultiSignature, MultiSignatureValue};
+use crate::TrustchainSovrinError;
+
+/// Transaction type of NODE transactions.
+const NODE_TXN_TYPE: &str = "0";
+/// Service advertised by nodes taking part in consensus.
+const VALIDATOR_SERVICE: &str = "VALIDATOR";
+const ALIAS_KEY: &str = "alias";
+const BLS_KEY_KEY: &str = "blskey";
+const SERVICES_KEY: &str = "services";
+/// Generator of the BLS signature group used by Indy pools.
+const DEFAULT_GENERATOR: &str = "3LHpUjiyFC2q2hD7MnwwNmVXiuaFbQx2XkAFJWzswCjgN1utjsCeLzHsKk1nJvFEaS4fcrUmVAkdhtPCYbrVyATZcmzwJReTcJqwqBCPTmTQ9uWPwz6rEncKb2pYYYFcdHa8N17HzVyTqKfgPi4X9pMetfT3A5xCHq54R2pDNYWVLDX";
+
+fn invalid_state_proof(msg: &str) -> TrustchainSovrinError {
+    TrustchainSovrinError::InvalidStateProof(msg.to_string())
+}
+
+/// Gets the generator of the BLS signature group used by Indy pools.
+pub(crate) fn default_generator() -> Result<Generator, TrustchainSovrinError> {
+    bs58::decode(DEFAULT_GENERATOR)
+        .into_vec()
+        .ok()
+        .and_then(|bytes| Generator::from_bytes(&bytes).ok())
+        .ok_or_else(|| invalid_state_proof("Invalid BLS generator."))
+}
+
+/// Serializes a value as it is signed by Indy nodes: object entries sorted by key and written
+/// as `key:value` separated by `|`, array items separated by `,`, and booleans and null as
+/// their Python literals.
+pub(crate) fn signing_serialization(value: &Value) -> String {
+    match value {
+        Value::Null => "None".to_string(),
+        Value::Bool(true) => "True".to_string(),
+        Value::Bool(false) => "False".to_string(),
+        Value::Number(number) => number.to_string(),
+        Value::String(string) => string.to_owned(),
+        Value::Array(items) => items
+            .iter()
+            .map(signing_serialization)
+            .collect::<Vec<_>>()
+            .join(","),
+        Value::Object(map) => {
+            let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            entries
+                .into_iter()
+                .map(|(key, value)| format!("{}:{}", key, signing_serialization(value)))
+                .collect::<Vec<_>>()
+                .join("|")
+        }
+    }
+}
+
+/// Gets the message signed by the validators for a multi-signature over ledger state values.
+pub(crate) fn multi_signature_message(
+    value: &MultiSignatureValue,
+) -> Result<Vec<u8>, TrustchainSovrinError> {
+    let value = serde_json::to_value(value)
+        .map_err(|_| invalid_state_proof("Failed to encode multi-signature value."))?;
+    Ok(signing_serialization(&value).into_bytes())
+}
+
+/// The BLS verification keys of the validator nodes of a pool, keyed by node alias.
+#[derive(Clone, Debug, Default)]
+pub struct ValidatorKeys {
+    keys: HashMap<String, String>,
+}
+
+impl ValidatorKeys {
+    /// Extracts the validator BLS keys from genesis transactions (one JSON transaction per line).
+    ///
+    /// Later NODE transactions for the same node update the data of earlier ones, so nodes
+    /// that were demoted or had their BLS key rotated are handled as on the ledger.
+    pub fn from_genesis(genesis: &str) -> Result<Self, TrustchainSovrinError> {
+        let mut nodes: HashMap<String, Map<String, Value>> = HashMap::new();
+        for line in genesis.lines().filter(|line| !line.trim().is_empty()) {
+            let txn: Value = serde_json::from_str(line).map_err(|_| {
+                TrustchainSovrinError::InvalidGenesis(format!("Invalid transaction: {}", line))
+            })?;
+            let txn = &txn["txn"];
+            if txn["type"].as_str() != Some(NODE_TXN_TYPE) {
+                continue;
+            }
+            let (dest, data) = match (txn["data"]["dest"].as_str(), &txn["data"]["data"]) {
+                (Some(dest), Value::Object(data)) => (dest, data),
+                _ => {
+                    return Err(TrustchainSovrinError::InvalidGenesis(format!(
+                        "Invalid NODE transaction: {}",
+                        line
+                    )))
+                }
+            };
+            nodes
+                .entry(dest.to_string())
+                .or_default()
+                .extend(data.to_owned());
+        }
+        let keys = nodes
+            .values()
+            .filter(|node| {
+                node.get(SERVICES_KEY)
+                    .and_then(|services| services.as_array())
+                    .is_some_and(|services| services.contains(&Value::from(VALIDATOR_SERVICE)))
+            })
+            .filter_map(|node| match (node.get(ALIAS_KEY), node.get(BLS_KEY_KEY)) {
+                (Some(Value::String(alias)), Some(Value::String(blskey))) => {
+                    Some((alias.to_owned(), blskey.to_owned()))
+                }
+                _ => None,
+            })
+            .collect();
+        Ok(Self { keys })
+    }
+
+    /// Gets the number of validators.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns true if there are no validators.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Gets the maximum number of faulty validators tolerated by the pool.
+    pub fn max_faulty(&self) -> usize {
+        self.len().saturating_sub(1) / 3
+    }
+
+    /// Gets the number of validator signatures needed to trust a multi-signature.
+    pub fn quorum(&self) -> usize {
+        self.len() - self.max_faulty()
+    }
+
+    /// Verifies a BLS multi-signature over ledger state values against the validator keys,
+    /// requiring the participants to form a quorum of the pool.
+    pub fn verify_multi_signature(
+        &self,
+        multi_signature: &MultiSignature,
+    ) -> Result<(), TrustchainSovrinError> {
+        if self.is_empty() {
+            return Err(invalid_state_proof("No validator BLS keys available."));
+        }
+        let participants: HashSet<&String> = multi_signature.participants.iter().collect();
+        if participants.len() < self.quorum() {
+            return Err(TrustchainSovrinError::InvalidStateProof(format!(
+                "Multi-signature has {} participants, a quorum of {} is required.",
+                participants.len(),
+                self.quorum()
+            )));
+        }
+        let ver_keys = participants
+            .iter()
+            .map(|alias| {
+                let blskey = self.keys.get(*alias).ok_or_else(|| {
+                    TrustchainSovrinError::InvalidStateProof(format!(
+                        "Unknown multi-signature participant: {}",
+                        alias
+                    ))
+                })?;
+                bs58::decode(blskey)
+                    .into_vec()
+                    .ok()
+                    .and_then(|bytes| VerKey::from_bytes(&bytes).ok())
+                    .ok_or_else(|| {
+                        TrustchainSovrinError::InvalidStateProof(format!(
+                            "Invalid BLS key for validator: {}",
+                            alias
+                        ))
+                    })
+            })
+            .collect::<Result<Vec<VerKey>, _>>()?;
+        let signature = bs58::decode(&multi_signature.signature)
+            .into_vec()
+            .ok()
+            .and_then(|bytes| BlsMultiSignature::from_bytes(&bytes).ok())
+            .ok_or_else(|| invalid_state_proof("Invalid BLS multi-signature encoding."))?;
+        let generator = default_generator()?;
+        let message = multi_signature_message(&multi_signature.value)?;
+        let ver_key_refs: Vec<&VerKey> = ver_keys.iter().collect();
+        match Bls::verify_multi_sig(&signature, &message, &ver_key_refs, &generator) {
+            Ok(true) => Ok(()),
+            _ => Err(invalid_state_proof(
+                "BLS multi-signature verification failed.",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{TestPool, TEST_GENESIS_TRANSACTIONS, TEST_GET_NYM_REPLY};
+    use crate::state_proof::StateProof;
+
+    #[test]
+    fn test_from_genesis() {
+        let validators = ValidatorKeys::from_genesis(TEST_GENESIS_TRANSACTIONS).unwrap();
+        // NodeE is demoted by a later transaction.
+        assert_eq!(validators.len(), 4);
+        assert!(validators.keys.contains_key("NodeA"));
+        assert!(!validators.keys.contains_key("NodeE"));
+        assert_eq!(validators.max_faulty(), 1);
+        assert_eq!(validators.quorum(), 3);
+    }
+
+    #[test]
+    fn test_from_genesis_invalid() {
+        assert!(matches!(
+            ValidatorKeys::from_genesis("not a transaction"),
+            Err(TrustchainSovrinError::InvalidGenesis(_))
+        ));
+    }
+
+    #[test]
+    fn test_signing_serialization() {
+        let state_proof = StateProof::from_reply(TEST_GET_NYM_REPLY.as_bytes()).unwrap();
+        let message = multi_signature_message(&state_proof.multi_signature.value).unwrap();
+        assert_eq!(
+            String::from_utf8(message).unwrap(),
+            "ledger_id:1\
+            |pool_state_root_hash:6D2nTi4Yifzu9mygqoQThhYkec96JWf81ow6CGjxr5Nm\
+            |state_root_hash:6CtqSvxyu4hsjrqfrdy4CvzwMMvDJZameixdAJK76Hzq\
+            |timestamp:1585581460\
+            |txn_root_hash:3ak9gLeW72a24Kbbv6LPL8YfKkNB47BeTo8LJF6SDYHh"
+        );
+        let value = serde_json::json!({"b": [true, null], "a": {"d": 1, "c": "x"}});
+        assert_eq!(signing_serialization(&value), "a:c:x|d:1|b:True,None");
+    }
+
+    #[test]
+    fn test_verify_multi_signature() {
+        let pool = TestPool::new(4);
+        let validators = ValidatorKeys::from_genesis(&pool.genesis()).unwrap();
+        assert_eq!(validators.quorum(), 3);
+        let reply = pool.sign_reply(TEST_GET_NYM_REPLY, 3);
+        let state_proof = StateProof::from_reply(reply.as_bytes()).unwrap();
+        assert!(validators
+            .verify_multi_signature(&state_proof.multi_signature)
+            .is_ok());
+
+        // The signature is over the signed state values.
+        let mut multi_signature = state_proof.multi_signature;
+        multi_signature.value.timestamp += 1;
+        assert!(matches!(
+            validators.verify_multi_signature(&multi_signature),
+            Err(TrustchainSovrinError::InvalidStateProof(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_multi_signature_quorum() {
+        let validators = ValidatorKeys::from_genesis(TEST_GENESIS_TRANSACTIONS).unwrap();
+        let state_proof = StateProof::from_reply(TEST_GET_NYM_REPLY.as_bytes()).unwrap();
+        let mut multi_signature = state_proof.multi_signature;
+        multi_signature.participants = vec!["NodeA".to_string(), "NodeB".to_string()];
+        let err = validators
+            .verify_multi_signature(&multi_signature)
+            .unwrap_err();
+        assert!(err.to_string().contains("quorum"));
+
+        // Duplicated participants do not count towards the quorum.
+        multi_signature.participants.push("NodeA".to_string());
+        let err = validators
+            .verify_multi_signature(&multi_signature)
+            .unwrap_err();
+        assert!(err.to_string().contains("quorum"));
+    }
+
+    #[test]
+    fn test_verify_multi_signature_unknown_participant() {
+        let validators = ValidatorKeys::from_genesis(TEST_GENESIS_TRANSACTIONS).unwrap();
+        let state_proof = StateProof::from_reply(TEST_GET_NYM_REPLY.as_bytes()).unwrap();
+        let mut multi_signature = state_proof.multi_signature;
+        multi_signature.participants.push("NodeE".to_string());
+        let err = validators
+            .verify_multi_signature(&multi_signature)
+            .unwrap_err();
+        assert!(err.to_string().contains("NodeE"));
+    }
+
+    #[test]
+    fn test_verify_multi_signature_invalid_signature() {
+        // The fixture signature is not by the validator keys.
+        let validators = ValidatorKeys::from_genesis(TEST_GENESIS_TRANSACTIONS).unwrap();
+        let state_proof = StateProof::from_reply(TEST_GET_NYM_REPLY.as_bytes()).unwrap();
+        assert!(matches!(
+            validators.verify_multi_signature(&state_proof.multi_signature),
+            Err(TrustchainSovrinError::InvalidStateProof(_))
+        ));
+    }
+}
@@ -1,25 +1,139 @@
+//! Trustchain library for the Sovrin (Hyperledger Indy) DID method.
+pub mod anoncreds;
+pub mod attest;
+pub mod attestor;
+pub mod attrib;
+pub mod commitment;
+pub mod controller;
+pub mod create;
+pub mod data;
+pub mod endpoint;
+pub mod genesis;
+pub mod mnemonic;
+pub mod network;
 pub mod resolver;
+pub mod root;
+pub mod state_proof;
+pub mod utils;
 pub mod verifier;
 
 use thiserror::Error;
 
+/// Type for a Sovrin verifier running with a full node (direct access to the ledger pool).
 pub struct FullClient;
+/// Type for a Sovrin verifier running as a light client (verification bundles fetched from a
+/// Trustchain HTTP server).
+pub struct LightClient;
 
+/// Key of the result object in a ledger reply.
+pub const RESULT_KEY: &str = "result";
+/// Key of the (JSON-encoded) transaction data in a ledger reply result.
+pub const DATA_KEY: &str = "data";
+/// Key of the state proof in a ledger reply result.
+pub const STATE_PROOF_KEY: &str = "state_proof";
+/// Key of the target DID in NYM transaction data.
+pub const DEST_KEY: &str = "dest";
+/// Key of the verification key in NYM transaction data.
+pub const VERKEY_KEY: &str = "verkey";
+/// Key of the ledger timestamp in NYM transaction data.
+pub const TXN_TIME_KEY: &str = "txnTime";
+/// Key of the transaction sequence number in a ledger reply result.
+pub const SEQ_NO_KEY: &str = "seqNo";
+/// Key of the version ID (the transaction sequence number) in DID document metadata.
+pub const VERSION_ID_KEY: &str = "versionId";
+/// Key of the attribute name in a GET_ATTRIB reply result.
+pub const RAW_KEY: &str = "raw";
+/// Key of the schema name in GET_SCHEMA reply data.
+pub const NAME_KEY: &str = "name";
+/// Key of the schema version in GET_SCHEMA reply data.
+pub const VERSION_KEY: &str = "version";
+/// Key of the schema attribute names in GET_SCHEMA reply data.
+pub const ATTR_NAMES_KEY: &str = "attr_names";
+/// Key of the issuer DID in a GET_CLAIM_DEF reply result.
+pub const ORIGIN_KEY: &str = "origin";
+/// Key of the schema sequence number in a GET_CLAIM_DEF reply result.
+pub const REF_KEY: &str = "ref";
+/// Key of the signature type in a GET_CLAIM_DEF reply result.
+pub const SIGNATURE_TYPE_KEY: &str = "signature_type";
+/// Key of the tag in a GET_CLAIM_DEF reply result.
+pub const TAG_KEY: &str = "tag";
+
+// BIP32
+pub const LEDGER_KEY_DERIVATION_PATH: &str = "m/0h";
+
+/// An error relating to the Sovrin DID method.
 #[derive(Error, Debug)]
 pub enum TrustchainSovrinError {
+    /// Failed to build the ledger request.
     #[error("Could not build the request to did: {0}")]
     FailedToBuildRequest(String),
-
+    /// Failed to send the ledger request.
     #[error("Error while sending the query to ledger: {0}")]
     LedgerQuery(String),
-
+    /// The ledger request timed out.
+    #[error("Ledger request timed out after {0} seconds")]
+    Timeout(u64),
+    /// Failed to parse the ledger reply.
     #[error("Could not parse ledger answer")]
     InvalidLedgerAnswer,
-
+    /// The ledger rejected the request.
     #[error("The ledger answered the query with Failure: {0}")]
     QueryFailed(String),
-
+    /// Failed to convert Indy resolution output into Trustchain types.
     #[error("Conversion error between Indy and Trustchain formats")]
     CouldNotConvert,
-
-}
\ No newline at end of file
+    /// The ledger reply has no state proof.
+    #[error("Ledger reply contains no state proof.")]
+    MissingStateProof,
+    /// A transaction expected on the ledger is missing.
+    #[error("Missing ledger transaction with sequence number: {0}")]
+    MissingTransaction(u64),
+    /// Invalid Sovrin DID.
+    #[error("Invalid Sovrin DID: {0}")]
+    InvalidDID(String),
+    /// Unknown Sovrin network.
+    #[error("Unknown Sovrin network: {0}")]
+    UnknownNetwork(String),
+    /// No genesis transactions were configured for the network.
+    #[error("Sovrin network not configured: {0}")]
+    NetworkNotConfigured(String),
+    /// Invalid genesis transactions.
+    #[error("Invalid genesis transactions: {0}")]
+    InvalidGenesis(String),
+    /// The state proof in the ledger reply is invalid.
+    #[error("Invalid state proof: {0}")]
+    InvalidStateProof(String),
+    /// The key is not an Ed25519 key, as used for Sovrin DIDs.
+    #[error("Invalid Ed25519 key: {0}")]
+    InvalidKey(String),
+    /// The key does not match the verification key of the DID on the ledger.
+    #[error("Key does not match the ledger verification key of DID: {0}")]
+    KeyMismatch(String),
+    /// Invalid mnemonic or failed key derivation from a mnemonic.
+    #[error("Invalid mnemonic: {0}")]
+    InvalidMnemonic(String),
+    /// Invalid `versionId` or `versionTime` in the resolution input metadata.
+    #[error("Invalid DID version: {0}")]
+    InvalidVersion(String),
+    /// Failed to sign a ledger request.
+    #[error("Failed to sign ledger request: {0}")]
+    SigningError(String),
+    /// Failed to fetch a verification bundle from a Trustchain HTTP server.
+    #[error("Failed to fetch verification bundle: {0}")]
+    BundleRequest(String),
+    /// The Trustchain controller proof ATTRIB is invalid.
+    #[error("Invalid Trustchain controller proof: {0}")]
+    InvalidControllerProof(String),
+    /// The service endpoint ATTRIB is invalid.
+    #[error("Invalid service endpoint: {0}")]
+    InvalidEndpoint(String),
+    /// Invalid AnonCreds schema identifier or ledger object.
+    #[error("Invalid AnonCreds schema: {0}")]
+    InvalidSchema(String),
+    /// Invalid AnonCreds credential definition identifier or ledger object.
+    #[error("Invalid AnonCreds credential definition: {0}")]
+    InvalidCredentialDefinition(String),
+    /// State proof signed by the validators longer ago than the maximum age (in seconds).
+    #[error("Stale state proof signed at {0}, older than the maximum age of {1} seconds.")]
+    StaleStateProof(u64, u64),
+}
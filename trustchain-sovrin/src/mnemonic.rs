@@ -0,0 +1,80 @@
+//! Sovrin ledger key generation from a mnemonic.
+use bip39::Mnemonic;
+use ed25519_dalek_bip32::derivation_path::DerivationPath;
+use ed25519_dalek_bip32::ExtendedSigningKey;
+use ssi::jwk::{Base64urlUInt, OctetParams, Params, JWK};
+use std::str::FromStr;
+
+use crate::{TrustchainSovrinError, LEDGER_KEY_DERIVATION_PATH};
+
+/// Generates the Ed25519 ledger key of a Sovrin DID from a mnemonic and child index.
+pub fn generate_ledger_key(
+    mnemonic: &Mnemonic,
+    index: Option<u32>,
+) -> Result<JWK, TrustchainSovrinError> {
+    let seed = mnemonic.to_seed("");
+    let extended_secret_key = ExtendedSigningKey::from_seed(&seed)
+        .map_err(|e| TrustchainSovrinError::InvalidMnemonic(e.to_string()))?;
+    let private_key = extended_secret_key
+        .derive(&derivation_path(LEDGER_KEY_DERIVATION_PATH, index)?)
+        .map_err(|e| TrustchainSovrinError::InvalidMnemonic(e.to_string()))?;
+    let public_key = private_key.verifying_key().to_bytes();
+    Ok(JWK::from(Params::OKP(OctetParams {
+        curve: "Ed25519".to_string(),
+        public_key: Base64urlUInt(public_key.to_vec()),
+        private_key: Some(Base64urlUInt(private_key.signing_key.to_bytes().to_vec())),
+    })))
+}
+
+/// Generates a hardened ed25519_dalek_bip32 derivation path.
+fn derivation_path(
+    path: &str,
+    index: Option<u32>,
+) -> Result<DerivationPath, TrustchainSovrinError> {
+    let index = index.unwrap_or(0);
+    // Handle case index > 2^31 - 1.
+    if index > 2u32.pow(31) - 1 {
+        return Err(TrustchainSovrinError::InvalidMnemonic(format!(
+            "Invalid child index: {}",
+            index
+        )));
+    }
+    DerivationPath::from_str(&format!("{}/{index}'", path.replace('h', "'")))
+        .map_err(|e| TrustchainSovrinError::InvalidMnemonic(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_test_mnemonic() -> Mnemonic {
+        Mnemonic::parse(
+            "state draft moral repeat knife trend animal pretty delay collect fall adjust",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_derivation_path() {
+        let expected = DerivationPath::from_str("m/0'/0'").unwrap();
+        assert_eq!(expected, derivation_path("m/0h", None).unwrap());
+        let expected = DerivationPath::from_str("m/0'/3'").unwrap();
+        assert_eq!(expected, derivation_path("m/0h", Some(3)).unwrap());
+        assert!(derivation_path("m/0h", Some(2u32.pow(31))).is_err());
+    }
+
+    #[test]
+    fn test_generate_ledger_key() -> Result<(), Box<dyn std::error::Error>> {
+        let result = generate_ledger_key(&get_test_mnemonic(), Some(0))?;
+        let expected = r#"
+        {
+            "kty": "OKP",
+            "crv": "Ed25519",
+            "x": "jil0ZZqW_cldlxq2a0Ezw59IgEIULSj9E3NOD6YQCHo",
+            "d": "wHwSUdy4a00qTxAhnuOHeWpai4ERjdZGslaou-Lig5g"
+        }"#;
+        assert_eq!(result, serde_json::from_str::<JWK>(expected)?);
+        assert_ne!(result, generate_ledger_key(&get_test_mnemonic(), Some(1))?);
+        Ok(())
+    }
+}
@@ -0,0 +1,231 @@
+//! Named Sovrin networks and their DID prefixes.
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::TrustchainSovrinError;
+
+/// DID methods resolved on Sovrin networks.
+pub const SOVRIN_METHODS: [&str; 2] = ["indy", "sov"];
+
+const DID_INDY_PREFIX: &str = "did:indy:";
+const DID_SOV_PREFIX: &str = "did:sov:";
+
+/// Returns true if the DID is a `did:indy` or `did:sov` DID.
+pub fn is_sovrin_did(did: &str) -> bool {
+    did.starts_with(DID_INDY_PREFIX) || did.starts_with(DID_SOV_PREFIX)
+}
+
+/// Normalises a Sovrin DID to its fully qualified `did:indy` form, the form of the `id` of its
+/// resolved DID Document. DIDs of other methods are returned unchanged.
+pub fn normalise_did(did: &str) -> Result<String, TrustchainSovrinError> {
+    if !is_sovrin_did(did) {
+        return Ok(did.to_string());
+    }
+    let (network, id) = SovrinNetwork::from_did(did)?;
+    Ok(network.did_indy(id))
+}
+
+/// A Sovrin network, each with its own pool of validators.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SovrinNetwork {
+    MainNet,
+    StagingNet,
+    BuilderNet,
+}
+
+impl SovrinNetwork {
+    /// All known networks.
+    pub const ALL: [SovrinNetwork; 3] = [
+        SovrinNetwork::MainNet,
+        SovrinNetwork::StagingNet,
+        SovrinNetwork::BuilderNet,
+    ];
+
+    /// Gets the `did:indy` namespace of the network.
+    pub fn namespace(&self) -> &'static str {
+        match self {
+            SovrinNetwork::MainNet => "sovrin",
+            SovrinNetwork::StagingNet => "sovrin:staging",
+            SovrinNetwork::BuilderNet => "sovrin:builder",
+        }
+    }
+
+    /// Gets the `did:sov` network name, which is omitted for MainNet DIDs.
+    pub fn sov_network(&self) -> Option<&'static str> {
+        match self {
+            SovrinNetwork::MainNet => None,
+            SovrinNetwork::StagingNet => Some("staging"),
+            SovrinNetwork::BuilderNet => Some("builder"),
+        }
+    }
+
+    /// Gets the network of a DID, together with the unqualified DID (the NYM identifier).
+    ///
+    /// Accepts `did:indy:<namespace>:<id>` and `did:sov[:<network>]:<id>` DIDs. Unqualified
+    /// identifiers are taken to be on MainNet.
+    pub fn from_did(did: &str) -> Result<(Self, &str), TrustchainSovrinError> {
+        let (network, id) = if let Some(rest) = did.strip_prefix(DID_INDY_PREFIX) {
+            let (namespace, id) = rest
+                .rsplit_once(':')
+                .ok_or_else(|| TrustchainSovrinError::InvalidDID(did.to_string()))?;
+            let network = Self::ALL
+                .into_iter()
+                .find(|network| network.namespace() == namespace)
+                .ok_or_else(|| TrustchainSovrinError::UnknownNetwork(namespace.to_string()))?;
+            (network, id)
+        } else if let Some(rest) = did.strip_prefix(DID_SOV_PREFIX) {
+            match rest.split_once(':') {
+                Some((sov_network, id)) => {
+                    let network = Self::ALL
+                        .into_iter()
+                        .find(|network| network.sov_network() == Some(sov_network))
+                        .ok_or_else(|| {
+                            TrustchainSovrinError::UnknownNetwork(sov_network.to_string())
+                        })?;
+                    (network, id)
+                }
+                None => (SovrinNetwork::MainNet, rest),
+            }
+        } else if did.contains(':') {
+            return Err(TrustchainSovrinError::InvalidDID(did.to_string()));
+        } else {
+            (SovrinNetwork::MainNet, did)
+        };
+        if id.is_empty() || id.contains(':') {
+            return Err(TrustchainSovrinError::InvalidDID(did.to_string()));
+        }
+        Ok((network, id))
+    }
+
+    /// Gets the fully qualified `did:indy` DID for an identifier on this network.
+    pub fn did_indy(&self, id: &str) -> String {
+        format!("{}{}:{}", DID_INDY_PREFIX, self.namespace(), id)
+    }
+}
+
+impl fmt::Display for SovrinNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SovrinNetwork::MainNet => "MainNet",
+            SovrinNetwork::StagingNet => "StagingNet",
+            SovrinNetwork::BuilderNet => "BuilderNet",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for SovrinNetwork {
+    type Err = TrustchainSovrinError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|network| network.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| TrustchainSovrinError::UnknownNetwork(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_did() {
+        let id = "BF4pE6ugcqNWt5kpJUE1Ro";
+        let cases = [
+            (
+                "did:indy:sovrin:BF4pE6ugcqNWt5kpJUE1Ro",
+                SovrinNetwork::MainNet,
+            ),
+            (
+                "did:indy:sovrin:staging:BF4pE6ugcqNWt5kpJUE1Ro",
+                SovrinNetwork::StagingNet,
+            ),
+            (
+                "did:indy:sovrin:builder:BF4pE6ugcqNWt5kpJUE1Ro",
+                SovrinNetwork::BuilderNet,
+            ),
+            ("did:sov:BF4pE6ugcqNWt5kpJUE1Ro", SovrinNetwork::MainNet),
+            (
+                "did:sov:staging:BF4pE6ugcqNWt5kpJUE1Ro",
+                SovrinNetwork::StagingNet,
+            ),
+            (
+                "did:sov:builder:BF4pE6ugcqNWt5kpJUE1Ro",
+                SovrinNetwork::BuilderNet,
+            ),
+            ("BF4pE6ugcqNWt5kpJUE1Ro", SovrinNetwork::MainNet),
+        ];
+        for (did, expected) in cases {
+            assert_eq!(SovrinNetwork::from_did(did).unwrap(), (expected, id));
+        }
+    }
+
+    #[test]
+    fn test_from_did_invalid() {
+        assert!(matches!(
+            SovrinNetwork::from_did("did:indy:idunion:BF4pE6ugcqNWt5kpJUE1Ro"),
+            Err(TrustchainSovrinError::UnknownNetwork(_))
+        ));
+        assert!(matches!(
+            SovrinNetwork::from_did("did:sov:test:BF4pE6ugcqNWt5kpJUE1Ro"),
+            Err(TrustchainSovrinError::UnknownNetwork(_))
+        ));
+        assert!(matches!(
+            SovrinNetwork::from_did("did:ion:test:EiCClfEdkTv_aM3UnBBhlOV89LlGhpQAbfeZLFdFxVFkEg"),
+            Err(TrustchainSovrinError::InvalidDID(_))
+        ));
+        assert!(matches!(
+            SovrinNetwork::from_did("did:sov:"),
+            Err(TrustchainSovrinError::InvalidDID(_))
+        ));
+    }
+
+    #[test]
+    fn test_normalise_did() {
+        let id = "BF4pE6ugcqNWt5kpJUE1Ro";
+        let cases = [
+            (format!("did:sov:{id}"), format!("did:indy:sovrin:{id}")),
+            (
+                format!("did:sov:staging:{id}"),
+                format!("did:indy:sovrin:staging:{id}"),
+            ),
+            (
+                format!("did:indy:sovrin:{id}"),
+                format!("did:indy:sovrin:{id}"),
+            ),
+            (
+                "did:ion:test:EiCClfEdkTv_aM3UnBBhlOV89LlGhpQAbfeZLFdFxVFkEg".to_string(),
+                "did:ion:test:EiCClfEdkTv_aM3UnBBhlOV89LlGhpQAbfeZLFdFxVFkEg".to_string(),
+            ),
+        ];
+        for (did, expected) in cases {
+            assert_eq!(normalise_did(&did).unwrap(), expected);
+        }
+        assert!(normalise_did("did:sov:test:BF4pE6ugcqNWt5kpJUE1Ro").is_err());
+    }
+
+    #[test]
+    fn test_is_sovrin_did() {
+        assert!(is_sovrin_did("did:indy:sovrin:BF4pE6ugcqNWt5kpJUE1Ro"));
+        assert!(is_sovrin_did("did:sov:staging:BF4pE6ugcqNWt5kpJUE1Ro"));
+        assert!(!is_sovrin_did("BF4pE6ugcqNWt5kpJUE1Ro"));
+        assert!(!is_sovrin_did(
+            "did:ion:test:EiCClfEdkTv_aM3UnBBhlOV89LlGhpQAbfeZLFdFxVFkEg"
+        ));
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(
+            SovrinNetwork::from_str("stagingnet").unwrap(),
+            SovrinNetwork::StagingNet
+        );
+        assert_eq!(
+            SovrinNetwork::from_str(&SovrinNetwork::BuilderNet.to_string()).unwrap(),
+            SovrinNetwork::BuilderNet
+        );
+        assert!(SovrinNetwork::from_str("testnet").is_err());
+    }
+}
@@ -1,105 +1,973 @@
+//! Implementation of `TrustchainResolver` API for the Sovrin DID method.
 use async_trait::async_trait;
-use ssi::did_resolve::{DIDResolver, DocumentMetadata, ResolutionInputMetadata, ResolutionMetadata};
-use futures_executor::block_on;
+use chrono::{DateTime, TimeZone, Utc};
 use indy_vdr::config::PoolConfig;
-use indy_vdr::pool::{SharedPool, Pool, PoolBuilder, PoolTransactions, RequestResult};
+use indy_vdr::ledger::identifiers::{CredentialDefinitionId, SchemaId};
+use indy_vdr::ledger::RequestBuilder;
 use indy_vdr::pool::helpers::perform_ledger_request;
+use indy_vdr::pool::{
+    Pool, PoolBuilder, PoolTransactions, PreparedRequest, RequestResult, SharedPool,
+};
 use indy_vdr::resolver::did_document::DidDocument;
-use indy_vdr::resolver::types::DidDocumentMetadata;
-use indy_vdr::resolver::types::Result as ResolverResult;
 use indy_vdr::resolver::types::Metadata;
+use indy_vdr::resolver::types::Result as ResolverResult;
 use indy_vdr::resolver::utils::handle_internal_resolution_result;
 use indy_vdr::utils::did::DidValue;
-use serde_json;
-use ssi::did::Document;
-use trustchain_core::resolver::{ TrustchainResolver};
-use crate::TrustchainSovrinError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use ssi::did::{Document, VerificationMethod};
+use ssi::did_resolve::{
+    DIDResolver, DocumentMetadata, ResolutionInputMetadata, ResolutionMetadata,
+};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+use trustchain_core::resolver::{generic_resolver, TrustchainResolver};
 
-pub struct Transaction;
+use crate::anoncreds::AnonCredsObjectSource;
+use crate::attrib::{add_controller_proof, ControllerProof, TRUSTCHAIN_ATTRIB_NAME};
+use crate::endpoint::{add_endpoint_services, SovrinEndpoint, ENDPOINT_ATTRIB_NAME};
+use crate::genesis::ValidatorKeys;
+use crate::network::SovrinNetwork;
+use crate::root::{check_txn_reply, LedgerTxn, LedgerTxnSource, DOMAIN_LEDGER_ID};
+use crate::state_proof::{
+    verify_attrib_reply, verify_cred_def_reply, verify_nym_reply, verify_schema_reply,
+};
+use crate::utils::{reply_result, verkey_jwk};
+use crate::{TrustchainSovrinError, SEQ_NO_KEY, TXN_TIME_KEY, VERSION_ID_KEY};
 
-pub struct SovrinResolver {
-    pool: SharedPool
+/// Type of the verification method of the ledger key in resolved Sovrin DID Documents.
+const ED25519_VERIFICATION_KEY_TYPE: &str = "Ed25519VerificationKey2018";
+
+/// Settings for the ledger requests sent by a `SovrinDIDResolver`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RequestConfig {
+    /// Timeout in seconds for a single attempt of a ledger request.
+    pub timeout: u64,
+    /// Number of times a request is retried after a timeout, a transport error or a reply
+    /// without a valid state proof.
+    pub retries: u32,
+    /// Number of nodes a read request is sent to at once. Replies with a valid state proof
+    /// need no further consensus, so more nodes only make a reply arrive sooner.
+    pub read_nodes: usize,
 }
 
-impl SovrinResolver {
-    pub fn new() -> Self {
-        // Load genesis transactions. The corresponding transactions for the ledger you
-        // are connecting to should be saved to a local file.
-        // TODO Put the root transaction path in a config file
-        let txns = PoolTransactions::from_json_file("./mainNet.txn").unwrap();
-        // Create a PoolBuilder instance
-        let pool_builder = PoolBuilder::new(PoolConfig::default(), txns);
-        // Convert into a thread-local Pool instance
-        let pool = pool_builder.into_shared().unwrap();
+impl Default for RequestConfig {
+    fn default() -> Self {
+        Self {
+            timeout: 20,
+            retries: 2,
+            read_nodes: 2,
+        }
+    }
+}
+
+impl RequestConfig {
+    /// Gets the per-attempt request timeout.
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout)
+    }
+
+    /// Gets the indy-vdr pool configuration for these settings.
+    fn pool_config(&self) -> PoolConfig {
+        PoolConfig {
+            reply_timeout: self.timeout as i64,
+            request_read_nodes: self.read_nodes,
+            ..PoolConfig::default()
+        }
+    }
+}
+
+/// A connection to the validator pool of a Sovrin network.
+struct SovrinPool {
+    pool: SharedPool,
+    validators: ValidatorKeys,
+}
+
+impl SovrinPool {
+    /// Connects to a pool from its genesis transactions.
+    fn from_genesis(genesis: &str, config: &RequestConfig) -> Result<Self, TrustchainSovrinError> {
+        let txns = PoolTransactions::from_json(genesis)
+            .map_err(|e| TrustchainSovrinError::InvalidGenesis(e.to_string()))?;
+        let validators = ValidatorKeys::from_genesis(genesis)?;
+        let pool = PoolBuilder::new(config.pool_config(), txns)
+            .into_shared()
+            .map_err(|e| TrustchainSovrinError::InvalidGenesis(e.to_string()))?;
+        Ok(Self { pool, validators })
+    }
+}
+
+/// A version of the ledger state of a Sovrin DID, given by the resolution input metadata.
+///
+/// A `versionId` is the sequence number of a ledger transaction and a `versionTime` is an
+/// RFC 3339 datetime: the DID (and its controller proof) is resolved as it was on the ledger
+/// right after that transaction, or at that time. With neither, the latest state is resolved.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NymVersion {
+    /// Ledger sequence number, from `versionId`.
+    pub seq_no: Option<i32>,
+    /// Unix timestamp, from `versionTime`.
+    pub timestamp: Option<u64>,
+}
 
+impl NymVersion {
+    /// Gets the version requested in the resolution input metadata.
+    pub fn from_input_metadata(
+        input_metadata: &ResolutionInputMetadata,
+    ) -> Result<Self, TrustchainSovrinError> {
+        let seq_no = match &input_metadata.version_id {
+            Some(version_id) => match version_id.parse::<i32>() {
+                Ok(seq_no) if seq_no > 0 => Some(seq_no),
+                _ => {
+                    return Err(TrustchainSovrinError::InvalidVersion(
+                        version_id.to_string(),
+                    ))
+                }
+            },
+            None => None,
+        };
+        let timestamp = match &input_metadata.version_time {
+            Some(version_time) => match DateTime::parse_from_rfc3339(version_time)
+                .map(|time| u64::try_from(time.timestamp()))
+            {
+                Ok(Ok(timestamp)) => Some(timestamp),
+                _ => {
+                    return Err(TrustchainSovrinError::InvalidVersion(
+                        version_time.to_string(),
+                    ))
+                }
+            },
+            None => None,
+        };
+        Ok(Self { seq_no, timestamp })
+    }
+}
+
+/// Returns true if a failed request may succeed when sent again.
+fn is_retryable(err: &TrustchainSovrinError) -> bool {
+    matches!(
+        err,
+        TrustchainSovrinError::LedgerQuery(_)
+            | TrustchainSovrinError::Timeout(_)
+            | TrustchainSovrinError::MissingStateProof
+            | TrustchainSovrinError::InvalidStateProof(_)
+    )
+}
+
+/// A resolver for Sovrin DIDs, querying the pools of one or more Sovrin networks.
+///
+/// The network of a DID is selected by its `did:indy:<namespace>:` or `did:sov:<network>:`
+/// prefix. Ledger replies are only accepted with a state proof signed by a quorum of the
+/// validators listed in the genesis transactions, so no single node needs to be trusted.
+///
+/// Resolved DID Documents always have the fully qualified `did:indy:<namespace>:<id>` DID as
+/// their `id`, including for DIDs requested in their `did:sov` form. Controllers written by
+/// Trustchain attestations are normalised to the same form (see `normalise_did`), so chains link
+/// the `id`s of resolved documents.
+pub struct SovrinDIDResolver {
+    pools: HashMap<SovrinNetwork, SovrinPool>,
+    config: RequestConfig,
+}
+
+impl Default for SovrinDIDResolver {
+    fn default() -> Self {
+        Self::new(RequestConfig::default())
+    }
+}
+
+impl SovrinDIDResolver {
+    /// Constructs a resolver with no networks, sending ledger requests with the given settings.
+    pub fn new(config: RequestConfig) -> Self {
         Self {
-            pool
+            pools: HashMap::new(),
+            config,
         }
     }
 
-    pub fn fetch_did(&self, did: &str) -> Result<(DidDocument, DidDocumentMetadata), TrustchainSovrinError> {
+    /// Adds (or replaces) a network, given its genesis transactions.
+    pub fn with_network(
+        mut self,
+        network: SovrinNetwork,
+        genesis: &str,
+    ) -> Result<Self, TrustchainSovrinError> {
+        self.pools
+            .insert(network, SovrinPool::from_genesis(genesis, &self.config)?);
+        Ok(self)
+    }
+
+    /// Adds (or replaces) a network, given the path to its genesis transactions file.
+    pub fn with_network_file<P: AsRef<Path>>(
+        self,
+        network: SovrinNetwork,
+        path: P,
+    ) -> Result<Self, TrustchainSovrinError> {
+        let genesis = std::fs::read_to_string(path.as_ref()).map_err(|e| {
+            TrustchainSovrinError::InvalidGenesis(format!(
+                "Failed to read {}: {}",
+                path.as_ref().display(),
+                e
+            ))
+        })?;
+        self.with_network(network, &genesis)
+    }
+
+    /// Gets the networks this resolver is connected to.
+    pub fn networks(&self) -> Vec<SovrinNetwork> {
+        SovrinNetwork::ALL
+            .into_iter()
+            .filter(|network| self.pools.contains_key(network))
+            .collect()
+    }
+
+    fn pool(&self, network: SovrinNetwork) -> Result<&SovrinPool, TrustchainSovrinError> {
+        self.pools
+            .get(&network)
+            .ok_or_else(|| TrustchainSovrinError::NetworkNotConfigured(network.to_string()))
+    }
+
+    /// Gets the BLS keys of the validators of the given network.
+    pub fn validators(
+        &self,
+        network: SovrinNetwork,
+    ) -> Result<&ValidatorKeys, TrustchainSovrinError> {
+        Ok(&self.pool(network)?.validators)
+    }
+
+    /// Gets the ledger request settings.
+    pub fn config(&self) -> &RequestConfig {
+        &self.config
+    }
+
+    /// Gets a request builder for the pool of the given DID's network.
+    pub fn request_builder(&self, did: &str) -> Result<RequestBuilder, TrustchainSovrinError> {
+        let (network, _) = SovrinNetwork::from_did(did)?;
+        Ok(self.pool(network)?.pool.get_request_builder())
+    }
+
+    /// Submits a signed write request for the given DID to the pool of its network and
+    /// returns the raw ledger reply.
+    ///
+    /// Write requests are not retried, as a timed out write may still have been ordered.
+    pub async fn submit_write(
+        &self,
+        did: &str,
+        request: &PreparedRequest,
+    ) -> Result<String, TrustchainSovrinError> {
+        let (network, _) = SovrinNetwork::from_did(did)?;
+        self.submit_request(self.pool(network)?, request).await
+    }
+
+    /// Sends a request to the pool, waiting at most the configured timeout for a reply.
+    async fn submit_request(
+        &self,
+        pool: &SovrinPool,
+        request: &PreparedRequest,
+    ) -> Result<String, TrustchainSovrinError> {
+        let (ledger_answer, _time) = tokio::time::timeout(
+            self.config.timeout(),
+            perform_ledger_request(&pool.pool, request, None),
+        )
+        .await
+        .map_err(|_| TrustchainSovrinError::Timeout(self.config.timeout))?
+        .map_err(|e| TrustchainSovrinError::LedgerQuery(e.to_string()))?;
+
+        match ledger_answer {
+            RequestResult::Reply(reply) => Ok(reply),
+            RequestResult::Failed(err) => Err(TrustchainSovrinError::QueryFailed(err.to_string())),
+        }
+    }
+
+    /// Sends a read request to the pool and verifies the reply with the given function.
+    ///
+    /// Failed attempts are retried up to the configured number of times, unless the ledger
+    /// itself rejected the request.
+    async fn submit_verified_read(
+        &self,
+        pool: &SovrinPool,
+        request: &PreparedRequest,
+        verify: fn(&[u8], &ValidatorKeys) -> Result<String, TrustchainSovrinError>,
+    ) -> Result<String, TrustchainSovrinError> {
+        let mut attempt = 0;
+        loop {
+            let result = self.submit_request(pool, request).await.and_then(|reply| {
+                verify(reply.as_bytes(), &pool.validators)?;
+                Ok(reply)
+            });
+            match result {
+                Err(err) if is_retryable(&err) && attempt < self.config.retries => attempt += 1,
+                result => return result,
+            }
+        }
+    }
+
+    /// Sends a GET_NYM request for the given DID and returns the raw ledger reply, after
+    /// verifying its state proof.
+    pub async fn fetch_nym(&self, did: &str) -> Result<String, TrustchainSovrinError> {
+        self.fetch_nym_version(did, NymVersion::default()).await
+    }
+
+    /// Sends a GET_NYM request for a version of the given DID and returns the raw ledger
+    /// reply, after verifying its state proof.
+    pub async fn fetch_nym_version(
+        &self,
+        did: &str,
+        version: NymVersion,
+    ) -> Result<String, TrustchainSovrinError> {
+        let (network, id) = SovrinNetwork::from_did(did)?;
+        let pool = self.pool(network)?;
+
         // Create a GET_NYM request
-        let request_builder = &self.pool.get_request_builder();
-        let target_did = DidValue::new(did, None);
-        let request = request_builder.build_get_nym_request(None, &target_did, None, None)
+        let request_builder = pool.pool.get_request_builder();
+        let target_did = DidValue::new(id, None);
+        let request = request_builder
+            .build_get_nym_request(None, &target_did, version.seq_no, version.timestamp)
             .map_err(|_| TrustchainSovrinError::FailedToBuildRequest(did.into()))?;
 
         // Run the request
-        let (ledger_answer, _time) = block_on(perform_ledger_request(&self.pool, &request, None))
-            .map_err(|e| TrustchainSovrinError::LedgerQuery(e.to_string()))?;
-
-        // Parse the result
-        let (res, meta) = match ledger_answer.map_result(|txn|
-            handle_internal_resolution_result("sovrin", txn.as_str())
-        ).map_err(|_| TrustchainSovrinError::InvalidLedgerAnswer)? {
-            RequestResult::Reply((res, meta)) => Ok((res, meta)),
-            RequestResult::Failed(err) => Err(TrustchainSovrinError::QueryFailed(err.to_string()))
-        }?;
-
-        // Check that we got a DID and not another type of document. Return the DID and Metadata
-        match (res, meta){
-            (ResolverResult::DidDocument(doc), Metadata::DidDocumentMetadata(meta)) => Ok((doc, meta)),
-            (_, _) =>  Err(TrustchainSovrinError::InvalidLedgerAnswer),
-        }
+        self.submit_verified_read(pool, &request, verify_nym_reply)
+            .await
+    }
+
+    /// Sends a GET_ATTRIB request for the raw attribute `name` of the given DID and returns
+    /// the raw ledger reply, after verifying its state proof.
+    ///
+    /// The state proof also covers a missing attribute, so its absence can be trusted.
+    pub async fn fetch_attrib(
+        &self,
+        did: &str,
+        name: &str,
+    ) -> Result<String, TrustchainSovrinError> {
+        self.fetch_attrib_version(did, name, NymVersion::default())
+            .await
+    }
+
+    /// Sends a GET_ATTRIB request for the raw attribute `name` of a version of the given DID
+    /// and returns the raw ledger reply, after verifying its state proof.
+    pub async fn fetch_attrib_version(
+        &self,
+        did: &str,
+        name: &str,
+        version: NymVersion,
+    ) -> Result<String, TrustchainSovrinError> {
+        let (network, id) = SovrinNetwork::from_did(did)?;
+        let pool = self.pool(network)?;
+
+        // Create a GET_ATTRIB request
+        let request_builder = pool.pool.get_request_builder();
+        let target_did = DidValue::new(id, None);
+        let request = request_builder
+            .build_get_attrib_request(
+                None,
+                &target_did,
+                Some(name.to_string()),
+                None,
+                None,
+                version.seq_no,
+                version.timestamp,
+            )
+            .map_err(|_| TrustchainSovrinError::FailedToBuildRequest(did.into()))?;
+
+        // Run the request
+        self.submit_verified_read(pool, &request, verify_attrib_reply)
+            .await
+    }
+
+    /// Fetches the Trustchain controller proof of the given DID, if one is on the ledger.
+    pub async fn fetch_controller_proof(
+        &self,
+        did: &str,
+    ) -> Result<Option<ControllerProof>, TrustchainSovrinError> {
+        self.fetch_controller_proof_version(did, NymVersion::default())
+            .await
+    }
+
+    /// Fetches the Trustchain controller proof of a version of the given DID, if one is on
+    /// the ledger.
+    pub async fn fetch_controller_proof_version(
+        &self,
+        did: &str,
+        version: NymVersion,
+    ) -> Result<Option<ControllerProof>, TrustchainSovrinError> {
+        let reply = self
+            .fetch_attrib_version(did, TRUSTCHAIN_ATTRIB_NAME, version)
+            .await?;
+        ControllerProof::from_attrib_reply(reply.as_bytes())
+    }
+
+    /// Sends a GET_SCHEMA request for the AnonCreds schema with the given legacy identifier on
+    /// a network and returns the raw ledger reply, after verifying its state proof.
+    pub async fn fetch_schema(
+        &self,
+        network: SovrinNetwork,
+        schema_id: &str,
+    ) -> Result<String, TrustchainSovrinError> {
+        let pool = self.pool(network)?;
+
+        // Create a GET_SCHEMA request
+        let request_builder = pool.pool.get_request_builder();
+        let request = request_builder
+            .build_get_schema_request(None, &SchemaId(schema_id.to_string()))
+            .map_err(|_| TrustchainSovrinError::FailedToBuildRequest(schema_id.into()))?;
+
+        // Run the request
+        self.submit_verified_read(pool, &request, verify_schema_reply)
+            .await
+    }
+
+    /// Sends a GET_CLAIM_DEF request for the AnonCreds credential definition with the given
+    /// legacy identifier on a network and returns the raw ledger reply, after verifying its
+    /// state proof.
+    pub async fn fetch_cred_def(
+        &self,
+        network: SovrinNetwork,
+        cred_def_id: &str,
+    ) -> Result<String, TrustchainSovrinError> {
+        let pool = self.pool(network)?;
+
+        // Create a GET_CLAIM_DEF request
+        let request_builder = pool.pool.get_request_builder();
+        let request = request_builder
+            .build_get_cred_def_request(None, &CredentialDefinitionId(cred_def_id.to_string()))
+            .map_err(|_| TrustchainSovrinError::FailedToBuildRequest(cred_def_id.into()))?;
+
+        // Run the request
+        self.submit_verified_read(pool, &request, verify_cred_def_reply)
+            .await
+    }
+
+    /// Sends a GET_TXN request for the domain ledger transaction with the given sequence
+    /// number on a network, returning `None` if the ledger has no such transaction.
+    ///
+    /// GET_TXN replies carry no state proof, so the transaction is not verified.
+    pub async fn fetch_txn(
+        &self,
+        network: SovrinNetwork,
+        seq_no: u64,
+    ) -> Result<Option<LedgerTxn>, TrustchainSovrinError> {
+        let pool = self.pool(network)?;
+
+        // Create a GET_TXN request
+        let request_builder = pool.pool.get_request_builder();
+        let request = i32::try_from(seq_no)
+            .ok()
+            .and_then(|seq_no| {
+                request_builder
+                    .build_get_txn_request(None, DOMAIN_LEDGER_ID, seq_no)
+                    .ok()
+            })
+            .ok_or_else(|| {
+                TrustchainSovrinError::FailedToBuildRequest(format!(
+                    "GET_TXN {} on {}",
+                    seq_no, network
+                ))
+            })?;
+
+        // Run the request
+        let reply = self
+            .submit_verified_read(pool, &request, |reply, _| check_txn_reply(reply))
+            .await?;
+        LedgerTxn::from_txn_reply(reply.as_bytes())
+    }
+
+    /// Resolves the given DID to its DID Document and Document Metadata, including the
+    /// services of its service endpoint.
+    pub async fn fetch_did(
+        &self,
+        did: &str,
+    ) -> Result<(Document, DocumentMetadata), TrustchainSovrinError> {
+        self.fetch_did_version(did, NymVersion::default()).await
+    }
+
+    /// Resolves a version of the given DID to its DID Document and Document Metadata.
+    pub async fn fetch_did_version(
+        &self,
+        did: &str,
+        version: NymVersion,
+    ) -> Result<(Document, DocumentMetadata), TrustchainSovrinError> {
+        let (network, _) = SovrinNetwork::from_did(did)?;
+        let nym_reply = self.fetch_nym_version(did, version).await?;
+        let endpoint_reply = self
+            .fetch_attrib_version(did, ENDPOINT_ATTRIB_NAME, version)
+            .await?;
+        resolve_did_replies(&nym_reply, &endpoint_reply, network)
+    }
+}
+
+#[async_trait]
+impl AnonCredsObjectSource for SovrinDIDResolver {
+    async fn schema_reply(
+        &self,
+        network: SovrinNetwork,
+        schema_id: &str,
+    ) -> Result<String, TrustchainSovrinError> {
+        self.fetch_schema(network, schema_id).await
+    }
+
+    async fn cred_def_reply(
+        &self,
+        network: SovrinNetwork,
+        cred_def_id: &str,
+    ) -> Result<String, TrustchainSovrinError> {
+        self.fetch_cred_def(network, cred_def_id).await
+    }
+}
+
+#[async_trait]
+impl LedgerTxnSource for SovrinDIDResolver {
+    async fn ledger_txn(
+        &self,
+        network: SovrinNetwork,
+        seq_no: u64,
+    ) -> Result<Option<LedgerTxn>, TrustchainSovrinError> {
+        self.fetch_txn(network, seq_no).await
     }
 }
 
-fn convert_resolution_documents(doc: DidDocument, meta: DidDocumentMetadata) -> Result<(Document, DocumentMetadata), TrustchainSovrinError>{
-    // TODO: Adapt if some specific data must be moved from Doc to Meta
-    let meta : DocumentMetadata = serde_json::from_value(
-        serde_json::to_value(&meta).map_err(|_| TrustchainSovrinError::CouldNotConvert)?
-    ).map_err(|_| TrustchainSovrinError::CouldNotConvert)?;
+/// Converts a raw GET_NYM ledger reply from the given network into a DID Document and
+/// Document Metadata.
+pub fn resolve_nym_reply(
+    reply: &str,
+    network: SovrinNetwork,
+) -> Result<(Document, DocumentMetadata), TrustchainSovrinError> {
+    // Check that we got a DID and not another type of document.
+    let doc = match handle_internal_resolution_result(network.namespace(), reply)
+        .map_err(|_| TrustchainSovrinError::InvalidLedgerAnswer)?
+    {
+        (ResolverResult::DidDocument(doc), Metadata::DidDocumentMetadata(_)) => doc,
+        (_, _) => return Err(TrustchainSovrinError::InvalidLedgerAnswer),
+    };
+    let meta = document_metadata(reply)?;
+    Ok((convert_document(doc)?, meta))
+}
+
+/// Converts a raw GET_NYM ledger reply and the raw GET_ATTRIB reply for the `endpoint`
+/// attribute of the same DID into a DID Document, with the service endpoint mapped into its
+/// services, and Document Metadata.
+pub fn resolve_did_replies(
+    nym_reply: &str,
+    endpoint_reply: &str,
+    network: SovrinNetwork,
+) -> Result<(Document, DocumentMetadata), TrustchainSovrinError> {
+    let (doc, meta) = resolve_nym_reply(nym_reply, network)?;
+    let endpoint = SovrinEndpoint::from_attrib_reply(endpoint_reply.as_bytes())?;
+    Ok((add_endpoint_services(doc, endpoint)?, meta))
+}
 
-    let doc : Document = serde_json::from_value(
-        doc.to_value().map_err(|_| TrustchainSovrinError::CouldNotConvert)?
-    ).map_err(|_| TrustchainSovrinError::CouldNotConvert)?;
+fn convert_document(doc: DidDocument) -> Result<Document, TrustchainSovrinError> {
+    let mut doc: Document = serde_json::from_value(
+        doc.to_value()
+            .map_err(|_| TrustchainSovrinError::CouldNotConvert)?,
+    )
+    .map_err(|_| TrustchainSovrinError::CouldNotConvert)?;
+    add_verkey_jwks(&mut doc)?;
+    Ok(doc)
+}
+
+/// Adds the Ed25519 ledger verification key of each `Ed25519VerificationKey2018` method of a
+/// DID Document as a public JWK, alongside its `publicKeyBase58`, so that attestations signed
+/// with the ledger key verify as those of any other DID method.
+pub(crate) fn add_verkey_jwks(doc: &mut Document) -> Result<(), TrustchainSovrinError> {
+    for vm in doc.verification_method.iter_mut().flatten() {
+        if let VerificationMethod::Map(vm_map) = vm {
+            if vm_map.type_ != ED25519_VERIFICATION_KEY_TYPE || vm_map.public_key_jwk.is_some() {
+                continue;
+            }
+            if let Some(verkey) = &vm_map.public_key_base58 {
+                vm_map.public_key_jwk = Some(verkey_jwk(verkey)?);
+            }
+        }
+    }
+    Ok(())
+}
 
-    Ok((doc, meta))
+/// Builds the Document Metadata from a ledger reply, with the ledger timestamp of the
+/// transaction as the last update time and its sequence number as the version ID.
+fn document_metadata(reply: &str) -> Result<DocumentMetadata, TrustchainSovrinError> {
+    let result = reply_result(reply.as_bytes())?;
+    let updated = match result.get(TXN_TIME_KEY) {
+        Some(Value::Number(txn_time)) => txn_time
+            .as_i64()
+            .and_then(|txn_time| Utc.timestamp_opt(txn_time, 0).single()),
+        _ => None,
+    };
+    let property_set = match result.get(SEQ_NO_KEY) {
+        Some(Value::Number(seq_no)) => Some(HashMap::from([(
+            VERSION_ID_KEY.to_string(),
+            ssi::did_resolve::Metadata::String(seq_no.to_string()),
+        )])),
+        _ => None,
+    };
+    Ok(DocumentMetadata {
+        created: None,
+        updated,
+        deactivated: None,
+        property_set,
+    })
 }
 
 #[async_trait]
-impl DIDResolver for SovrinResolver{
-    async fn resolve(&self, did: &str, _input_metadata: &ResolutionInputMetadata) -> (ResolutionMetadata, Option<Document>, Option<DocumentMetadata>) {
-        self.fetch_did(did)
-            .and_then(|(doc, meta)| convert_resolution_documents(doc, meta))
-            .map_or_else(
-                |e| (
-                    ResolutionMetadata{ error: Some(format!("Error while resolving DID : {}", e.to_string())), content_type: None, property_set : None },
+impl DIDResolver for SovrinDIDResolver {
+    async fn resolve(
+        &self,
+        did: &str,
+        input_metadata: &ResolutionInputMetadata,
+    ) -> (
+        ResolutionMetadata,
+        Option<Document>,
+        Option<DocumentMetadata>,
+    ) {
+        let result = match NymVersion::from_input_metadata(input_metadata) {
+            Ok(version) => self.fetch_did_version(did, version).await,
+            Err(err) => Err(err),
+        };
+        result.map_or_else(
+            |e| {
+                (
+                    ResolutionMetadata {
+                        error: Some(format!("Error while resolving DID : {}", e)),
+                        content_type: None,
+                        property_set: None,
+                    },
                     None,
-                    None
-                ),
-                |(doc, meta) | (
-                    ResolutionMetadata { error: None, content_type: None, property_set: None },
+                    None,
+                )
+            },
+            |(doc, meta)| {
+                (
+                    ResolutionMetadata {
+                        error: None,
+                        content_type: None,
+                        property_set: None,
+                    },
                     Some(doc),
-                    Some(meta)
+                    Some(meta),
                 )
-            )
+            },
+        )
+    }
+}
+
+/// A Trustchain resolver for Sovrin DIDs.
+///
+/// Wraps a `SovrinDIDResolver` and lifts the Trustchain controller proof stored in a DID's
+/// ATTRIB records into the resolved DID Document and Document Metadata.
+pub struct SovrinResolver {
+    pub wrapped_resolver: SovrinDIDResolver,
+}
+
+impl Default for SovrinResolver {
+    fn default() -> Self {
+        Self::new(RequestConfig::default())
+    }
+}
+
+impl From<SovrinDIDResolver> for SovrinResolver {
+    fn from(resolver: SovrinDIDResolver) -> Self {
+        Self {
+            wrapped_resolver: resolver,
+        }
+    }
+}
+
+impl SovrinResolver {
+    /// Constructs a resolver with no networks, sending ledger requests with the given settings.
+    pub fn new(config: RequestConfig) -> Self {
+        Self::from(SovrinDIDResolver::new(config))
+    }
+
+    /// Constructs a resolver for Sovrin MainNet from genesis transactions.
+    pub fn from_genesis(genesis: &str) -> Result<Self, TrustchainSovrinError> {
+        Self::default().with_network(SovrinNetwork::MainNet, genesis)
+    }
+
+    /// Constructs a resolver for Sovrin MainNet from a genesis transactions file.
+    pub fn from_genesis_file<P: AsRef<Path>>(path: P) -> Result<Self, TrustchainSovrinError> {
+        Self::default().with_network_file(SovrinNetwork::MainNet, path)
+    }
+
+    /// Adds (or replaces) a network, given its genesis transactions.
+    pub fn with_network(
+        self,
+        network: SovrinNetwork,
+        genesis: &str,
+    ) -> Result<Self, TrustchainSovrinError> {
+        Ok(Self::from(
+            self.wrapped_resolver.with_network(network, genesis)?,
+        ))
+    }
+
+    /// Adds (or replaces) a network, given the path to its genesis transactions file.
+    pub fn with_network_file<P: AsRef<Path>>(
+        self,
+        network: SovrinNetwork,
+        path: P,
+    ) -> Result<Self, TrustchainSovrinError> {
+        Ok(Self::from(
+            self.wrapped_resolver.with_network_file(network, path)?,
+        ))
     }
 }
 
 #[async_trait]
-impl TrustchainResolver for SovrinResolver{
+impl DIDResolver for SovrinResolver {
+    async fn resolve(
+        &self,
+        did: &str,
+        input_metadata: &ResolutionInputMetadata,
+    ) -> (
+        ResolutionMetadata,
+        Option<Document>,
+        Option<DocumentMetadata>,
+    ) {
+        self.trustchain_resolve(did, input_metadata).await
+    }
+}
+
+#[async_trait]
+impl TrustchainResolver for SovrinResolver {
     fn wrapped_resolver(&self) -> &dyn DIDResolver {
-        self
+        &self.wrapped_resolver
+    }
+
+    async fn trustchain_resolve(
+        &self,
+        did: &str,
+        input_metadata: &ResolutionInputMetadata,
+    ) -> (
+        ResolutionMetadata,
+        Option<Document>,
+        Option<DocumentMetadata>,
+    ) {
+        // DIDs of methods without a ledger are resolved directly, with no extended transform.
+        if let Some(resolver) = generic_resolver(did) {
+            return self.transform(resolver.resolve(did, input_metadata).await);
+        }
+        let resolved = self.wrapped_resolver.resolve(did, input_metadata).await;
+        // An invalid version is already reported by the wrapped resolver.
+        let version = NymVersion::from_input_metadata(input_metadata).unwrap_or_default();
+        self.versioned_transform(self.transform(resolved), version)
+            .await
+    }
+
+    async fn extended_transform(
+        &self,
+        resolved: (
+            ResolutionMetadata,
+            Option<Document>,
+            Option<DocumentMetadata>,
+        ),
+    ) -> (
+        ResolutionMetadata,
+        Option<Document>,
+        Option<DocumentMetadata>,
+    ) {
+        self.versioned_transform(resolved, NymVersion::default())
+            .await
     }
-}
\ No newline at end of file
+}
+
+impl SovrinResolver {
+    /// Adds the controller proof (if any) of the given version of the DID to a resolution
+    /// result, so that a past DID document is returned with its controller proof at the time.
+    async fn versioned_transform(
+        &self,
+        (res_meta, doc, doc_meta): (
+            ResolutionMetadata,
+            Option<Document>,
+            Option<DocumentMetadata>,
+        ),
+        version: NymVersion,
+    ) -> (
+        ResolutionMetadata,
+        Option<Document>,
+        Option<DocumentMetadata>,
+    ) {
+        // If a document and document metadata are returned, add the controller proof (if any).
+        if let (Some(did_doc), Some(did_doc_meta)) = (doc, doc_meta) {
+            let tc_result = match self
+                .wrapped_resolver
+                .fetch_controller_proof_version(&did_doc.id, version)
+                .await
+            {
+                Ok(proof) => add_controller_proof(did_doc, did_doc_meta, proof),
+                Err(err) => Err(err),
+            };
+            match tc_result {
+                Ok((tc_doc, tc_doc_meta)) => (res_meta, Some(tc_doc), Some(tc_doc_meta)),
+                // If failed to convert, return the relevant error.
+                Err(err) => {
+                    let res_meta = ResolutionMetadata {
+                        error: Some(err.to_string()),
+                        content_type: None,
+                        property_set: None,
+                    };
+                    (res_meta, None, None)
+                }
+            }
+        } else {
+            (res_meta, None, None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{
+        TEST_GET_ENDPOINT_REPLY, TEST_GET_NYM_REPLY, TEST_NYM_TXN_TIME, TEST_NYM_VERKEY,
+    };
+
+    #[test]
+    fn test_resolve_nym_reply() {
+        let (doc, meta) = resolve_nym_reply(TEST_GET_NYM_REPLY, SovrinNetwork::MainNet).unwrap();
+        assert_eq!(doc.id, "did:indy:sovrin:BF4pE6ugcqNWt5kpJUE1Ro");
+        let verification_methods = doc.verification_method.unwrap();
+        assert_eq!(verification_methods.len(), 1);
+        match &verification_methods[0] {
+            VerificationMethod::Map(vm_map) => {
+                assert_eq!(vm_map.public_key_base58.as_deref(), Some(TEST_NYM_VERKEY));
+                assert_eq!(
+                    vm_map.public_key_jwk.as_ref(),
+                    Some(&verkey_jwk(TEST_NYM_VERKEY).unwrap())
+                );
+            }
+            _ => panic!("Expected verification method map."),
+        }
+        assert_eq!(meta.updated.unwrap().timestamp(), TEST_NYM_TXN_TIME as i64);
+        assert!(matches!(
+            meta.property_set.unwrap().get(VERSION_ID_KEY),
+            Some(ssi::did_resolve::Metadata::String(version_id)) if version_id == "29314"
+        ));
+    }
+
+    #[test]
+    fn test_resolve_did_replies() {
+        let (doc, _) = resolve_did_replies(
+            TEST_GET_NYM_REPLY,
+            TEST_GET_ENDPOINT_REPLY,
+            SovrinNetwork::MainNet,
+        )
+        .unwrap();
+        let service_ids: Vec<String> = doc
+            .service
+            .unwrap()
+            .into_iter()
+            .map(|service| service.id)
+            .collect();
+        assert_eq!(
+            service_ids,
+            vec![
+                "did:indy:sovrin:BF4pE6ugcqNWt5kpJUE1Ro#endpoint",
+                "did:indy:sovrin:BF4pE6ugcqNWt5kpJUE1Ro#did-communication"
+            ]
+        );
+
+        // A DID without a service endpoint has no services.
+        let mut reply: Value = serde_json::from_str(TEST_GET_ENDPOINT_REPLY).unwrap();
+        reply["result"]["data"] = Value::Null;
+        let (doc, _) = resolve_did_replies(
+            TEST_GET_NYM_REPLY,
+            &reply.to_string(),
+            SovrinNetwork::MainNet,
+        )
+        .unwrap();
+        assert!(doc.service.is_none());
+    }
+
+    #[test]
+    fn test_nym_version_from_input_metadata() {
+        let version = NymVersion::from_input_metadata(&ResolutionInputMetadata::default());
+        assert_eq!(version.unwrap(), NymVersion::default());
+
+        let input_metadata = ResolutionInputMetadata {
+            version_id: Some("29314".to_string()),
+            version_time: Some("2020-03-30T15:17:37Z".to_string()),
+            ..ResolutionInputMetadata::default()
+        };
+        assert_eq!(
+            NymVersion::from_input_metadata(&input_metadata).unwrap(),
+            NymVersion {
+                seq_no: Some(29314),
+                timestamp: Some(TEST_NYM_TXN_TIME),
+            }
+        );
+
+        for (version_id, version_time) in [
+            (Some("0"), None),
+            (Some("latest"), None),
+            (None, Some("1585581457")),
+            (None, Some("1960-01-01T00:00:00Z")),
+        ] {
+            let input_metadata = ResolutionInputMetadata {
+                version_id: version_id.map(str::to_string),
+                version_time: version_time.map(str::to_string),
+                ..ResolutionInputMetadata::default()
+            };
+            assert!(matches!(
+                NymVersion::from_input_metadata(&input_metadata),
+                Err(TrustchainSovrinError::InvalidVersion(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_resolve_nym_reply_network() {
+        let (doc, _) = resolve_nym_reply(TEST_GET_NYM_REPLY, SovrinNetwork::StagingNet).unwrap();
+        assert_eq!(doc.id, "did:indy:sovrin:staging:BF4pE6ugcqNWt5kpJUE1Ro");
+    }
+
+    #[test]
+    fn test_with_network_invalid_genesis() {
+        assert!(matches!(
+            SovrinResolver::from_genesis("not a transaction"),
+            Err(TrustchainSovrinError::InvalidGenesis(_))
+        ));
+        assert!(matches!(
+            SovrinResolver::from_genesis_file("./does_not_exist.txn"),
+            Err(TrustchainSovrinError::InvalidGenesis(_))
+        ));
+    }
+
+    #[test]
+    fn test_request_config() {
+        let config: RequestConfig = serde_json::from_str(r#"{"timeout": 5}"#).unwrap();
+        assert_eq!(
+            config,
+            RequestConfig {
+                timeout: 5,
+                ..RequestConfig::default()
+            }
+        );
+        let pool_config = config.pool_config();
+        assert_eq!(pool_config.reply_timeout, 5);
+        assert_eq!(pool_config.request_read_nodes, config.read_nodes);
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(is_retryable(&TrustchainSovrinError::Timeout(20)));
+        assert!(is_retryable(&TrustchainSovrinError::MissingStateProof));
+        assert!(!is_retryable(&TrustchainSovrinError::QueryFailed(
+            "Rejected".to_string()
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_nym_network_not_configured() {
+        let resolver = SovrinDIDResolver::default();
+        assert!(resolver.networks().is_empty());
+        assert!(matches!(
+            resolver
+                .fetch_nym("did:sov:staging:BF4pE6ugcqNWt5kpJUE1Ro")
+                .await,
+            Err(TrustchainSovrinError::NetworkNotConfigured(_))
+        ));
+        assert!(matches!(
+            resolver
+                .fetch_nym("did:ion:test:EiCClfEdkTv_aM3UnBBhlOV89LlGhpQAbfeZLFdFxVFkEg")
+                .await,
+            Err(TrustchainSovrinError::InvalidDID(_))
+        ));
+    }
+}
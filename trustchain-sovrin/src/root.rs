@@ -0,0 +1,339 @@
+//! Identification of candidate root DIDs on a Sovrin ledger by date.
+//!
+//! The transactions of the domain ledger are ordered by sequence number and their ledger
+//! timestamps (`txnTime`) increase with it, so the NYM transactions written on a date are
+//! found by a binary search for the first transaction of the day, followed by a scan up to
+//! the first transaction of the next day.
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::network::SovrinNetwork;
+use crate::utils::reply_result;
+use crate::{TrustchainSovrinError, DATA_KEY, DEST_KEY, SEQ_NO_KEY, TXN_TIME_KEY};
+
+/// Identifier of the domain ledger, holding NYM and ATTRIB transactions.
+pub const DOMAIN_LEDGER_ID: i32 = 1;
+/// Transaction type of a NYM transaction.
+pub const NYM_TXN_TYPE: &str = "1";
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// A transaction of the domain ledger, as returned by a GET_TXN request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LedgerTxn {
+    /// Sequence number of the transaction on the ledger.
+    pub seq_no: u64,
+    /// Ledger timestamp of the transaction (zero for genesis transactions, which have none).
+    pub txn_time: u64,
+    /// Transaction type, e.g. "1" for NYM.
+    pub txn_type: String,
+    /// The target DID of the transaction, if any.
+    pub dest: Option<String>,
+}
+
+impl LedgerTxn {
+    /// Extracts the transaction from a raw GET_TXN reply. Returns `None` if there is no
+    /// transaction with the requested sequence number.
+    pub fn from_txn_reply(reply: &[u8]) -> Result<Option<Self>, TrustchainSovrinError> {
+        let data = match reply_result(reply)?.get(DATA_KEY) {
+            None | Some(Value::Null) => return Ok(None),
+            Some(Value::String(data)) => serde_json::from_str(data)
+                .map_err(|_| TrustchainSovrinError::InvalidLedgerAnswer)?,
+            Some(data @ Value::Object(_)) => data.to_owned(),
+            _ => return Err(TrustchainSovrinError::InvalidLedgerAnswer),
+        };
+        let seq_no = data["txnMetadata"][SEQ_NO_KEY]
+            .as_u64()
+            .ok_or(TrustchainSovrinError::InvalidLedgerAnswer)?;
+        let txn_type = data["txn"]["type"]
+            .as_str()
+            .ok_or(TrustchainSovrinError::InvalidLedgerAnswer)?;
+        Ok(Some(Self {
+            seq_no,
+            txn_time: data["txnMetadata"][TXN_TIME_KEY].as_u64().unwrap_or(0),
+            txn_type: txn_type.to_string(),
+            dest: data["txn"]["data"][DEST_KEY].as_str().map(str::to_string),
+        }))
+    }
+
+    /// Returns true if this is a NYM transaction.
+    pub fn is_nym(&self) -> bool {
+        self.txn_type == NYM_TXN_TYPE
+    }
+}
+
+/// Checks that a raw GET_TXN reply holds a well-formed transaction (or none).
+///
+/// GET_TXN replies carry a ledger audit path rather than a state proof, and neither the audit
+/// path nor the multi-signature over its ledger root is verified, so a reply is trusted as
+/// served by a single node. Root candidates are therefore only candidates: the root DID is
+/// verified by the `Verifier` like any other Sovrin DID.
+pub(crate) fn check_txn_reply(reply: &[u8]) -> Result<String, TrustchainSovrinError> {
+    LedgerTxn::from_txn_reply(reply)?;
+    Ok(String::from_utf8_lossy(reply).to_string())
+}
+
+/// A source of domain ledger transactions by sequence number.
+#[async_trait]
+pub trait LedgerTxnSource {
+    /// Gets the transaction with the given sequence number on a network, if any.
+    async fn ledger_txn(
+        &self,
+        network: SovrinNetwork,
+        seq_no: u64,
+    ) -> Result<Option<LedgerTxn>, TrustchainSovrinError>;
+}
+
+/// A candidate root DID: the target of a NYM transaction written on the date of interest.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SovrinRootCandidate {
+    pub did: String,
+    pub seq_no: u64,
+    pub txn_time: u64,
+}
+
+/// Gets the unix timestamps of the start and the end of a date (UTC).
+fn date_range(date: NaiveDate) -> (u64, u64) {
+    let start = date.and_hms_opt(0, 0, 0).unwrap().timestamp().max(0) as u64;
+    (start, start + SECONDS_PER_DAY)
+}
+
+/// Gets the transaction with the given sequence number, which must be on the ledger.
+async fn required_txn<S: LedgerTxnSource + Sync>(
+    source: &S,
+    network: SovrinNetwork,
+    seq_no: u64,
+) -> Result<LedgerTxn, TrustchainSovrinError> {
+    source
+        .ledger_txn(network, seq_no)
+        .await?
+        .ok_or(TrustchainSovrinError::MissingTransaction(seq_no))
+}
+
+/// Gets the number of transactions on the domain ledger, with an exponential search for an
+/// upper bound followed by a binary search.
+async fn ledger_size<S: LedgerTxnSource + Sync>(
+    source: &S,
+    network: SovrinNetwork,
+) -> Result<u64, TrustchainSovrinError> {
+    // Invariant: the transaction `lower` exists (or `lower` is zero) and `upper` does not.
+    let mut lower = 0;
+    let mut upper = 1;
+    while source.ledger_txn(network, upper).await?.is_some() {
+        lower = upper;
+        upper *= 2;
+    }
+    while upper - lower > 1 {
+        let mid = lower + (upper - lower) / 2;
+        match source.ledger_txn(network, mid).await? {
+            Some(_) => lower = mid,
+            None => upper = mid,
+        }
+    }
+    Ok(lower)
+}
+
+/// Gets the sequence number of the first transaction with a timestamp at or after `time`,
+/// or one past the ledger size if there is none.
+async fn first_txn_since<S: LedgerTxnSource + Sync>(
+    source: &S,
+    network: SovrinNetwork,
+    size: u64,
+    time: u64,
+) -> Result<u64, TrustchainSovrinError> {
+    let mut lower = 1;
+    let mut upper = size + 1;
+    while lower < upper {
+        let mid = lower + (upper - lower) / 2;
+        if required_txn(source, network, mid).await?.txn_time < time {
+            lower = mid + 1;
+        } else {
+            upper = mid;
+        }
+    }
+    Ok(lower)
+}
+
+/// Gets the root DID candidates written to the ledger of a network in the time range
+/// `[start, end)`, given as unix timestamps.
+///
+/// Every NYM transaction in the range is a candidate, including NYM transactions updating
+/// an existing DID.
+pub async fn root_did_candidates_in_range<S: LedgerTxnSource + Sync>(
+    source: &S,
+    network: SovrinNetwork,
+    start: u64,
+    end: u64,
+) -> Result<Vec<SovrinRootCandidate>, TrustchainSovrinError> {
+    let size = ledger_size(source, network).await?;
+    let mut candidates = Vec::new();
+    for seq_no in first_txn_since(source, network, size, start).await?..=size {
+        let txn = required_txn(source, network, seq_no).await?;
+        if txn.txn_time >= end {
+            break;
+        }
+        if let (true, Some(dest)) = (txn.is_nym(), &txn.dest) {
+            candidates.push(SovrinRootCandidate {
+                did: network.did_indy(dest),
+                seq_no: txn.seq_no,
+                txn_time: txn.txn_time,
+            });
+        }
+    }
+    Ok(candidates)
+}
+
+/// Gets the root DID candidates written to the ledger of a network on a given date (UTC).
+pub async fn root_did_candidates<S: LedgerTxnSource + Sync>(
+    source: &S,
+    network: SovrinNetwork,
+    date: NaiveDate,
+) -> Result<Vec<SovrinRootCandidate>, TrustchainSovrinError> {
+    let (start, end) = date_range(date);
+    root_did_candidates_in_range(source, network, start, end).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{TEST_GET_TXN_REPLY, TEST_NYM_TXN_TIME};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// An in-memory ledger, counting the transactions requested from it.
+    struct TestLedger {
+        txns: Vec<LedgerTxn>,
+        requests: AtomicUsize,
+    }
+
+    impl TestLedger {
+        /// Constructs a ledger with one transaction per (timestamp, type) pair.
+        fn new(txns: &[(u64, &str)]) -> Self {
+            let txns = txns
+                .iter()
+                .enumerate()
+                .map(|(i, (txn_time, txn_type))| LedgerTxn {
+                    seq_no: i as u64 + 1,
+                    txn_time: *txn_time,
+                    txn_type: txn_type.to_string(),
+                    dest: Some(format!("Dest{}", i + 1)),
+                })
+                .collect();
+            Self {
+                txns,
+                requests: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl LedgerTxnSource for TestLedger {
+        async fn ledger_txn(
+            &self,
+            _network: SovrinNetwork,
+            seq_no: u64,
+        ) -> Result<Option<LedgerTxn>, TrustchainSovrinError> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            Ok(seq_no
+                .checked_sub(1)
+                .and_then(|i| self.txns.get(i as usize))
+                .cloned())
+        }
+    }
+
+    #[test]
+    fn test_from_txn_reply() {
+        let txn = LedgerTxn::from_txn_reply(TEST_GET_TXN_REPLY.as_bytes())
+            .unwrap()
+            .unwrap();
+        assert_eq!(txn.seq_no, 29314);
+        assert_eq!(txn.txn_time, TEST_NYM_TXN_TIME);
+        assert!(txn.is_nym());
+        assert_eq!(txn.dest.as_deref(), Some("BF4pE6ugcqNWt5kpJUE1Ro"));
+
+        // No transaction with the requested sequence number.
+        let reply = r#"{"op": "REPLY", "result": {"type": "3", "seqNo": null, "data": null}}"#;
+        assert!(LedgerTxn::from_txn_reply(reply.as_bytes())
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_date_range() {
+        let date = NaiveDate::from_ymd_opt(2020, 3, 30).unwrap();
+        let (start, end) = date_range(date);
+        assert_eq!(start, 1585526400);
+        assert_eq!(end - start, SECONDS_PER_DAY);
+        assert!((start..end).contains(&TEST_NYM_TXN_TIME));
+    }
+
+    #[tokio::test]
+    async fn test_ledger_size() {
+        for size in [0, 1, 2, 7, 8, 9, 100] {
+            let ledger = TestLedger::new(&vec![(0, NYM_TXN_TYPE); size]);
+            assert_eq!(
+                ledger_size(&ledger, SovrinNetwork::MainNet).await.unwrap(),
+                size as u64
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_root_did_candidates_in_range() {
+        let ledger = TestLedger::new(&[
+            (0, NYM_TXN_TYPE),
+            (100, NYM_TXN_TYPE),
+            (200, NYM_TXN_TYPE),
+            (200, "100"),
+            (250, NYM_TXN_TYPE),
+            (300, NYM_TXN_TYPE),
+            (400, NYM_TXN_TYPE),
+        ]);
+        let candidates = root_did_candidates_in_range(&ledger, SovrinNetwork::StagingNet, 150, 300)
+            .await
+            .unwrap();
+        assert_eq!(
+            candidates,
+            vec![
+                SovrinRootCandidate {
+                    did: "did:indy:sovrin:staging:Dest3".to_string(),
+                    seq_no: 3,
+                    txn_time: 200,
+                },
+                SovrinRootCandidate {
+                    did: "did:indy:sovrin:staging:Dest5".to_string(),
+                    seq_no: 5,
+                    txn_time: 250,
+                },
+            ]
+        );
+
+        // Ranges before, between and after the transactions have no candidates.
+        for (start, end) in [(1, 100), (260, 300), (500, 600)] {
+            assert!(
+                root_did_candidates_in_range(&ledger, SovrinNetwork::MainNet, start, end)
+                    .await
+                    .unwrap()
+                    .is_empty()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_root_did_candidates_request_count() {
+        // One NYM transaction per hour over ten days.
+        let txns: Vec<(u64, &str)> = (0..240).map(|i| (i * 3600, NYM_TXN_TYPE)).collect();
+        let ledger = TestLedger::new(&txns);
+        let date = NaiveDate::from_ymd_opt(1970, 1, 4).unwrap();
+        let candidates = root_did_candidates(&ledger, SovrinNetwork::MainNet, date)
+            .await
+            .unwrap();
+        assert_eq!(candidates.len(), 24);
+        assert_eq!(candidates[0].seq_no, 73);
+
+        // The search only reads the transactions of the day and a logarithmic number of others.
+        assert!(ledger.requests.load(Ordering::SeqCst) < 24 + 50);
+    }
+}
@@ -0,0 +1,624 @@
+//! Verification of Indy ledger state proofs.
+//!
+//! Replies to ledger read requests carry a proof that the returned data is stored in the
+//! ledger state: a path of Merkle Patricia trie nodes from the state root down to the value,
+//! together with a BLS multi-signature of the pool validators over that state root.
+use rlp::{DecoderError, Rlp};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha3::{Digest, Sha3_256};
+use std::collections::HashMap;
+
+use crate::genesis::ValidatorKeys;
+use crate::utils::{
+    attrib_state_key, cred_def_state_key, hex_digest, nym_data, nym_state_key, reply_result,
+    schema_state_key,
+};
+use crate::{
+    TrustchainSovrinError, ATTR_NAMES_KEY, DATA_KEY, DEST_KEY, NAME_KEY, ORIGIN_KEY, RAW_KEY,
+    REF_KEY, SEQ_NO_KEY, SIGNATURE_TYPE_KEY, STATE_PROOF_KEY, TAG_KEY, TXN_TIME_KEY, VERSION_KEY,
+};
+
+/// Number of items in an RLP-encoded trie branch node.
+const BRANCH_NODE_LENGTH: usize = 17;
+/// Number of items in an RLP-encoded trie leaf or extension node.
+const PAIR_NODE_LENGTH: usize = 2;
+
+/// The ledger state values signed by the validators.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MultiSignatureValue {
+    pub ledger_id: u64,
+    pub pool_state_root_hash: String,
+    pub state_root_hash: String,
+    pub timestamp: u64,
+    pub txn_root_hash: String,
+}
+
+/// A BLS multi-signature of the pool validators over the ledger state.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MultiSignature {
+    pub value: MultiSignatureValue,
+    pub signature: String,
+    pub participants: Vec<String>,
+}
+
+/// A state proof, as found in ledger replies.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct StateProof {
+    /// Base58-encoded state trie root hash.
+    pub root_hash: String,
+    /// Base64-encoded RLP list of the trie nodes on the path to the value.
+    pub proof_nodes: String,
+    pub multi_signature: MultiSignature,
+}
+
+impl StateProof {
+    /// Extracts the state proof from a raw ledger reply.
+    pub fn from_reply(reply: &[u8]) -> Result<Self, TrustchainSovrinError> {
+        match reply_result(reply)?.get(STATE_PROOF_KEY) {
+            Some(state_proof @ Value::Object(_)) => serde_json::from_value(state_proof.to_owned())
+                .map_err(|e| TrustchainSovrinError::InvalidStateProof(e.to_string())),
+            _ => Err(TrustchainSovrinError::MissingStateProof),
+        }
+    }
+
+    /// Looks up the raw value stored under `key`, using only the trie nodes of the proof.
+    fn lookup(&self, key: &[u8]) -> Result<Option<Vec<u8>>, TrustchainSovrinError> {
+        let root = bs58::decode(&self.root_hash).into_vec().map_err(|_| {
+            TrustchainSovrinError::InvalidStateProof("Root hash is not valid base58.".to_string())
+        })?;
+        let proof_nodes = base64::decode(&self.proof_nodes).map_err(|_| {
+            TrustchainSovrinError::InvalidStateProof(
+                "Proof nodes are not valid base64.".to_string(),
+            )
+        })?;
+        trie_get(&root, key, &proof_nodes)
+    }
+
+    /// Verifies that the trie nodes prove the given JSON value is stored under `key` in the
+    /// state with root `root_hash`.
+    pub fn verify_value(&self, key: &[u8], expected: &Value) -> Result<(), TrustchainSovrinError> {
+        let value = self.lookup(key)?.ok_or_else(|| {
+            TrustchainSovrinError::InvalidStateProof("Key not found in state trie.".to_string())
+        })?;
+        // Ledger state values are stored as an RLP list wrapping the JSON serialized value.
+        let value = Rlp::new(&value)
+            .at(0)
+            .and_then(|item| item.data().map(|data| data.to_vec()))
+            .map_err(decoder_error)?;
+        let value: Value = serde_json::from_slice(&value).map_err(|_| {
+            TrustchainSovrinError::InvalidStateProof("State value is not valid JSON.".to_string())
+        })?;
+        if value.ne(expected) {
+            return Err(TrustchainSovrinError::InvalidStateProof(format!(
+                "State value {} does not match expected {}.",
+                value, expected
+            )));
+        }
+        Ok(())
+    }
+
+    /// Verifies that the trie nodes prove no value is stored under `key` in the state with
+    /// root `root_hash`.
+    pub fn verify_absent(&self, key: &[u8]) -> Result<(), TrustchainSovrinError> {
+        match self.lookup(key)? {
+            None => Ok(()),
+            Some(_) => Err(TrustchainSovrinError::InvalidStateProof(
+                "Key claimed absent is found in state trie.".to_string(),
+            )),
+        }
+    }
+
+    /// Checks that the multi-signature is over the state root hash of this proof.
+    pub fn verify_multi_signature_value(&self) -> Result<(), TrustchainSovrinError> {
+        if self
+            .multi_signature
+            .value
+            .state_root_hash
+            .ne(&self.root_hash)
+        {
+            return Err(TrustchainSovrinError::InvalidStateProof(format!(
+                "Signed state root {} does not match proof root {}.",
+                self.multi_signature.value.state_root_hash, self.root_hash
+            )));
+        }
+        if self.multi_signature.participants.is_empty() {
+            return Err(TrustchainSovrinError::InvalidStateProof(
+                "Multi-signature has no participants.".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Verifies that the state root of this proof is signed by a quorum of the given validators.
+    pub fn verify_signature(
+        &self,
+        validators: &ValidatorKeys,
+    ) -> Result<(), TrustchainSovrinError> {
+        self.verify_multi_signature_value()?;
+        validators.verify_multi_signature(&self.multi_signature)
+    }
+}
+
+/// Verifies a raw GET_NYM reply: the NYM data must be proven by the state proof, under a
+/// state root signed by a quorum of the given validators.
+///
+/// Returns the base58-encoded state root hash under which the NYM data is proven.
+pub fn verify_nym_reply(
+    reply: &[u8],
+    validators: &ValidatorKeys,
+) -> Result<String, TrustchainSovrinError> {
+    let state_root = verify_nym_state_proof(reply)?;
+    StateProof::from_reply(reply)?.verify_signature(validators)?;
+    Ok(state_root)
+}
+
+/// Verifies the state proof in a raw GET_NYM reply against the NYM data in the same reply.
+///
+/// Returns the base58-encoded state root hash under which the NYM data is proven.
+pub fn verify_nym_state_proof(reply: &[u8]) -> Result<String, TrustchainSovrinError> {
+    let state_proof = StateProof::from_reply(reply)?;
+    // The DID is the state key, so it is omitted from the stored value.
+    let mut data = nym_data(reply)?;
+    let dest = match data.as_object_mut().and_then(|map| map.remove(DEST_KEY)) {
+        Some(Value::String(dest)) => dest,
+        _ => return Err(TrustchainSovrinError::InvalidLedgerAnswer),
+    };
+    state_proof.verify_value(&nym_state_key(&dest), &data)?;
+    Ok(state_proof.root_hash)
+}
+
+/// Verifies a raw GET_ATTRIB reply: the attribute (or its absence) must be proven by the
+/// state proof, under a state root signed by a quorum of the given validators.
+///
+/// Returns the base58-encoded state root hash under which the attribute is proven.
+pub fn verify_attrib_reply(
+    reply: &[u8],
+    validators: &ValidatorKeys,
+) -> Result<String, TrustchainSovrinError> {
+    let state_root = verify_attrib_state_proof(reply)?;
+    StateProof::from_reply(reply)?.verify_signature(validators)?;
+    Ok(state_root)
+}
+
+/// Verifies the state proof in a raw GET_ATTRIB reply against the attribute in the same reply.
+///
+/// The state stores the hash of the raw attribute together with the sequence number and
+/// time of the transaction that last set it.
+///
+/// Returns the base58-encoded state root hash under which the attribute is proven.
+pub fn verify_attrib_state_proof(reply: &[u8]) -> Result<String, TrustchainSovrinError> {
+    let state_proof = StateProof::from_reply(reply)?;
+    let result = reply_result(reply)?;
+    let key = match (result.get(DEST_KEY), result.get(RAW_KEY)) {
+        (Some(Value::String(dest)), Some(Value::String(name))) => attrib_state_key(dest, name),
+        _ => return Err(TrustchainSovrinError::InvalidLedgerAnswer),
+    };
+    match result.get(DATA_KEY) {
+        None | Some(Value::Null) => state_proof.verify_absent(&key)?,
+        Some(Value::String(data)) => {
+            let expected = serde_json::json!({
+                "lsn": result.get(SEQ_NO_KEY),
+                "lut": result.get(TXN_TIME_KEY),
+                "val": hex_digest(data.as_bytes()),
+            });
+            state_proof.verify_value(&key, &expected)?
+        }
+        _ => return Err(TrustchainSovrinError::InvalidLedgerAnswer),
+    }
+    Ok(state_proof.root_hash)
+}
+
+/// Verifies a raw GET_SCHEMA reply: the schema (or its absence) must be proven by the state
+/// proof, under a state root signed by a quorum of the given validators.
+///
+/// Returns the base58-encoded state root hash under which the schema is proven.
+pub fn verify_schema_reply(
+    reply: &[u8],
+    validators: &ValidatorKeys,
+) -> Result<String, TrustchainSovrinError> {
+    let state_root = verify_schema_state_proof(reply)?;
+    StateProof::from_reply(reply)?.verify_signature(validators)?;
+    Ok(state_root)
+}
+
+/// Verifies the state proof in a raw GET_SCHEMA reply against the schema in the same reply.
+///
+/// The state stores the schema attribute names under a key made of the issuer DID, schema
+/// name and version, together with the sequence number and time of the transaction.
+///
+/// Returns the base58-encoded state root hash under which the schema is proven.
+pub fn verify_schema_state_proof(reply: &[u8]) -> Result<String, TrustchainSovrinError> {
+    let state_proof = StateProof::from_reply(reply)?;
+    let result = reply_result(reply)?;
+    let data = result.get(DATA_KEY).and_then(Value::as_object);
+    let key = match (
+        result.get(DEST_KEY),
+        data.and_then(|data| data.get(NAME_KEY)),
+        data.and_then(|data| data.get(VERSION_KEY)),
+    ) {
+        (Some(Value::String(dest)), Some(Value::String(name)), Some(Value::String(version))) => {
+            schema_state_key(dest, name, version)
+        }
+        _ => return Err(TrustchainSovrinError::InvalidLedgerAnswer),
+    };
+    // A missing schema is answered with its name and version only.
+    match data.and_then(|data| data.get(ATTR_NAMES_KEY)) {
+        None | Some(Value::Null) => state_proof.verify_absent(&key)?,
+        Some(attr_names) => {
+            let expected = serde_json::json!({
+                "lsn": result.get(SEQ_NO_KEY),
+                "lut": result.get(TXN_TIME_KEY),
+                "val": { ATTR_NAMES_KEY: attr_names },
+            });
+            state_proof.verify_value(&key, &expected)?
+        }
+    }
+    Ok(state_proof.root_hash)
+}
+
+/// Verifies a raw GET_CLAIM_DEF reply: the credential definition (or its absence) must be
+/// proven by the state proof, under a state root signed by a quorum of the given validators.
+///
+/// Returns the base58-encoded state root hash under which the credential definition is proven.
+pub fn verify_cred_def_reply(
+    reply: &[u8],
+    validators: &ValidatorKeys,
+) -> Result<String, TrustchainSovrinError> {
+    let state_root = verify_cred_def_state_proof(reply)?;
+    StateProof::from_reply(reply)?.verify_signature(validators)?;
+    Ok(state_root)
+}
+
+/// Verifies the state proof in a raw GET_CLAIM_DEF reply against the credential definition in
+/// the same reply.
+///
+/// Returns the base58-encoded state root hash under which the credential definition is proven.
+pub fn verify_cred_def_state_proof(reply: &[u8]) -> Result<String, TrustchainSovrinError> {
+    let state_proof = StateProof::from_reply(reply)?;
+    let result = reply_result(reply)?;
+    let key = match (
+        result.get(ORIGIN_KEY),
+        result.get(SIGNATURE_TYPE_KEY),
+        result.get(REF_KEY).and_then(Value::as_u64),
+        result.get(TAG_KEY),
+    ) {
+        (
+            Some(Value::String(origin)),
+            Some(Value::String(signature_type)),
+            Some(schema_ref),
+            Some(Value::String(tag)),
+        ) => cred_def_state_key(origin, signature_type, schema_ref, tag),
+        _ => return Err(TrustchainSovrinError::InvalidLedgerAnswer),
+    };
+    match result.get(DATA_KEY) {
+        None | Some(Value::Null) => state_proof.verify_absent(&key)?,
+        Some(data @ Value::Object(_)) => {
+            let expected = serde_json::json!({
+                "lsn": result.get(SEQ_NO_KEY),
+                "lut": result.get(TXN_TIME_KEY),
+                "val": data,
+            });
+            state_proof.verify_value(&key, &expected)?
+        }
+        _ => return Err(TrustchainSovrinError::InvalidLedgerAnswer),
+    }
+    Ok(state_proof.root_hash)
+}
+
+fn decoder_error(err: DecoderError) -> TrustchainSovrinError {
+    TrustchainSovrinError::InvalidStateProof(format!("RLP decoding error: {}", err))
+}
+
+/// Splits bytes into half-byte nibbles.
+fn nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect()
+}
+
+/// Decodes a hex-prefix encoded trie path, returning the path nibbles and whether the node
+/// is a leaf.
+fn decode_hex_prefix(encoded: &[u8]) -> Result<(Vec<u8>, bool), TrustchainSovrinError> {
+    let first = *encoded.first().ok_or_else(|| {
+        TrustchainSovrinError::InvalidStateProof("Empty trie node path.".to_string())
+    })?;
+    let flag = first >> 4;
+    let mut path = Vec::new();
+    // Odd length paths carry their first nibble in the flag byte.
+    if flag & 1 == 1 {
+        path.push(first & 0x0f);
+    }
+    path.extend(nibbles(&encoded[1..]));
+    Ok((path, flag & 2 == 2))
+}
+
+/// Resolves a reference to a child node: either an inline node or the hash of a proof node.
+fn child_node(
+    child: &Rlp,
+    nodes: &HashMap<Vec<u8>, Vec<u8>>,
+) -> Result<Option<Vec<u8>>, TrustchainSovrinError> {
+    if child.is_list() {
+        return Ok(Some(child.as_raw().to_vec()));
+    }
+    if child.is_empty() {
+        return Ok(None);
+    }
+    let hash = child.data().map_err(decoder_error)?;
+    match nodes.get(hash) {
+        Some(node) => Ok(Some(node.to_owned())),
+        None => Err(TrustchainSovrinError::InvalidStateProof(
+            "Missing trie node in proof.".to_string(),
+        )),
+    }
+}
+
+/// Looks up the value stored under `key` in the trie with the given root, using only the
+/// supplied (RLP list of) proof nodes. Returns `None` if the proof shows the key is absent.
+fn trie_get(
+    root: &[u8],
+    key: &[u8],
+    proof_nodes: &[u8],
+) -> Result<Option<Vec<u8>>, TrustchainSovrinError> {
+    let proof_nodes = Rlp::new(proof_nodes);
+    if !proof_nodes.is_list() {
+        return Err(TrustchainSovrinError::InvalidStateProof(
+            "Proof nodes are not an RLP list.".to_string(),
+        ));
+    }
+    // Index the proof nodes by their hash.
+    let nodes: HashMap<Vec<u8>, Vec<u8>> = proof_nodes
+        .iter()
+        .map(|node| {
+            (
+                Sha3_256::digest(node.as_raw()).to_vec(),
+                node.as_raw().to_vec(),
+            )
+        })
+        .collect();
+
+    let path = nibbles(key);
+    let mut remaining: &[u8] = &path;
+    let mut node = match nodes.get(root) {
+        Some(node) => node.to_owned(),
+        None => {
+            return Err(TrustchainSovrinError::InvalidStateProof(
+                "Root node not found in proof.".to_string(),
+            ))
+        }
+    };
+    loop {
+        let rlp = Rlp::new(&node);
+        let child = match rlp.item_count().map_err(decoder_error)? {
+            BRANCH_NODE_LENGTH => match remaining.split_first() {
+                Some((nibble, rest)) => {
+                    remaining = rest;
+                    rlp.at(*nibble as usize).map_err(decoder_error)?
+                }
+                None => {
+                    let value = rlp.at(16).map_err(decoder_error)?;
+                    return match value.data().map_err(decoder_error)? {
+                        [] => Ok(None),
+                        data => Ok(Some(data.to_vec())),
+                    };
+                }
+            },
+            PAIR_NODE_LENGTH => {
+                let (node_path, is_leaf) =
+                    decode_hex_prefix(rlp.at(0).and_then(|p| p.data()).map_err(decoder_error)?)?;
+                if is_leaf {
+                    return match remaining.eq(node_path.as_slice()) {
+                        true => Ok(Some(
+                            rlp.at(1)
+                                .and_then(|v| v.data().map(|data| data.to_vec()))
+                                .map_err(decoder_error)?,
+                        )),
+                        false => Ok(None),
+                    };
+                }
+                // Extension node.
+                if !remaining.starts_with(&node_path) {
+                    return Ok(None);
+                }
+                remaining = &remaining[node_path.len()..];
+                rlp.at(1).map_err(decoder_error)?
+            }
+            _ => {
+                return Err(TrustchainSovrinError::InvalidStateProof(
+                    "Unexpected trie node.".to_string(),
+                ))
+            }
+        };
+        node = match child_node(&child, &nodes)? {
+            Some(child) => child,
+            None => return Ok(None),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{
+        TestPool, TEST_ATTRIB_STATE_ROOT_HASH, TEST_ENDPOINT_STATE_ROOT_HASH,
+        TEST_GENESIS_TRANSACTIONS, TEST_GET_ATTRIB_REPLY, TEST_GET_CRED_DEF_REPLY,
+        TEST_GET_ENDPOINT_REPLY, TEST_GET_NYM_REPLY, TEST_GET_SCHEMA_REPLY, TEST_STATE_ROOT_HASH,
+    };
+
+    #[test]
+    fn test_from_reply() {
+        let state_proof = StateProof::from_reply(TEST_GET_NYM_REPLY.as_bytes()).unwrap();
+        assert_eq!(state_proof.root_hash, TEST_STATE_ROOT_HASH);
+        assert_eq!(state_proof.multi_signature.value.timestamp, 1585581460);
+        assert_eq!(state_proof.multi_signature.participants.len(), 3);
+        assert!(state_proof.verify_multi_signature_value().is_ok());
+    }
+
+    #[test]
+    fn test_verify_nym_state_proof() {
+        let root_hash = verify_nym_state_proof(TEST_GET_NYM_REPLY.as_bytes()).unwrap();
+        assert_eq!(root_hash, TEST_STATE_ROOT_HASH);
+    }
+
+    #[test]
+    fn test_verify_nym_state_proof_tampered_data() {
+        // Replace the role in the NYM data: the proven state value no longer matches.
+        let tampered = TEST_GET_NYM_REPLY.replace(r#"\"role\":\"101\""#, r#"\"role\":\"0\""#);
+        assert_ne!(tampered, TEST_GET_NYM_REPLY);
+        assert!(matches!(
+            verify_nym_state_proof(tampered.as_bytes()),
+            Err(TrustchainSovrinError::InvalidStateProof(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_nym_reply() {
+        // The state proof is valid but its multi-signature is not by the validators.
+        let validators = ValidatorKeys::from_genesis(TEST_GENESIS_TRANSACTIONS).unwrap();
+        assert!(matches!(
+            verify_nym_reply(TEST_GET_NYM_REPLY.as_bytes(), &validators),
+            Err(TrustchainSovrinError::InvalidStateProof(_))
+        ));
+        // Without validator keys no reply can be verified.
+        assert!(
+            verify_nym_reply(TEST_GET_NYM_REPLY.as_bytes(), &ValidatorKeys::default()).is_err()
+        );
+
+        // Signed by a quorum of the pool, the reply is verified.
+        let pool = TestPool::new(4);
+        let validators = ValidatorKeys::from_genesis(&pool.genesis()).unwrap();
+        let reply = pool.sign_reply(TEST_GET_NYM_REPLY, 3);
+        assert_eq!(
+            verify_nym_reply(reply.as_bytes(), &validators).unwrap(),
+            TEST_STATE_ROOT_HASH
+        );
+        let reply = pool.sign_reply(TEST_GET_NYM_REPLY, 2);
+        assert!(matches!(
+            verify_nym_reply(reply.as_bytes(), &validators),
+            Err(TrustchainSovrinError::InvalidStateProof(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_attrib_reply() {
+        let pool = TestPool::new(4);
+        let validators = ValidatorKeys::from_genesis(&pool.genesis()).unwrap();
+        let reply = pool.sign_reply(TEST_GET_ATTRIB_REPLY, 4);
+        assert_eq!(
+            verify_attrib_reply(reply.as_bytes(), &validators).unwrap(),
+            TEST_ATTRIB_STATE_ROOT_HASH
+        );
+        let reply = pool.sign_reply(TEST_GET_ENDPOINT_REPLY, 3);
+        assert_eq!(
+            verify_attrib_reply(reply.as_bytes(), &validators).unwrap(),
+            TEST_ENDPOINT_STATE_ROOT_HASH
+        );
+    }
+
+    #[test]
+    fn test_verify_value_wrong_key() {
+        let state_proof = StateProof::from_reply(TEST_GET_NYM_REPLY.as_bytes()).unwrap();
+        let data = nym_data(TEST_GET_NYM_REPLY.as_bytes()).unwrap();
+        // The proof does not cover the state key of another DID.
+        let result = state_proof.verify_value(&nym_state_key("V4SGRU86Z58d6TV7PBUe6f"), &data);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_verify_attrib_state_proof() {
+        let root_hash = verify_attrib_state_proof(TEST_GET_ATTRIB_REPLY.as_bytes()).unwrap();
+        assert_eq!(root_hash, TEST_ATTRIB_STATE_ROOT_HASH);
+
+        // Tampering with the attribute changes its hash.
+        let tampered = TEST_GET_ATTRIB_REPLY.replace("did:ion:test:", "did:ion:");
+        assert!(matches!(
+            verify_attrib_state_proof(tampered.as_bytes()),
+            Err(TrustchainSovrinError::InvalidStateProof(_))
+        ));
+
+        // The attribute is in the state, so it cannot be claimed absent.
+        let mut reply: Value = serde_json::from_str(TEST_GET_ATTRIB_REPLY).unwrap();
+        reply["result"]["data"] = Value::Null;
+        assert!(matches!(
+            verify_attrib_state_proof(reply.to_string().as_bytes()),
+            Err(TrustchainSovrinError::InvalidStateProof(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_schema_state_proof() {
+        let root_hash = verify_schema_state_proof(TEST_GET_SCHEMA_REPLY.as_bytes()).unwrap();
+        assert_eq!(root_hash, "CCLF23Dik5wJXhPcNq4ii3KcphjQQPWGMSGyWe27pWiV");
+
+        // Tampering with the attribute names is detected.
+        let tampered = TEST_GET_SCHEMA_REPLY.replace(r#""role""#, r#""salary""#);
+        assert!(matches!(
+            verify_schema_state_proof(tampered.as_bytes()),
+            Err(TrustchainSovrinError::InvalidStateProof(_))
+        ));
+
+        // The schema is in the state, so it cannot be claimed absent.
+        let mut reply: Value = serde_json::from_str(TEST_GET_SCHEMA_REPLY).unwrap();
+        reply["result"]["data"]
+            .as_object_mut()
+            .unwrap()
+            .remove(ATTR_NAMES_KEY);
+        assert!(matches!(
+            verify_schema_state_proof(reply.to_string().as_bytes()),
+            Err(TrustchainSovrinError::InvalidStateProof(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_cred_def_state_proof() {
+        let root_hash = verify_cred_def_state_proof(TEST_GET_CRED_DEF_REPLY.as_bytes()).unwrap();
+        assert_eq!(root_hash, "Buo1Ajh9ae6XngvMmFxuvm4GbR1SSQsZeXVua8qZpYuh");
+
+        // The credential definition must be on the referenced schema.
+        let tampered = TEST_GET_CRED_DEF_REPLY.replace(r#""ref": 31050"#, r#""ref": 31049"#);
+        assert_ne!(tampered, TEST_GET_CRED_DEF_REPLY);
+        assert!(matches!(
+            verify_cred_def_state_proof(tampered.as_bytes()),
+            Err(TrustchainSovrinError::InvalidStateProof(_))
+        ));
+
+        // Tampering with the key values is detected.
+        let tampered = TEST_GET_CRED_DEF_REPLY.replace(r#""n": "779""#, r#""n": "781""#);
+        assert_ne!(tampered, TEST_GET_CRED_DEF_REPLY);
+        assert!(matches!(
+            verify_cred_def_state_proof(tampered.as_bytes()),
+            Err(TrustchainSovrinError::InvalidStateProof(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_absent() {
+        let state_proof = StateProof::from_reply(TEST_GET_ATTRIB_REPLY.as_bytes()).unwrap();
+        assert!(state_proof
+            .verify_absent(&attrib_state_key("BF4pE6ugcqNWt5kpJUE1Ro", "endpoint"))
+            .is_ok());
+        assert!(state_proof
+            .verify_absent(&attrib_state_key("BF4pE6ugcqNWt5kpJUE1Ro", "trustchain"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_verify_multi_signature_value_wrong_root() {
+        let mut state_proof = StateProof::from_reply(TEST_GET_NYM_REPLY.as_bytes()).unwrap();
+        state_proof.multi_signature.value.state_root_hash =
+            "6D2nTi4Yifzu9mygqoQThhYkec96JWf81ow6CGjxr5Nm".to_string();
+        assert!(state_proof.verify_multi_signature_value().is_err());
+    }
+
+    #[test]
+    fn test_decode_hex_prefix() {
+        // Odd length leaf path.
+        assert_eq!(
+            decode_hex_prefix(&[0x3a, 0xbc]).unwrap(),
+            (vec![0xa, 0xb, 0xc], true)
+        );
+        // Even length extension path.
+        assert_eq!(
+            decode_hex_prefix(&[0x00, 0xab]).unwrap(),
+            (vec![0xa, 0xb], false)
+        );
+    }
+}
@@ -0,0 +1,228 @@
+//! Utils module.
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use ssi::jwk::{Base64urlUInt, OctetParams, Params, JWK};
+
+use crate::{TrustchainSovrinError, DATA_KEY, DEST_KEY, RESULT_KEY, VERKEY_KEY};
+
+/// Extracts the result object from a raw ledger reply.
+pub fn reply_result(reply: &[u8]) -> Result<Value, TrustchainSovrinError> {
+    let reply: Value =
+        serde_json::from_slice(reply).map_err(|_| TrustchainSovrinError::InvalidLedgerAnswer)?;
+    match reply.get(RESULT_KEY) {
+        Some(result @ Value::Object(_)) => Ok(result.to_owned()),
+        _ => Err(TrustchainSovrinError::InvalidLedgerAnswer),
+    }
+}
+
+/// Extracts the NYM transaction data from a raw GET_NYM ledger reply.
+///
+/// The data is returned exactly as committed to by the ledger state, i.e. with a possibly
+/// abbreviated verification key.
+pub fn nym_data(reply: &[u8]) -> Result<Value, TrustchainSovrinError> {
+    match reply_result(reply)?.get(DATA_KEY) {
+        Some(Value::String(data)) => {
+            serde_json::from_str(data).map_err(|_| TrustchainSovrinError::InvalidLedgerAnswer)
+        }
+        _ => Err(TrustchainSovrinError::InvalidLedgerAnswer),
+    }
+}
+
+/// Extracts the NYM transaction data from a raw GET_NYM ledger reply, with the
+/// verification key expanded to its full base58 form.
+pub fn expanded_nym_data(reply: &[u8]) -> Result<Value, TrustchainSovrinError> {
+    let mut data = nym_data(reply)?;
+    let dest = match data.get(DEST_KEY) {
+        Some(Value::String(dest)) => dest.to_owned(),
+        _ => return Err(TrustchainSovrinError::InvalidLedgerAnswer),
+    };
+    if let Some(Value::String(verkey)) = data.get(VERKEY_KEY) {
+        let full_verkey = expand_verkey(&dest, verkey)?;
+        data[VERKEY_KEY] = Value::String(full_verkey);
+    }
+    Ok(data)
+}
+
+/// Expands an abbreviated (`~` prefixed) Indy verification key to its full base58 form.
+///
+/// An abbreviated verkey holds the last 16 bytes of the key, the first 16 bytes being the
+/// DID itself. Full verkeys are returned unchanged.
+pub fn expand_verkey(did: &str, verkey: &str) -> Result<String, TrustchainSovrinError> {
+    let abbreviated = match verkey.strip_prefix('~') {
+        Some(abbreviated) => abbreviated,
+        None => return Ok(verkey.to_string()),
+    };
+    let suffix = did.rsplit(':').next().unwrap_or(did);
+    let mut key = bs58::decode(suffix)
+        .into_vec()
+        .map_err(|_| TrustchainSovrinError::CouldNotConvert)?;
+    key.extend(
+        bs58::decode(abbreviated)
+            .into_vec()
+            .map_err(|_| TrustchainSovrinError::CouldNotConvert)?,
+    );
+    Ok(bs58::encode(key).into_string())
+}
+
+/// Gets the base58-encoded Indy verification key of an Ed25519 JWK.
+pub fn ed25519_verkey(key: &JWK) -> Result<String, TrustchainSovrinError> {
+    match &key.params {
+        Params::OKP(params) if params.curve == "Ed25519" => {
+            Ok(bs58::encode(&params.public_key.0).into_string())
+        }
+        _ => Err(TrustchainSovrinError::InvalidKey(
+            "Expected an Ed25519 OKP key.".to_string(),
+        )),
+    }
+}
+
+/// Gets the Ed25519 public JWK of a base58-encoded Indy verification key.
+pub fn verkey_jwk(verkey: &str) -> Result<JWK, TrustchainSovrinError> {
+    let verkey_bytes = bs58::decode(verkey)
+        .into_vec()
+        .map_err(|e| TrustchainSovrinError::InvalidKey(e.to_string()))?;
+    if verkey_bytes.len() != 32 {
+        return Err(TrustchainSovrinError::InvalidKey(format!(
+            "Expected a 32 byte verification key, got {} bytes.",
+            verkey_bytes.len()
+        )));
+    }
+    Ok(JWK::from(Params::OKP(OctetParams {
+        curve: "Ed25519".to_string(),
+        public_key: Base64urlUInt(verkey_bytes),
+        private_key: None,
+    })))
+}
+
+/// Derives the Sovrin DID (NYM identifier) of a base58-encoded verification key: the base58
+/// encoding of its first 16 bytes.
+pub fn did_from_verkey(verkey: &str) -> Result<String, TrustchainSovrinError> {
+    let verkey_bytes = bs58::decode(verkey)
+        .into_vec()
+        .map_err(|e| TrustchainSovrinError::InvalidKey(e.to_string()))?;
+    if verkey_bytes.len() != 32 {
+        return Err(TrustchainSovrinError::InvalidKey(format!(
+            "Expected a 32 byte verification key, got {} bytes.",
+            verkey_bytes.len()
+        )));
+    }
+    Ok(bs58::encode(&verkey_bytes[..16]).into_string())
+}
+
+/// Computes the ledger state key under which the NYM for the given DID is stored.
+pub fn nym_state_key(dest: &str) -> Vec<u8> {
+    Sha256::digest(dest.as_bytes()).to_vec()
+}
+
+/// Computes the ledger state key under which the raw attribute `name` of the given DID is
+/// stored.
+pub fn attrib_state_key(dest: &str, name: &str) -> Vec<u8> {
+    format!("{}:1:{}", dest, hex_digest(name.as_bytes())).into_bytes()
+}
+
+/// Computes the ledger state key under which the schema with the given name and version,
+/// written by the given DID, is stored.
+pub fn schema_state_key(dest: &str, name: &str, version: &str) -> Vec<u8> {
+    format!("{}:2:{}:{}", dest, name, version).into_bytes()
+}
+
+/// Computes the ledger state key under which the credential definition with the given
+/// signature type and tag, written by the given DID on the schema with the given sequence
+/// number, is stored.
+pub fn cred_def_state_key(
+    origin: &str,
+    signature_type: &str,
+    schema_ref: u64,
+    tag: &str,
+) -> Vec<u8> {
+    format!("{}:3:{}:{}:{}", origin, signature_type, schema_ref, tag).into_bytes()
+}
+
+/// Gets the hex-encoded SHA-256 digest of some data.
+pub fn hex_digest(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::TEST_GET_NYM_REPLY;
+
+    #[test]
+    fn test_ed25519_verkey() {
+        let key = JWK::generate_ed25519().unwrap();
+        let verkey = ed25519_verkey(&key).unwrap();
+        assert_eq!(bs58::decode(verkey).into_vec().unwrap().len(), 32);
+        assert!(matches!(
+            ed25519_verkey(&JWK::generate_secp256k1().unwrap()),
+            Err(TrustchainSovrinError::InvalidKey(_))
+        ));
+        assert_eq!(verkey_jwk(&verkey).unwrap(), key.to_public());
+        assert!(matches!(
+            verkey_jwk("AZwZEje9BYYr2dbU7WUgus"),
+            Err(TrustchainSovrinError::InvalidKey(_))
+        ));
+    }
+
+    #[test]
+    fn test_did_from_verkey() {
+        assert_eq!(
+            did_from_verkey("AZwZEje9BYYr2dbU7WUgussLnagnGTQDoeqgYmPqWLTb").unwrap(),
+            "JZBDcz3cJyNtzMGCLVKAkJ"
+        );
+        assert!(matches!(
+            did_from_verkey("JZBDcz3cJyNtzMGCLVKAkJ"),
+            Err(TrustchainSovrinError::InvalidKey(_))
+        ));
+    }
+
+    #[test]
+    fn test_attrib_state_key() {
+        assert_eq!(
+            attrib_state_key("BF4pE6ugcqNWt5kpJUE1Ro", "trustchain"),
+            b"BF4pE6ugcqNWt5kpJUE1Ro:1:9991d650bd700b85f15ec25e0d0275cfa988a4401378b9e3b95c8fe8d1a5b61e"
+                .to_vec()
+        );
+    }
+
+    #[test]
+    fn test_ledger_object_state_keys() {
+        assert_eq!(
+            schema_state_key("BF4pE6ugcqNWt5kpJUE1Ro", "trustchain-employee", "1.0"),
+            b"BF4pE6ugcqNWt5kpJUE1Ro:2:trustchain-employee:1.0".to_vec()
+        );
+        assert_eq!(
+            cred_def_state_key("BF4pE6ugcqNWt5kpJUE1Ro", "CL", 31050, "default"),
+            b"BF4pE6ugcqNWt5kpJUE1Ro:3:CL:31050:default".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_expand_verkey() {
+        let expanded = expand_verkey("BF4pE6ugcqNWt5kpJUE1Ro", "~2861q8nPGS7nv53JP5TPYt").unwrap();
+        assert_eq!(expanded, "6anbDQNCcVh2f6okexjaX1VGj6tEnizJ1kV5UTBS8Zhi");
+
+        // Qualified DIDs and full verkeys are also handled.
+        let expanded =
+            expand_verkey("did:sov:BF4pE6ugcqNWt5kpJUE1Ro", "~2861q8nPGS7nv53JP5TPYt").unwrap();
+        assert_eq!(expanded, "6anbDQNCcVh2f6okexjaX1VGj6tEnizJ1kV5UTBS8Zhi");
+        let full = "6anbDQNCcVh2f6okexjaX1VGj6tEnizJ1kV5UTBS8Zhi";
+        assert_eq!(expand_verkey("BF4pE6ugcqNWt5kpJUE1Ro", full).unwrap(), full);
+    }
+
+    #[test]
+    fn test_nym_data() {
+        let data = nym_data(TEST_GET_NYM_REPLY.as_bytes()).unwrap();
+        assert_eq!(data[DEST_KEY], "BF4pE6ugcqNWt5kpJUE1Ro");
+        assert_eq!(data[VERKEY_KEY], "~2861q8nPGS7nv53JP5TPYt");
+
+        let data = expanded_nym_data(TEST_GET_NYM_REPLY.as_bytes()).unwrap();
+        assert_eq!(
+            data[VERKEY_KEY],
+            "6anbDQNCcVh2f6okexjaX1VGj6tEnizJ1kV5UTBS8Zhi"
+        );
+    }
+}
@@ -1,52 +1,257 @@
+//! Implementation of `Verifier` API for the Sovrin DID method.
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use ssi::did::Document;
+use ssi::did_resolve::{
+    DIDResolver, DocumentMetadata, ResolutionInputMetadata, ResolutionMetadata,
+};
 use std::collections::HashMap;
 use std::marker::PhantomData;
+use std::path::Path;
 use std::sync::{Arc, Mutex};
-use ssi::did::Document;
-use ssi::did_resolve::{ DocumentMetadata};
-use serde::{Deserialize, Serialize};
-use trustchain_core::resolver::{TrustchainResolver, ResolverError};
-use trustchain_core::verifier::{VerifierError};
+use trustchain_core::chain::DEFAULT_MAX_CHAIN_DEPTH;
+use trustchain_core::commitment::{
+    CommitmentError, DIDCommitment, TimestampCommitment, TrivialCommitment,
+};
+use trustchain_core::policy::TrustPolicy;
+use trustchain_core::resolver::TrustchainResolver;
+use trustchain_core::utils::current_time;
+use trustchain_core::verifier::{Timestamp, VerifiableTimestamp, Verifier, VerifierError};
 
-use crate::FullClient;
-use crate::resolver::{SovrinResolver};
+use crate::attrib::{add_controller_proof, ControllerProof, TRUSTCHAIN_ATTRIB_NAME};
+use crate::commitment::{NymCommitment, NymTimestampCommitment};
+use crate::endpoint::ENDPOINT_ATTRIB_NAME;
+use crate::genesis::ValidatorKeys;
+use crate::network::SovrinNetwork;
+use crate::resolver::{resolve_did_replies, SovrinResolver};
+use crate::state_proof::{verify_attrib_reply, verify_nym_reply, StateProof};
+use crate::utils::reply_result;
+use crate::{FullClient, LightClient, TrustchainSovrinError, DEST_KEY, RAW_KEY};
 
+/// Default maximum age, in seconds, of the state proofs in a verification bundle fetched from an
+/// untrusted source. Validators refresh the signed ledger state every few minutes.
+pub const DEFAULT_MAX_BUNDLE_AGE: u64 = 3600;
 
 /// Data bundle for DID timestamp verification.
-/// TODO: fill with what is needed for the verification.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct VerificationBundle {
     /// DID Document.
     did_doc: Document,
+    /// DID Document Metadata.
     did_doc_meta: DocumentMetadata,
+    /// Raw GET_NYM ledger reply, including the state proof.
+    ledger_reply: String,
+    /// Raw GET_ATTRIB ledger reply for the Trustchain controller proof, including the state
+    /// proof.
+    attrib_reply: String,
+    /// Raw GET_ATTRIB ledger reply for the service endpoint, including the state proof.
+    endpoint_reply: String,
+}
+
+impl VerificationBundle {
+    pub fn new(
+        did_doc: Document,
+        did_doc_meta: DocumentMetadata,
+        ledger_reply: String,
+        attrib_reply: String,
+        endpoint_reply: String,
+    ) -> Self {
+        Self {
+            did_doc,
+            did_doc_meta,
+            ledger_reply,
+            attrib_reply,
+            endpoint_reply,
+        }
+    }
+
+    /// Checks a bundle received from an untrusted source for the given DID: all ledger replies
+    /// must be for the DID and proven by state proofs signed by a quorum of the validators at
+    /// most `max_age` seconds ago.
+    ///
+    /// Returns the bundle with the DID document and metadata rebuilt from the ledger replies.
+    pub fn verify(
+        self,
+        did: &str,
+        validators: &ValidatorKeys,
+        max_age: u64,
+    ) -> Result<Self, TrustchainSovrinError> {
+        self.verify_at(did, validators, max_age, current_time())
+    }
+
+    /// Checks a bundle as in [`verify`](Self::verify), at the given Unix time in seconds.
+    pub fn verify_at(
+        self,
+        did: &str,
+        validators: &ValidatorKeys,
+        max_age: u64,
+        time: Timestamp,
+    ) -> Result<Self, TrustchainSovrinError> {
+        let (network, id) = SovrinNetwork::from_did(did)?;
+        verify_nym_reply(self.ledger_reply.as_bytes(), validators)?;
+        verify_attrib_reply(self.attrib_reply.as_bytes(), validators)?;
+        verify_attrib_reply(self.endpoint_reply.as_bytes(), validators)?;
+        // Replies signed long ago may have been superseded (e.g. by a key rotation).
+        self.check_freshness(max_age, time)?;
+        if reply_result(self.ledger_reply.as_bytes())?.get(DEST_KEY) != Some(&Value::from(id)) {
+            return Err(TrustchainSovrinError::InvalidLedgerAnswer);
+        }
+        check_attrib_target(&self.attrib_reply, id, TRUSTCHAIN_ATTRIB_NAME)?;
+        check_attrib_target(&self.endpoint_reply, id, ENDPOINT_ATTRIB_NAME)?;
+        let (did_doc, did_doc_meta) =
+            resolve_did_replies(&self.ledger_reply, &self.endpoint_reply, network)?;
+        Ok(Self::new(
+            did_doc,
+            did_doc_meta,
+            self.ledger_reply,
+            self.attrib_reply,
+            self.endpoint_reply,
+        ))
+    }
+
+    /// Checks that the state proofs of all ledger replies were signed by the validators at most
+    /// `max_age` seconds before the given Unix time.
+    pub fn check_freshness(
+        &self,
+        max_age: u64,
+        time: Timestamp,
+    ) -> Result<(), TrustchainSovrinError> {
+        for reply in [&self.ledger_reply, &self.attrib_reply, &self.endpoint_reply] {
+            let signed_at = StateProof::from_reply(reply.as_bytes())?
+                .multi_signature
+                .value
+                .timestamp;
+            if time.saturating_sub(signed_at) > max_age {
+                return Err(TrustchainSovrinError::StaleStateProof(signed_at, max_age));
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets the DID document and metadata with the controller proof (if any) in the bundle.
+    pub fn resolve(&self) -> Result<(Document, DocumentMetadata), TrustchainSovrinError> {
+        add_controller_proof(
+            self.did_doc.clone(),
+            self.did_doc_meta.clone(),
+            ControllerProof::from_attrib_reply(self.attrib_reply.as_bytes())?,
+        )
+    }
 }
 
+/// Checks that a raw GET_ATTRIB ledger reply is for the given raw attribute of the DID with the
+/// given ID.
+fn check_attrib_target(reply: &str, id: &str, name: &str) -> Result<(), TrustchainSovrinError> {
+    let result = reply_result(reply.as_bytes())?;
+    if result.get(DEST_KEY) != Some(&Value::from(id))
+        || result.get(RAW_KEY) != Some(&Value::from(name))
+    {
+        return Err(TrustchainSovrinError::InvalidLedgerAnswer);
+    }
+    Ok(())
+}
 
-pub struct TrustchainVerifier<U = FullClient>
-{
-    resolver: SovrinResolver,
-    bundles: Mutex<HashMap<String, Arc<VerificationBundle>>>,
+/// Trustchain Verifier implementation via the Sovrin DID method.
+pub struct TrustchainVerifier<U = FullClient, R = SovrinResolver> {
+    resolver: R,
+    bundles: Arc<Mutex<HashMap<String, Arc<VerificationBundle>>>>,
+    max_bundle_age: u64,
+    max_chain_depth: usize,
+    policy: Option<TrustPolicy>,
     _marker: PhantomData<U>,
 }
 
-impl TrustchainVerifier<FullClient>
-{
+impl<U, R> TrustchainVerifier<U, R> {
+    /// Sets the maximum number of DIDs in a verified chain.
+    pub fn with_max_chain_depth(mut self, max_chain_depth: usize) -> Self {
+        self.max_chain_depth = max_chain_depth;
+        self
+    }
+
+    /// Sets the trust policy evaluated against verified chains.
+    pub fn with_policy(mut self, policy: TrustPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+}
+
+impl TrustchainVerifier<FullClient> {
     /// Constructs a new Sovrin Verifier.
     pub fn new(resolver: SovrinResolver) -> Self {
-        let bundles = Mutex::new(HashMap::new());
+        let bundles = Arc::new(Mutex::new(HashMap::new()));
         Self {
             resolver,
             bundles,
+            max_bundle_age: DEFAULT_MAX_BUNDLE_AGE,
+            max_chain_depth: DEFAULT_MAX_CHAIN_DEPTH,
+            policy: None,
             _marker: PhantomData,
         }
     }
 
+    /// Sets the maximum age, in seconds, of the state proofs in cached bundles, after which a
+    /// bundle is fetched again.
+    pub fn with_max_bundle_age(mut self, max_bundle_age: u64) -> Self {
+        self.max_bundle_age = max_bundle_age;
+        self
+    }
+
+    /// Gets the Sovrin resolver of the verifier.
+    pub fn sovrin_resolver(&self) -> &SovrinResolver {
+        &self.resolver
+    }
+
     /// Fetches the data needed to verify the DID's timestamp and stores it as a verification bundle.
     pub async fn fetch_bundle(&self, did: &str) -> Result<(), VerifierError> {
-        let (did_doc, did_doc_meta) = self.resolve_did(did).await?;
-        let bundle = VerificationBundle {
+        let ledger_reply = self
+            .resolver
+            .wrapped_resolver
+            .fetch_nym(did)
+            .await
+            .map_err(|e| {
+                VerifierError::ErrorFetchingVerificationMaterial(
+                    format!("Error fetching NYM for DID: {}", did),
+                    e.into(),
+                )
+            })?;
+        let attrib_reply = self
+            .resolver
+            .wrapped_resolver
+            .fetch_attrib(did, TRUSTCHAIN_ATTRIB_NAME)
+            .await
+            .map_err(|e| {
+                VerifierError::ErrorFetchingVerificationMaterial(
+                    format!("Error fetching controller proof ATTRIB for DID: {}", did),
+                    e.into(),
+                )
+            })?;
+        let endpoint_reply = self
+            .resolver
+            .wrapped_resolver
+            .fetch_attrib(did, ENDPOINT_ATTRIB_NAME)
+            .await
+            .map_err(|e| {
+                VerifierError::ErrorFetchingVerificationMaterial(
+                    format!("Error fetching endpoint ATTRIB for DID: {}", did),
+                    e.into(),
+                )
+            })?;
+        // Construct the document from the same replies, so it is covered by the state proofs.
+        let (did_doc, did_doc_meta) = SovrinNetwork::from_did(did)
+            .and_then(|(network, _)| resolve_did_replies(&ledger_reply, &endpoint_reply, network))
+            .map_err(|e| {
+                VerifierError::ErrorFetchingVerificationMaterial(
+                    format!("Error resolving NYM reply for DID: {}", did),
+                    e.into(),
+                )
+            })?;
+        let bundle = VerificationBundle::new(
             did_doc,
             did_doc_meta,
-        };
+            ledger_reply,
+            attrib_reply,
+            endpoint_reply,
+        );
         // Insert the bundle into the HashMap of bundles, keyed by the DID.
         self.bundles
             .lock()
@@ -60,22 +265,703 @@ impl TrustchainVerifier<FullClient>
         &self,
         did: &str,
     ) -> Result<Arc<VerificationBundle>, VerifierError> {
-        // Fetch (and store) the bundle if it isn't already available.
-        if !self.bundles.lock().unwrap().contains_key(did) {
-            self.fetch_bundle(did).await?;
+        // Fetch (and store) the bundle if it isn't already available or has become stale.
+        if let Some(bundle) = cached_bundle(&self.bundles, did, self.max_bundle_age, current_time())
+        {
+            return Ok(bundle);
         }
+        self.fetch_bundle(did).await?;
         Ok(self.bundles.lock().unwrap().get(did).cloned().unwrap())
     }
-    /// Resolves the given DID to obtain the DID Document and Document Metadata.
-    async fn resolve_did(&self, did: &str) -> Result<(Document, DocumentMetadata), VerifierError> {
-        let (res_meta, doc, doc_meta) = self.resolver.resolve_as_result(did).await?;
-        if let (Some(doc), Some(doc_meta)) = (doc, doc_meta) {
-            Ok((doc, doc_meta))
-        } else {
-            Err(VerifierError::DIDResolutionError(
-                format!("Missing Document and/or DocumentMetadata for DID: {}", did),
-                ResolverError::FailureWithMetadata(res_meta).into(),
+}
+
+/// A Trustchain resolver for Sovrin light clients, resolving DIDs from the verification
+/// bundles served by a Trustchain HTTP server.
+///
+/// The ledger replies in a bundle are only accepted with state proofs signed by a quorum of
+/// the validators listed in the genesis transactions, so the server need not be trusted and
+/// no connection to the validator pool is needed.
+pub struct BundleResolver {
+    endpoint: String,
+    validators: HashMap<SovrinNetwork, ValidatorKeys>,
+    max_bundle_age: u64,
+    bundles: Arc<Mutex<HashMap<String, Arc<VerificationBundle>>>>,
+}
+
+impl BundleResolver {
+    /// Constructs a resolver with no networks, fetching bundles from the given endpoint.
+    pub fn new(endpoint: &str) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            validators: HashMap::new(),
+            max_bundle_age: DEFAULT_MAX_BUNDLE_AGE,
+            bundles: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Sets the maximum age, in seconds, of the state proofs in accepted bundles.
+    pub fn with_max_bundle_age(mut self, max_bundle_age: u64) -> Self {
+        self.max_bundle_age = max_bundle_age;
+        self
+    }
+
+    /// Adds (or replaces) a network, given its genesis transactions.
+    pub fn with_network(
+        mut self,
+        network: SovrinNetwork,
+        genesis: &str,
+    ) -> Result<Self, TrustchainSovrinError> {
+        self.validators
+            .insert(network, ValidatorKeys::from_genesis(genesis)?);
+        Ok(self)
+    }
+
+    /// Adds (or replaces) a network, given the path to its genesis transactions file.
+    pub fn with_network_file<P: AsRef<Path>>(
+        self,
+        network: SovrinNetwork,
+        path: P,
+    ) -> Result<Self, TrustchainSovrinError> {
+        let genesis = std::fs::read_to_string(path.as_ref()).map_err(|e| {
+            TrustchainSovrinError::InvalidGenesis(format!(
+                "Failed to read {}: {}",
+                path.as_ref().display(),
+                e
             ))
+        })?;
+        self.with_network(network, &genesis)
+    }
+
+    /// Gets the endpoint bundles are fetched from.
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Gets the BLS keys of the validators of the given network.
+    pub fn validators(
+        &self,
+        network: SovrinNetwork,
+    ) -> Result<&ValidatorKeys, TrustchainSovrinError> {
+        self.validators
+            .get(&network)
+            .ok_or_else(|| TrustchainSovrinError::NetworkNotConfigured(network.to_string()))
+    }
+
+    /// Fetches the verification bundle of a DID from the endpoint and stores it, after
+    /// verifying its ledger replies.
+    pub async fn fetch_bundle(&self, did: &str) -> Result<(), TrustchainSovrinError> {
+        let (network, _) = SovrinNetwork::from_did(did)?;
+        let validators = self.validators(network)?;
+        let url = format!("{}did/bundle/{}", self.endpoint, did);
+        let response = reqwest::get(&url)
+            .await
+            .map_err(|e| TrustchainSovrinError::BundleRequest(format!("{}: {}", url, e)))?
+            .text()
+            .await
+            .map_err(|e| TrustchainSovrinError::BundleRequest(format!("{}: {}", url, e)))?;
+        let bundle: VerificationBundle = serde_json::from_str(&response)
+            .map_err(|e| TrustchainSovrinError::BundleRequest(format!("{}: {}", url, e)))?;
+        let bundle = bundle.verify(did, validators, self.max_bundle_age)?;
+        // Insert the bundle into the HashMap of bundles, keyed by the DID.
+        self.bundles
+            .lock()
+            .unwrap()
+            .insert(did.to_string(), Arc::new(bundle));
+        Ok(())
+    }
+
+    /// Gets a DID verification bundle, including a fetch if not initially cached.
+    pub async fn verification_bundle(
+        &self,
+        did: &str,
+    ) -> Result<Arc<VerificationBundle>, TrustchainSovrinError> {
+        // Fetch (and store) the bundle if it isn't already available or has become stale.
+        if let Some(bundle) = cached_bundle(&self.bundles, did, self.max_bundle_age, current_time())
+        {
+            return Ok(bundle);
+        }
+        self.fetch_bundle(did).await?;
+        Ok(self.bundles.lock().unwrap().get(did).cloned().unwrap())
+    }
+}
+
+#[async_trait]
+impl DIDResolver for BundleResolver {
+    async fn resolve(
+        &self,
+        did: &str,
+        _input_metadata: &ResolutionInputMetadata,
+    ) -> (
+        ResolutionMetadata,
+        Option<Document>,
+        Option<DocumentMetadata>,
+    ) {
+        let result = match self.verification_bundle(did).await {
+            Ok(bundle) => bundle.resolve(),
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok((doc, doc_meta)) => (
+                ResolutionMetadata {
+                    error: None,
+                    content_type: None,
+                    property_set: None,
+                },
+                Some(doc),
+                Some(doc_meta),
+            ),
+            Err(e) => (
+                ResolutionMetadata {
+                    error: Some(format!("Error while resolving DID : {}", e)),
+                    content_type: None,
+                    property_set: None,
+                },
+                None,
+                None,
+            ),
         }
     }
-}
\ No newline at end of file
+}
+
+#[async_trait]
+impl TrustchainResolver for BundleResolver {
+    fn wrapped_resolver(&self) -> &dyn DIDResolver {
+        self
+    }
+}
+
+impl TrustchainVerifier<LightClient, BundleResolver> {
+    /// Constructs a new Sovrin light client Verifier, fetching verification bundles from the
+    /// Trustchain HTTP server at the given endpoint.
+    pub fn with_endpoint(resolver: BundleResolver) -> Self {
+        let bundles = resolver.bundles.clone();
+        let max_bundle_age = resolver.max_bundle_age;
+        Self {
+            resolver,
+            bundles,
+            max_bundle_age,
+            max_chain_depth: DEFAULT_MAX_CHAIN_DEPTH,
+            policy: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Gets a DID verification bundle, including a fetch if not initially cached.
+    pub async fn verification_bundle(
+        &self,
+        did: &str,
+    ) -> Result<Arc<VerificationBundle>, VerifierError> {
+        self.resolver.verification_bundle(did).await.map_err(|e| {
+            VerifierError::ErrorFetchingVerificationMaterial(
+                format!(
+                    "Error fetching bundle for DID {} from endpoint: {}",
+                    did,
+                    self.resolver.endpoint()
+                ),
+                e.into(),
+            )
+        })
+    }
+}
+
+/// Gets the cached verification bundle of a DID, unless its state proofs are older than the
+/// maximum age at the given time.
+fn cached_bundle(
+    bundles: &Mutex<HashMap<String, Arc<VerificationBundle>>>,
+    did: &str,
+    max_bundle_age: u64,
+    time: Timestamp,
+) -> Option<Arc<VerificationBundle>> {
+    bundles
+        .lock()
+        .unwrap()
+        .get(did)
+        .filter(|bundle| bundle.check_freshness(max_bundle_age, time).is_ok())
+        .cloned()
+}
+
+/// Converts a VerificationBundle into a NymCommitment.
+pub fn construct_commitment(
+    bundle: Arc<VerificationBundle>,
+) -> Result<NymCommitment, CommitmentError> {
+    NymCommitment::new(
+        bundle.did_doc.clone(),
+        bundle.ledger_reply.as_bytes().to_vec(),
+    )
+}
+
+/// Checks that the state proof of a verification bundle is for the given state root, signed
+/// by a quorum of the validators.
+pub fn validate_state_root(
+    bundle: &VerificationBundle,
+    state_root: &str,
+    validators: &ValidatorKeys,
+) -> Result<(), VerifierError> {
+    let state_proof = StateProof::from_reply(bundle.ledger_reply.as_bytes())
+        .map_err(|e| VerifierError::InvalidStateProof(e.to_string()))?;
+    if state_proof.root_hash.ne(state_root) {
+        return Err(VerifierError::InvalidStateProof(format!(
+            "State root {} not proven by bundle for DID: {}",
+            state_root, bundle.did_doc.id
+        )));
+    }
+    state_proof
+        .verify_signature(validators)
+        .map_err(|e| VerifierError::InvalidStateProof(e.to_string()))
+}
+
+/// Verifies the timestamp of a root DID against the expected root timestamp, as in
+/// `Verifier::verify_root_timestamp`, from the verification bundle of the root itself: the
+/// ledger state root committing to the root DID and its timestamp must be proven by the state
+/// proof of the same bundle, signed by a quorum of the validators of its network.
+fn verify_bundle_root_timestamp(
+    root: &str,
+    root_timestamp: Timestamp,
+    bundle: Arc<VerificationBundle>,
+    validators: &ValidatorKeys,
+) -> Result<(), VerifierError> {
+    let verifiable_timestamp = sovrin_timestamp(
+        Box::new(construct_commitment(bundle.clone())?),
+        root_timestamp,
+    )?;
+    let hash = verifiable_timestamp.timestamp_commitment().hash()?;
+    verifiable_timestamp.verify(&hash)?;
+    validate_state_root(&bundle, &hash, validators)?;
+    if verifiable_timestamp.timestamp() != root_timestamp {
+        return Err(VerifierError::InvalidRootTimestamp(
+            root.to_string(),
+            verifiable_timestamp.timestamp(),
+        ));
+    }
+    Ok(())
+}
+
+/// Gets the validators of the network of a Sovrin DID.
+fn did_validators<'a>(
+    did: &str,
+    validators: impl Fn(SovrinNetwork) -> Result<&'a ValidatorKeys, TrustchainSovrinError>,
+) -> Result<&'a ValidatorKeys, VerifierError> {
+    SovrinNetwork::from_did(did)
+        .and_then(|(network, _)| validators(network))
+        .map_err(|e| VerifierError::InvalidStateProof(e.to_string()))
+}
+
+/// Checks that one of the verification bundles proves the given state root, with a state
+/// proof signed by a quorum of the validators of the bundle's network.
+fn validate_bundles_state_root<'a>(
+    bundles: &Mutex<HashMap<String, Arc<VerificationBundle>>>,
+    hash: &str,
+    validators: impl Fn(SovrinNetwork) -> Result<&'a ValidatorKeys, TrustchainSovrinError>,
+) -> Result<(), VerifierError> {
+    let bundles = bundles.lock().unwrap();
+    if bundles.values().any(|bundle| {
+        SovrinNetwork::from_did(&bundle.did_doc.id)
+            .and_then(|(network, _)| validators(network))
+            .is_ok_and(|validators| validate_state_root(bundle, hash, validators).is_ok())
+    }) {
+        Ok(())
+    } else {
+        Err(VerifierError::InvalidStateProof(format!(
+            "No verification bundle proves state root: {}",
+            hash
+        )))
+    }
+}
+
+/// Constructs the verifiable timestamp of a DID from its commitment.
+fn sovrin_timestamp(
+    did_commitment: Box<dyn DIDCommitment>,
+    expected_timestamp: Timestamp,
+) -> Result<Box<dyn VerifiableTimestamp>, VerifierError> {
+    // The ledger timestamp is committed to by the same ledger reply as the DID content.
+    let timestamp_commitment = Box::new(NymTimestampCommitment::new(
+        did_commitment.candidate_data().to_owned(),
+        expected_timestamp,
+    )?);
+    Ok(Box::new(SovrinTimestamp::new(
+        did_commitment,
+        timestamp_commitment,
+    )))
+}
+
+#[async_trait]
+impl Verifier<SovrinResolver> for TrustchainVerifier<FullClient> {
+    /// Sovrin has no proof of work: instead the ledger state root (the commitment target) must
+    /// be signed by a quorum of the validator pool, as attested by the state proof of the
+    /// bundle of the root DID.
+    async fn verify_root_timestamp(
+        &self,
+        root: &str,
+        root_timestamp: Timestamp,
+    ) -> Result<(), VerifierError> {
+        let bundle = self.verification_bundle(root).await?;
+        let validators = did_validators(root, |network| {
+            self.resolver.wrapped_resolver.validators(network)
+        })?;
+        verify_bundle_root_timestamp(root, root_timestamp, bundle, validators)
+    }
+
+    /// Checks the ledger state root is proven by the state proof of one of the fetched bundles.
+    /// Root timestamps are instead verified against the bundle of the root DID itself.
+    fn validate_pow_hash(&self, hash: &str) -> Result<(), VerifierError> {
+        validate_bundles_state_root(&self.bundles, hash, |network| {
+            self.resolver.wrapped_resolver.validators(network)
+        })
+    }
+
+    async fn did_commitment(&self, did: &str) -> Result<Box<dyn DIDCommitment>, VerifierError> {
+        let bundle = self.verification_bundle(did).await?;
+        Ok(construct_commitment(bundle).map(Box::new)?)
+    }
+
+    fn resolver(&self) -> &dyn TrustchainResolver {
+        &self.resolver
+    }
+
+    fn max_chain_depth(&self) -> usize {
+        self.max_chain_depth
+    }
+
+    fn policy(&self) -> Option<&TrustPolicy> {
+        self.policy.as_ref()
+    }
+
+    async fn verifiable_timestamp(
+        &self,
+        did: &str,
+        expected_timestamp: Timestamp,
+    ) -> Result<Box<dyn VerifiableTimestamp>, VerifierError> {
+        sovrin_timestamp(self.did_commitment(did).await?, expected_timestamp)
+    }
+}
+
+#[async_trait]
+impl Verifier<BundleResolver> for TrustchainVerifier<LightClient, BundleResolver> {
+    /// As for the full client, the state root must be signed by a quorum of the validator
+    /// pool, as attested by the state proof of the bundle of the root DID.
+    async fn verify_root_timestamp(
+        &self,
+        root: &str,
+        root_timestamp: Timestamp,
+    ) -> Result<(), VerifierError> {
+        let bundle = self.verification_bundle(root).await?;
+        let validators = did_validators(root, |network| self.resolver.validators(network))?;
+        verify_bundle_root_timestamp(root, root_timestamp, bundle, validators)
+    }
+
+    /// As for the full client, the state root must be proven by one of the fetched bundles.
+    fn validate_pow_hash(&self, hash: &str) -> Result<(), VerifierError> {
+        validate_bundles_state_root(&self.bundles, hash, |network| {
+            self.resolver.validators(network)
+        })
+    }
+
+    async fn did_commitment(&self, did: &str) -> Result<Box<dyn DIDCommitment>, VerifierError> {
+        let bundle = self.verification_bundle(did).await?;
+        Ok(construct_commitment(bundle).map(Box::new)?)
+    }
+
+    fn resolver(&self) -> &dyn TrustchainResolver {
+        &self.resolver
+    }
+
+    fn max_chain_depth(&self) -> usize {
+        self.max_chain_depth
+    }
+
+    fn policy(&self) -> Option<&TrustPolicy> {
+        self.policy.as_ref()
+    }
+
+    async fn verifiable_timestamp(
+        &self,
+        did: &str,
+        expected_timestamp: Timestamp,
+    ) -> Result<Box<dyn VerifiableTimestamp>, VerifierError> {
+        sovrin_timestamp(self.did_commitment(did).await?, expected_timestamp)
+    }
+}
+
+/// Contains the corresponding `DIDCommitment` and `TimestampCommitment` for a given DID.
+pub struct SovrinTimestamp {
+    did_commitment: Box<dyn DIDCommitment>,
+    timestamp_commitment: Box<dyn TimestampCommitment>,
+}
+
+impl SovrinTimestamp {
+    fn new(
+        did_commitment: Box<dyn DIDCommitment>,
+        timestamp_commitment: Box<dyn TimestampCommitment>,
+    ) -> Self {
+        Self {
+            did_commitment,
+            timestamp_commitment,
+        }
+    }
+
+    /// Gets the DID.
+    pub fn did(&self) -> &str {
+        self.did_commitment.did()
+    }
+    /// Gets the DID Document.
+    pub fn did_document(&self) -> &Document {
+        self.did_commitment.did_document()
+    }
+}
+
+impl VerifiableTimestamp for SovrinTimestamp {
+    fn did_commitment(&self) -> &dyn DIDCommitment {
+        self.did_commitment.as_ref()
+    }
+
+    fn timestamp_commitment(&self) -> &dyn TimestampCommitment {
+        self.timestamp_commitment.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{
+        TestPool, TEST_CONTROLLER_DID, TEST_GENESIS_TRANSACTIONS, TEST_GET_ATTRIB_REPLY,
+        TEST_GET_ENDPOINT_REPLY, TEST_GET_NYM_REPLY, TEST_NYM_TXN_TIME, TEST_STATE_ROOT_HASH,
+    };
+    use ssi::one_or_many::OneOrMany;
+    use trustchain_core::utils::HasEndpoints;
+
+    fn test_bundle() -> VerificationBundle {
+        let (did_doc, did_doc_meta) = resolve_did_replies(
+            TEST_GET_NYM_REPLY,
+            TEST_GET_ENDPOINT_REPLY,
+            SovrinNetwork::MainNet,
+        )
+        .unwrap();
+        VerificationBundle::new(
+            did_doc,
+            did_doc_meta,
+            TEST_GET_NYM_REPLY.to_string(),
+            TEST_GET_ATTRIB_REPLY.to_string(),
+            TEST_GET_ENDPOINT_REPLY.to_string(),
+        )
+    }
+
+    /// Gets the test bundle with its ledger replies signed by a quorum of the given pool.
+    fn signed_test_bundle(pool: &TestPool) -> VerificationBundle {
+        let bundle = test_bundle();
+        VerificationBundle::new(
+            bundle.did_doc,
+            bundle.did_doc_meta,
+            pool.sign_reply(TEST_GET_NYM_REPLY, 3),
+            pool.sign_reply(TEST_GET_ATTRIB_REPLY, 3),
+            pool.sign_reply(TEST_GET_ENDPOINT_REPLY, 3),
+        )
+    }
+
+    #[test]
+    fn test_verifiable_timestamp() {
+        let bundle = Arc::new(test_bundle());
+        let did_commitment = Box::new(construct_commitment(bundle).unwrap());
+        let timestamp_commitment = Box::new(
+            NymTimestampCommitment::new(
+                did_commitment.candidate_data().to_owned(),
+                TEST_NYM_TXN_TIME,
+            )
+            .unwrap(),
+        );
+        let verifiable_timestamp = SovrinTimestamp::new(did_commitment, timestamp_commitment);
+
+        let target = verifiable_timestamp.timestamp_commitment().hash().unwrap();
+        assert_eq!(target, TEST_STATE_ROOT_HASH);
+        assert!(verifiable_timestamp.verify(&target).is_ok());
+        assert_eq!(verifiable_timestamp.timestamp(), TEST_NYM_TXN_TIME);
+    }
+
+    #[test]
+    fn test_validate_state_root() {
+        let bundle = test_bundle();
+        let validators = ValidatorKeys::from_genesis(TEST_GENESIS_TRANSACTIONS).unwrap();
+        assert!(matches!(
+            validate_state_root(
+                &bundle,
+                "6D2nTi4Yifzu9mygqoQThhYkec96JWf81ow6CGjxr5Nm",
+                &validators
+            ),
+            Err(VerifierError::InvalidStateProof(_))
+        ));
+        // The fixture state root is not signed by the validators.
+        assert!(matches!(
+            validate_state_root(&bundle, TEST_STATE_ROOT_HASH, &validators),
+            Err(VerifierError::InvalidStateProof(_))
+        ));
+
+        // Signed by a quorum of the pool, the state root is valid.
+        let pool = TestPool::new(4);
+        let validators = ValidatorKeys::from_genesis(&pool.genesis()).unwrap();
+        let bundle = signed_test_bundle(&pool);
+        assert!(validate_state_root(&bundle, TEST_STATE_ROOT_HASH, &validators).is_ok());
+        assert!(matches!(
+            validate_state_root(
+                &bundle,
+                "6D2nTi4Yifzu9mygqoQThhYkec96JWf81ow6CGjxr5Nm",
+                &validators
+            ),
+            Err(VerifierError::InvalidStateProof(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_bundles_state_root() {
+        let pool = TestPool::new(4);
+        let validators = ValidatorKeys::from_genesis(&pool.genesis()).unwrap();
+        let bundles = Mutex::new(HashMap::from([(
+            "did:sov:BF4pE6ugcqNWt5kpJUE1Ro".to_string(),
+            Arc::new(signed_test_bundle(&pool)),
+        )]));
+        assert!(
+            validate_bundles_state_root(&bundles, TEST_STATE_ROOT_HASH, |_| Ok(&validators))
+                .is_ok()
+        );
+        assert!(matches!(
+            validate_bundles_state_root(
+                &bundles,
+                "6D2nTi4Yifzu9mygqoQThhYkec96JWf81ow6CGjxr5Nm",
+                |_| Ok(&validators)
+            ),
+            Err(VerifierError::InvalidStateProof(_))
+        ));
+        // The state root must be signed by the validators of the bundle's network.
+        assert!(
+            validate_bundles_state_root(&bundles, TEST_STATE_ROOT_HASH, |network| Err(
+                TrustchainSovrinError::NetworkNotConfigured(network.to_string())
+            ))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_verify_bundle_root_timestamp() {
+        let root = "did:sov:BF4pE6ugcqNWt5kpJUE1Ro";
+        let pool = TestPool::new(4);
+        let validators = ValidatorKeys::from_genesis(&pool.genesis()).unwrap();
+        let bundle = Arc::new(signed_test_bundle(&pool));
+        assert!(
+            verify_bundle_root_timestamp(root, TEST_NYM_TXN_TIME, bundle.clone(), &validators)
+                .is_ok()
+        );
+        assert!(matches!(
+            verify_bundle_root_timestamp(root, TEST_NYM_TXN_TIME + 1, bundle.clone(), &validators),
+            Err(VerifierError::InvalidRootTimestamp(_, TEST_NYM_TXN_TIME))
+        ));
+
+        // The state root of the root's own bundle must be signed by the validators of its
+        // network, whatever other bundles are cached.
+        let other_validators = ValidatorKeys::from_genesis(TEST_GENESIS_TRANSACTIONS).unwrap();
+        assert!(matches!(
+            verify_bundle_root_timestamp(root, TEST_NYM_TXN_TIME, bundle, &other_validators),
+            Err(VerifierError::InvalidStateProof(_))
+        ));
+        assert!(matches!(
+            verify_bundle_root_timestamp(
+                root,
+                TEST_NYM_TXN_TIME,
+                Arc::new(test_bundle()),
+                &validators
+            ),
+            Err(VerifierError::InvalidStateProof(_))
+        ));
+    }
+
+    #[test]
+    fn test_cached_bundle() {
+        let did = "did:sov:BF4pE6ugcqNWt5kpJUE1Ro";
+        let bundles = Mutex::new(HashMap::from([(did.to_string(), Arc::new(test_bundle()))]));
+        let signed_at = StateProof::from_reply(TEST_GET_NYM_REPLY.as_bytes())
+            .unwrap()
+            .multi_signature
+            .value
+            .timestamp;
+        assert!(cached_bundle(&bundles, did, DEFAULT_MAX_BUNDLE_AGE, signed_at).is_some());
+        // A bundle older than the maximum age is expired.
+        assert!(cached_bundle(
+            &bundles,
+            did,
+            DEFAULT_MAX_BUNDLE_AGE,
+            signed_at + DEFAULT_MAX_BUNDLE_AGE + 1
+        )
+        .is_none());
+        assert!(
+            cached_bundle(&bundles, "did:sov:other", DEFAULT_MAX_BUNDLE_AGE, signed_at).is_none()
+        );
+    }
+
+    #[test]
+    fn test_bundle_serialization() {
+        let bundle = test_bundle();
+        let serialized = serde_json::to_string(&bundle).unwrap();
+        let deserialized: VerificationBundle = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.ledger_reply, bundle.ledger_reply);
+        assert_eq!(deserialized.attrib_reply, bundle.attrib_reply);
+        assert_eq!(deserialized.endpoint_reply, bundle.endpoint_reply);
+    }
+
+    #[test]
+    fn test_bundle_resolve() {
+        let (doc, _) = test_bundle().resolve().unwrap();
+        assert_eq!(doc.id, "did:indy:sovrin:BF4pE6ugcqNWt5kpJUE1Ro");
+        assert_eq!(
+            doc.controller,
+            Some(OneOrMany::One(TEST_CONTROLLER_DID.to_string()))
+        );
+        assert_eq!(doc.get_endpoints().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_bundle_verify() {
+        let did = "did:sov:BF4pE6ugcqNWt5kpJUE1Ro";
+        // The fixture replies are not signed by the validators.
+        let validators = ValidatorKeys::from_genesis(TEST_GENESIS_TRANSACTIONS).unwrap();
+        assert!(matches!(
+            test_bundle().verify(did, &validators, DEFAULT_MAX_BUNDLE_AGE),
+            Err(TrustchainSovrinError::InvalidStateProof(_))
+        ));
+
+        // Signed by a quorum of the pool, the bundle is valid while its state proofs are fresh.
+        // The GET_NYM reply is the oldest and the GET_ATTRIB endpoint reply the latest.
+        let pool = TestPool::new(4);
+        let validators = ValidatorKeys::from_genesis(&pool.genesis()).unwrap();
+        let (nym_signed_at, latest) = (1585581460, 1591358125);
+        let bundle = signed_test_bundle(&pool)
+            .verify_at(did, &validators, latest - nym_signed_at, latest)
+            .unwrap();
+        assert_eq!(bundle.did_doc.id, "did:indy:sovrin:BF4pE6ugcqNWt5kpJUE1Ro");
+        assert!(matches!(
+            signed_test_bundle(&pool).verify_at(did, &validators, latest - nym_signed_at - 1, latest),
+            Err(TrustchainSovrinError::StaleStateProof(signed_at, _)) if signed_at == nym_signed_at
+        ));
+        assert!(matches!(
+            signed_test_bundle(&pool).verify(did, &validators, DEFAULT_MAX_BUNDLE_AGE),
+            Err(TrustchainSovrinError::StaleStateProof(
+                _,
+                DEFAULT_MAX_BUNDLE_AGE
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_bundle_resolver_network_not_configured() {
+        let resolver = BundleResolver::new("http://127.0.0.1:8081/");
+        assert!(matches!(
+            resolver
+                .verification_bundle("did:sov:BF4pE6ugcqNWt5kpJUE1Ro")
+                .await,
+            Err(TrustchainSovrinError::NetworkNotConfigured(_))
+        ));
+        let verifier = TrustchainVerifier::with_endpoint(resolver);
+        assert!(matches!(
+            verifier
+                .verification_bundle("did:sov:BF4pE6ugcqNWt5kpJUE1Ro")
+                .await,
+            Err(VerifierError::ErrorFetchingVerificationMaterial(_, _))
+        ));
+    }
+}